@@ -0,0 +1,33 @@
+#!/bin/sh
+# Genera los paquetes de mapas que se incluyen en el instalador (src-tauri/mapas):
+#   paises.geojson     Países de Natural Earth 1:110m (dominio público)
+#   mx-estados.geojson Estados del Marco Geoestadístico del INEGI (00ent.shp)
+#   base.mbtiles       Mapa base vectorial generado a partir de los países
+#
+# Uso: scripts/preparar-mapas.sh RUTA/A/00ent.shp
+# Requiere curl, ogr2ogr (GDAL) y tippecanoe.
+set -eu
+
+DESTINO="$(dirname "$0")/../src-tauri/mapas"
+PAISES_URL="https://raw.githubusercontent.com/nvkelso/natural-earth-vector/master/geojson/ne_110m_admin_0_countries.geojson"
+
+if [ $# -ne 1 ]; then
+    echo "Uso: $0 RUTA/A/00ent.shp (capa de entidades del Marco Geoestadístico del INEGI)" >&2
+    exit 1
+fi
+
+for herramienta in curl ogr2ogr tippecanoe; do
+    command -v "$herramienta" >/dev/null || {
+        echo "Falta $herramienta" >&2
+        exit 1
+    }
+done
+
+curl -fsSL "$PAISES_URL" -o "$DESTINO/paises.geojson"
+
+# El marco del INEGI viene en Lambert cónica; el paquete debe ir en WGS 84
+ogr2ogr -f GeoJSON -t_srs EPSG:4326 -lco COORDINATE_PRECISION=4 \
+    -select CVE_ENT,NOMGEO "$DESTINO/mx-estados.geojson" "$1"
+
+tippecanoe --force -o "$DESTINO/base.mbtiles" -Z0 -z6 -l paises \
+    --attribution "Natural Earth" "$DESTINO/paises.geojson"
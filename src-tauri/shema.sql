@@ -14,6 +14,7 @@ CREATE TABLE IF NOT EXISTS global_datasets (
                                                nombre TEXT NOT NULL,
                                                tipo TEXT NOT NULL, -- 'grafico', 'tabla', 'kpi'
                                                columnas TEXT NOT NULL, -- JSON array de nombres de columnas
+                                               tipos_columna TEXT NOT NULL DEFAULT '{}', -- JSON object columna -> 'texto', 'numero', 'fecha', 'booleano', 'latlon', 'geojson'
                                                created_at TEXT NOT NULL,
                                                updated_at TEXT NOT NULL
 );
@@ -27,6 +28,19 @@ CREATE TABLE IF NOT EXISTS dataset_data (
                                             FOREIGN KEY (dataset_id) REFERENCES global_datasets(id) ON DELETE CASCADE
     );
 
+-- Carpetas (anidables) para organizar boards
+CREATE TABLE IF NOT EXISTS board_folders (
+                                             id TEXT PRIMARY KEY,
+                                             user_id TEXT NOT NULL,
+                                             nombre TEXT NOT NULL,
+                                             parent_id TEXT, -- NULL = carpeta en la raíz
+                                             orden INTEGER NOT NULL DEFAULT 0,
+                                             created_at TEXT NOT NULL,
+                                             updated_at TEXT NOT NULL,
+                                             FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (parent_id) REFERENCES board_folders(id) ON DELETE CASCADE
+    );
+
 -- Tabla de boards
 CREATE TABLE IF NOT EXISTS boards (
                                       id TEXT PRIMARY KEY,
@@ -35,11 +49,43 @@ CREATE TABLE IF NOT EXISTS boards (
                                       description TEXT,
                                       icon TEXT,
                                       color TEXT,
+                                      filtros TEXT NOT NULL DEFAULT '[]', -- JSON con los filtros del board
+                                      variables TEXT NOT NULL DEFAULT '[]', -- JSON con las variables del board
+                                      folder_id TEXT REFERENCES board_folders(id) ON DELETE SET NULL,
+                                      etiquetas TEXT NOT NULL DEFAULT '[]', -- JSON array de etiquetas libres
+                                      favorito INTEGER NOT NULL DEFAULT 0,
+                                      orden INTEGER NOT NULL DEFAULT 0, -- Orden manual
+                                      abierto_at TEXT, -- Última vez que se abrió
+                                      cuadricula INTEGER, -- Celda de ajuste en px; NULL = libre
+                                      evitar_solapes INTEGER NOT NULL DEFAULT 0,
                                       created_at TEXT NOT NULL,
                                       updated_at TEXT NOT NULL,
                                       FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
     );
 
+-- Páginas (pestañas) de un board, cada una con su lienzo
+CREATE TABLE IF NOT EXISTS board_pages (
+                                           id TEXT PRIMARY KEY,
+                                           board_id TEXT NOT NULL,
+                                           nombre TEXT NOT NULL,
+                                           orden INTEGER NOT NULL DEFAULT 0,
+                                           viewport_x REAL NOT NULL DEFAULT 0, -- Desplazamiento del lienzo
+                                           viewport_y REAL NOT NULL DEFAULT 0,
+                                           zoom REAL NOT NULL DEFAULT 1,
+                                           created_at TEXT NOT NULL,
+                                           updated_at TEXT NOT NULL,
+                                           FOREIGN KEY (board_id) REFERENCES boards(id) ON DELETE CASCADE
+    );
+
+-- Grupos de paneles que se mueven y redimensionan juntos
+CREATE TABLE IF NOT EXISTS panel_groups (
+                                            id TEXT PRIMARY KEY,
+                                            board_id TEXT NOT NULL,
+                                            nombre TEXT NOT NULL,
+                                            created_at TEXT NOT NULL,
+                                            FOREIGN KEY (board_id) REFERENCES boards(id) ON DELETE CASCADE
+    );
+
 -- Tabla de paneles
 CREATE TABLE IF NOT EXISTS panels (
                                       id TEXT PRIMARY KEY,
@@ -54,14 +100,322 @@ CREATE TABLE IF NOT EXISTS panels (
                                       activo INTEGER NOT NULL DEFAULT 0,
                                       dataset_id TEXT, -- Vinculación al dataset
                                       config TEXT, -- JSON con configuración específica del panel
+                                      page_id TEXT REFERENCES board_pages(id) ON DELETE CASCADE,
+                                      grupo_id TEXT REFERENCES panel_groups(id) ON DELETE SET NULL,
+                                      bloqueado INTEGER NOT NULL DEFAULT 0, -- No admite cambios de posición/tamaño
+                                      oculto INTEGER NOT NULL DEFAULT 0,
                                       created_at TEXT NOT NULL,
                                       updated_at TEXT NOT NULL,
                                       FOREIGN KEY (board_id) REFERENCES boards(id) ON DELETE CASCADE,
     FOREIGN KEY (dataset_id) REFERENCES global_datasets(id) ON DELETE SET NULL
     );
 
+-- Posición y tamaño alternativos de un panel por breakpoint; 'desktop' vive en panels
+CREATE TABLE IF NOT EXISTS panel_layouts (
+                                             panel_id TEXT NOT NULL,
+                                             breakpoint TEXT NOT NULL, -- 'laptop', 'tablet'
+                                             posicion_x INTEGER NOT NULL,
+                                             posicion_y INTEGER NOT NULL,
+                                             ancho INTEGER NOT NULL,
+                                             alto INTEGER NOT NULL,
+                                             updated_at TEXT NOT NULL,
+                                             PRIMARY KEY (panel_id, breakpoint),
+                                             FOREIGN KEY (panel_id) REFERENCES panels(id) ON DELETE CASCADE
+    );
+
+-- Selecciones activas por usuario en cada board (se limpian al iniciar sesión)
+CREATE TABLE IF NOT EXISTS board_selections (
+                                                board_id TEXT NOT NULL,
+                                                user_id TEXT NOT NULL,
+                                                panel_id TEXT NOT NULL,
+                                                valores TEXT NOT NULL, -- JSON [{columna, valor}]
+                                                updated_at TEXT NOT NULL,
+                                                PRIMARY KEY (board_id, user_id, panel_id),
+                                                FOREIGN KEY (board_id) REFERENCES boards(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (panel_id) REFERENCES panels(id) ON DELETE CASCADE
+    );
+
+-- Preferencias de cada usuario al abrir un board; el viewport vive en cada página
+-- y la cuadrícula en las reglas de layout del board
+CREATE TABLE IF NOT EXISTS board_preferences (
+                                                 board_id TEXT NOT NULL,
+                                                 user_id TEXT NOT NULL,
+                                                 page_id TEXT REFERENCES board_pages(id) ON DELETE SET NULL, -- Última página abierta
+                                                 panel_seleccionado_id TEXT REFERENCES panels(id) ON DELETE SET NULL,
+                                                 mostrar_minimapa INTEGER NOT NULL DEFAULT 1,
+                                                 updated_at TEXT NOT NULL,
+                                                 PRIMARY KEY (board_id, user_id),
+                                                 FOREIGN KEY (board_id) REFERENCES boards(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+    );
+
+-- Contenido Markdown de los paneles de notas
+CREATE TABLE IF NOT EXISTS panel_notes (
+                                           panel_id TEXT PRIMARY KEY,
+                                           contenido TEXT NOT NULL DEFAULT '',
+                                           created_at TEXT NOT NULL,
+                                           updated_at TEXT NOT NULL, -- Control de concurrencia al guardar
+                                           FOREIGN KEY (panel_id) REFERENCES panels(id) ON DELETE CASCADE
+    );
+
+-- Versiones anteriores de cada nota
+CREATE TABLE IF NOT EXISTS panel_note_revisions (
+                                                    id TEXT PRIMARY KEY,
+                                                    panel_id TEXT NOT NULL,
+                                                    user_id TEXT,
+                                                    contenido TEXT NOT NULL,
+                                                    created_at TEXT NOT NULL,
+                                                    FOREIGN KEY (panel_id) REFERENCES panels(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE SET NULL
+    );
+
+-- Imágenes incrustadas en las notas; el fichero vive en el directorio de datos de la app
+CREATE TABLE IF NOT EXISTS panel_note_attachments (
+                                                      id TEXT PRIMARY KEY,
+                                                      panel_id TEXT NOT NULL,
+                                                      nombre TEXT NOT NULL,
+                                                      mime TEXT NOT NULL,
+                                                      tamano INTEGER NOT NULL, -- Bytes
+                                                      ruta TEXT NOT NULL,
+                                                      created_at TEXT NOT NULL,
+                                                      FOREIGN KEY (panel_id) REFERENCES panels(id) ON DELETE CASCADE
+    );
+
+-- Eventos de calendario; las fechas van en hora local
+CREATE TABLE IF NOT EXISTS calendar_events (
+                                               id TEXT PRIMARY KEY,
+                                               user_id TEXT NOT NULL,
+                                               board_id TEXT,
+                                               uid TEXT, -- UID del .ics de origen, para no duplicar al reimportar
+                                               titulo TEXT NOT NULL,
+                                               descripcion TEXT,
+                                               inicio TEXT NOT NULL, -- 'YYYY-MM-DDTHH:MM:SS' o 'YYYY-MM-DD' si es de día completo
+                                               fin TEXT,
+                                               todo_el_dia INTEGER NOT NULL DEFAULT 0,
+                                               recurrencia TEXT, -- RRULE
+                                               excepciones TEXT NOT NULL DEFAULT '[]', -- JSON con los inicios de ocurrencias anuladas
+                                               color TEXT,
+                                               created_at TEXT NOT NULL,
+                                               updated_at TEXT NOT NULL,
+                                               FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (board_id) REFERENCES boards(id) ON DELETE CASCADE
+    );
+
+-- Elementos de los paneles de lista
+CREATE TABLE IF NOT EXISTS panel_list_items (
+                                                id TEXT PRIMARY KEY,
+                                                panel_id TEXT NOT NULL,
+                                                texto TEXT NOT NULL,
+                                                completado INTEGER NOT NULL DEFAULT 0,
+                                                fecha_limite TEXT, -- 'YYYY-MM-DD' o 'YYYY-MM-DDTHH:MM:SS' en hora local
+                                                asignado_id TEXT,
+                                                prioridad TEXT NOT NULL DEFAULT 'media', -- 'baja', 'media', 'alta'
+                                                orden INTEGER NOT NULL DEFAULT 0,
+                                                completado_at TEXT,
+                                                created_at TEXT NOT NULL,
+                                                updated_at TEXT NOT NULL,
+                                                FOREIGN KEY (panel_id) REFERENCES panels(id) ON DELETE CASCADE,
+    FOREIGN KEY (asignado_id) REFERENCES users(id) ON DELETE SET NULL
+    );
+
+-- Tabla de reglas de alerta sobre datasets
+CREATE TABLE IF NOT EXISTS alert_rules (
+                                           id TEXT PRIMARY KEY,
+                                           dataset_id TEXT NOT NULL,
+                                           nombre TEXT NOT NULL,
+                                           columna TEXT, -- NULL solo para 'count'
+                                           agregacion TEXT NOT NULL, -- 'avg', 'sum', 'min', 'max', 'count'
+                                           operador TEXT NOT NULL, -- '>', '>=', '<', '<=', '=', '!='
+                                           umbral REAL NOT NULL,
+                                           ventana_minutos INTEGER, -- NULL = todas las filas
+                                           filtro TEXT NOT NULL DEFAULT '{}', -- JSON {columna: valor}
+                                           silenciada INTEGER NOT NULL DEFAULT 0,
+                                           created_at TEXT NOT NULL,
+                                           updated_at TEXT NOT NULL,
+                                           FOREIGN KEY (dataset_id) REFERENCES global_datasets(id) ON DELETE CASCADE
+    );
+
+-- Tabla de disparos y resoluciones de alertas
+CREATE TABLE IF NOT EXISTS alert_events (
+                                            id TEXT PRIMARY KEY,
+                                            rule_id TEXT NOT NULL,
+                                            estado TEXT NOT NULL, -- 'activa', 'resuelta'
+                                            valor REAL,
+                                            umbral REAL NOT NULL, -- Umbral vigente al disparar
+                                            reconocida INTEGER NOT NULL DEFAULT 0,
+                                            disparada_at TEXT NOT NULL,
+                                            resuelta_at TEXT,
+                                            reconocida_at TEXT,
+                                            FOREIGN KEY (rule_id) REFERENCES alert_rules(id) ON DELETE CASCADE
+    );
+
+-- Tabla de reportes programados de boards
+CREATE TABLE IF NOT EXISTS report_schedules (
+                                                id TEXT PRIMARY KEY,
+                                                user_id TEXT NOT NULL,
+                                                board_id TEXT NOT NULL,
+                                                nombre TEXT NOT NULL,
+                                                formato TEXT NOT NULL, -- 'svg', 'png', 'pdf'
+                                                programacion TEXT NOT NULL, -- Expresión cron de cinco campos
+                                                directorio TEXT NOT NULL, -- Carpeta donde se escriben los archivos
+                                                activo INTEGER NOT NULL DEFAULT 1,
+                                                ultima_ejecucion TEXT,
+                                                proxima_ejecucion TEXT, -- UTC; NULL si la programación no vuelve a cumplirse
+                                                created_at TEXT NOT NULL,
+                                                updated_at TEXT NOT NULL,
+                                                FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+                                                FOREIGN KEY (board_id) REFERENCES boards(id) ON DELETE CASCADE
+    );
+
+-- Historial de ejecuciones de reportes programados
+CREATE TABLE IF NOT EXISTS report_runs (
+                                           id TEXT PRIMARY KEY,
+                                           schedule_id TEXT NOT NULL,
+                                           estado TEXT NOT NULL, -- 'exito', 'error'
+                                           ruta TEXT, -- Archivo generado, si hubo éxito
+                                           mensaje TEXT, -- Error, si falló
+                                           manual INTEGER NOT NULL DEFAULT 0, -- Lanzada con run_report_schedule
+                                           iniciada_at TEXT NOT NULL,
+                                           finalizada_at TEXT NOT NULL,
+                                           FOREIGN KEY (schedule_id) REFERENCES report_schedules(id) ON DELETE CASCADE
+    );
+
+-- Índice de búsqueda de texto completo sobre boards, paneles y datasets.
+-- Lo mantienen los triggers de abajo; board_id y user_id son NULL para datasets (son globales)
+CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
+    tipo UNINDEXED, -- 'board', 'panel', 'dataset'
+    entidad_id UNINDEXED,
+    board_id UNINDEXED,
+    user_id UNINDEXED,
+    titulo,
+    contenido,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+CREATE TRIGGER IF NOT EXISTS search_boards_insert AFTER INSERT ON boards BEGIN
+    INSERT INTO search_index (tipo, entidad_id, board_id, user_id, titulo, contenido)
+    VALUES ('board', new.id, new.id, new.user_id, new.title, COALESCE(new.description, ''));
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_boards_update AFTER UPDATE OF title, description ON boards BEGIN
+    DELETE FROM search_index WHERE tipo = 'board' AND entidad_id = old.id;
+    INSERT INTO search_index (tipo, entidad_id, board_id, user_id, titulo, contenido)
+    VALUES ('board', new.id, new.id, new.user_id, new.title, COALESCE(new.description, ''));
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_boards_delete AFTER DELETE ON boards BEGIN
+    DELETE FROM search_index WHERE tipo = 'board' AND entidad_id = old.id;
+END;
+
+-- Texto indexado de cada panel: título, los textos de la config (no sus claves) y el
+-- contenido guardado en otras tablas.
+-- La vista y los triggers que la usan se recrean en cada arranque para que las bases
+-- existentes reciban los cambios; ensure_search_index reindexa los paneles si cambió
+DROP VIEW IF EXISTS search_panels;
+CREATE VIEW search_panels AS
+SELECT p.id, p.board_id, b.user_id, p.titulo,
+       COALESCE((SELECT group_concat(value, ' ')
+                 FROM json_tree(CASE WHEN json_valid(p.config) THEN p.config ELSE '{}' END)
+                 WHERE type = 'text'), '')
+           || COALESCE(' ' || (SELECT contenido FROM panel_notes n WHERE n.panel_id = p.id), '')
+           || COALESCE(' ' || (SELECT group_concat(texto, ' ') FROM panel_list_items i
+                               WHERE i.panel_id = p.id), '')
+           AS contenido
+FROM panels p
+LEFT JOIN boards b ON b.id = p.board_id;
+
+DROP TRIGGER IF EXISTS search_panels_insert;
+CREATE TRIGGER search_panels_insert AFTER INSERT ON panels BEGIN
+    INSERT INTO search_index (tipo, entidad_id, board_id, user_id, titulo, contenido)
+    SELECT 'panel', id, board_id, user_id, titulo, contenido FROM search_panels WHERE id = new.id;
+END;
+
+DROP TRIGGER IF EXISTS search_panels_update;
+CREATE TRIGGER search_panels_update AFTER UPDATE OF titulo, config, board_id ON panels BEGIN
+    DELETE FROM search_index WHERE tipo = 'panel' AND entidad_id = old.id;
+    INSERT INTO search_index (tipo, entidad_id, board_id, user_id, titulo, contenido)
+    SELECT 'panel', id, board_id, user_id, titulo, contenido FROM search_panels WHERE id = new.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_panels_delete AFTER DELETE ON panels BEGIN
+    DELETE FROM search_index WHERE tipo = 'panel' AND entidad_id = old.id;
+END;
+
+-- Las notas forman parte del texto de su panel
+DROP TRIGGER IF EXISTS search_panel_notes_insert;
+CREATE TRIGGER search_panel_notes_insert AFTER INSERT ON panel_notes BEGIN
+    DELETE FROM search_index WHERE tipo = 'panel' AND entidad_id = new.panel_id;
+    INSERT INTO search_index (tipo, entidad_id, board_id, user_id, titulo, contenido)
+    SELECT 'panel', id, board_id, user_id, titulo, contenido FROM search_panels WHERE id = new.panel_id;
+END;
+
+DROP TRIGGER IF EXISTS search_panel_notes_update;
+CREATE TRIGGER search_panel_notes_update AFTER UPDATE OF contenido ON panel_notes BEGIN
+    DELETE FROM search_index WHERE tipo = 'panel' AND entidad_id = new.panel_id;
+    INSERT INTO search_index (tipo, entidad_id, board_id, user_id, titulo, contenido)
+    SELECT 'panel', id, board_id, user_id, titulo, contenido FROM search_panels WHERE id = new.panel_id;
+END;
+
+DROP TRIGGER IF EXISTS search_panel_notes_delete;
+CREATE TRIGGER search_panel_notes_delete AFTER DELETE ON panel_notes BEGIN
+    DELETE FROM search_index WHERE tipo = 'panel' AND entidad_id = old.panel_id;
+    INSERT INTO search_index (tipo, entidad_id, board_id, user_id, titulo, contenido)
+    SELECT 'panel', id, board_id, user_id, titulo, contenido FROM search_panels WHERE id = old.panel_id;
+END;
+
+-- Y también los elementos de las listas
+DROP TRIGGER IF EXISTS search_panel_list_items_insert;
+CREATE TRIGGER search_panel_list_items_insert AFTER INSERT ON panel_list_items BEGIN
+    DELETE FROM search_index WHERE tipo = 'panel' AND entidad_id = new.panel_id;
+    INSERT INTO search_index (tipo, entidad_id, board_id, user_id, titulo, contenido)
+    SELECT 'panel', id, board_id, user_id, titulo, contenido FROM search_panels WHERE id = new.panel_id;
+END;
+
+DROP TRIGGER IF EXISTS search_panel_list_items_update;
+CREATE TRIGGER search_panel_list_items_update AFTER UPDATE OF texto, panel_id ON panel_list_items BEGIN
+    DELETE FROM search_index WHERE tipo = 'panel' AND entidad_id IN (old.panel_id, new.panel_id);
+    INSERT INTO search_index (tipo, entidad_id, board_id, user_id, titulo, contenido)
+    SELECT 'panel', id, board_id, user_id, titulo, contenido FROM search_panels
+    WHERE id IN (old.panel_id, new.panel_id);
+END;
+
+DROP TRIGGER IF EXISTS search_panel_list_items_delete;
+CREATE TRIGGER search_panel_list_items_delete AFTER DELETE ON panel_list_items BEGIN
+    DELETE FROM search_index WHERE tipo = 'panel' AND entidad_id = old.panel_id;
+    INSERT INTO search_index (tipo, entidad_id, board_id, user_id, titulo, contenido)
+    SELECT 'panel', id, board_id, user_id, titulo, contenido FROM search_panels WHERE id = old.panel_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_datasets_insert AFTER INSERT ON global_datasets BEGIN
+    INSERT INTO search_index (tipo, entidad_id, board_id, user_id, titulo, contenido)
+    VALUES ('dataset', new.id, NULL, NULL, new.nombre, new.columnas);
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_datasets_update AFTER UPDATE OF nombre, columnas ON global_datasets BEGIN
+    DELETE FROM search_index WHERE tipo = 'dataset' AND entidad_id = old.id;
+    INSERT INTO search_index (tipo, entidad_id, board_id, user_id, titulo, contenido)
+    VALUES ('dataset', new.id, NULL, NULL, new.nombre, new.columnas);
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_datasets_delete AFTER DELETE ON global_datasets BEGIN
+    DELETE FROM search_index WHERE tipo = 'dataset' AND entidad_id = old.id;
+END;
+
 -- Índices para mejorar rendimiento
 CREATE INDEX IF NOT EXISTS idx_boards_user_id ON boards(user_id);
 CREATE INDEX IF NOT EXISTS idx_panels_board_id ON panels(board_id);
 CREATE INDEX IF NOT EXISTS idx_panels_dataset_id ON panels(dataset_id);
 CREATE INDEX IF NOT EXISTS idx_dataset_data_dataset_id ON dataset_data(dataset_id);
+CREATE INDEX IF NOT EXISTS idx_alert_rules_dataset_id ON alert_rules(dataset_id);
+CREATE INDEX IF NOT EXISTS idx_alert_events_rule_id ON alert_events(rule_id);
+CREATE INDEX IF NOT EXISTS idx_report_schedules_proxima ON report_schedules(activo, proxima_ejecucion);
+CREATE INDEX IF NOT EXISTS idx_report_runs_schedule_id ON report_runs(schedule_id);
+CREATE INDEX IF NOT EXISTS idx_board_folders_user_id ON board_folders(user_id);
+CREATE INDEX IF NOT EXISTS idx_board_pages_board_id ON board_pages(board_id);
+CREATE INDEX IF NOT EXISTS idx_panel_note_revisions_panel_id ON panel_note_revisions(panel_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_panel_note_attachments_panel_id ON panel_note_attachments(panel_id);
+CREATE INDEX IF NOT EXISTS idx_calendar_events_user_id ON calendar_events(user_id, inicio);
+CREATE INDEX IF NOT EXISTS idx_calendar_events_board_id ON calendar_events(board_id);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_calendar_events_board_uid ON calendar_events(user_id, board_id, uid);
+CREATE INDEX IF NOT EXISTS idx_panel_list_items_panel_id ON panel_list_items(panel_id, orden);
\ No newline at end of file
@@ -0,0 +1,506 @@
+use crate::commands::stats::valor_numerico;
+use crate::models::{
+    alert::{AlertEvent, AlertRule, AlertRuleDb, CreateAlertRuleDto},
+    database::{AppStateHandle, DbError, DbResult},
+};
+use chrono::{Duration, Utc};
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Emitter};
+use uuid::Uuid;
+
+const AGREGACIONES: [&str; 5] = ["avg", "sum", "min", "max", "count"];
+const OPERADORES: [&str; 6] = [">", ">=", "<", "<=", "=", "!="];
+const MAX_VENTANA_MINUTOS: i64 = 366 * 24 * 60; // Un año
+
+#[tauri::command]
+pub async fn create_alert_rule(
+    state: AppStateHandle<'_>,
+    app: AppHandle,
+    dto: CreateAlertRuleDto,
+) -> DbResult<AlertRule> {
+    let pool = &state.pool;
+
+    if !AGREGACIONES.contains(&dto.agregacion.as_str()) {
+        return Err(DbError::InvalidData(format!(
+            "Agregación desconocida: {}",
+            dto.agregacion
+        )));
+    }
+    if !OPERADORES.contains(&dto.operador.as_str()) {
+        return Err(DbError::InvalidData(format!(
+            "Operador desconocido: {}",
+            dto.operador
+        )));
+    }
+    if dto.agregacion != "count" && dto.columna.is_none() {
+        return Err(DbError::InvalidData(
+            "La agregación requiere una columna".to_string(),
+        ));
+    }
+
+    if dto
+        .ventana_minutos
+        .is_some_and(|m| m <= 0 || m > MAX_VENTANA_MINUTOS)
+    {
+        return Err(DbError::InvalidData(format!(
+            "La ventana debe estar entre 1 y {} minutos",
+            MAX_VENTANA_MINUTOS
+        )));
+    }
+
+    let filtro = dto.filtro.unwrap_or(serde_json::json!({}));
+    if !filtro.is_object() {
+        return Err(DbError::InvalidData(
+            "El filtro debe ser un objeto {columna: valor}".to_string(),
+        ));
+    }
+
+    // Verificar que el dataset existe
+    let _: (String,) = sqlx::query_as("SELECT id FROM global_datasets WHERE id = ?")
+        .bind(&dto.dataset_id)
+        .fetch_optional(&**pool)
+        .await?
+        .ok_or(DbError::NotFound)?;
+
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+
+    sqlx::query(
+        r#"
+        INSERT INTO alert_rules
+        (id, dataset_id, nombre, columna, agregacion, operador, umbral, ventana_minutos, filtro, silenciada, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, 0, ?, ?)
+        "#,
+    )
+    .bind(&id)
+    .bind(&dto.dataset_id)
+    .bind(&dto.nombre)
+    .bind(&dto.columna)
+    .bind(&dto.agregacion)
+    .bind(&dto.operador)
+    .bind(dto.umbral)
+    .bind(dto.ventana_minutos)
+    .bind(filtro.to_string())
+    .bind(&now)
+    .bind(&now)
+    .execute(&**pool)
+    .await?;
+
+    // Evaluar la regla contra los datos que ya existen
+    if let Err(e) = evaluate_dataset_alerts(&app, pool, &dto.dataset_id).await {
+        log::error!(
+            "Error al evaluar alertas del dataset {}: {}",
+            dto.dataset_id,
+            e
+        );
+    }
+
+    get_alert_rule(pool, &id).await
+}
+
+#[tauri::command]
+pub async fn get_alert_rules(
+    state: AppStateHandle<'_>,
+    dataset_id: Option<String>,
+) -> DbResult<Vec<AlertRule>> {
+    let pool = &state.pool;
+
+    let rules: Vec<AlertRuleDb> = sqlx::query_as(
+        r#"
+        SELECT id, dataset_id, nombre, columna, agregacion, operador, umbral,
+               ventana_minutos, filtro, silenciada, created_at, updated_at
+        FROM alert_rules
+        WHERE ? IS NULL OR dataset_id = ?
+        ORDER BY created_at DESC
+        "#,
+    )
+    .bind(&dataset_id)
+    .bind(&dataset_id)
+    .fetch_all(&**pool)
+    .await?;
+
+    Ok(rules.into_iter().map(AlertRule::from).collect())
+}
+
+#[tauri::command]
+pub async fn delete_alert_rule(state: AppStateHandle<'_>, rule_id: String) -> DbResult<()> {
+    let pool = &state.pool;
+
+    let result = sqlx::query("DELETE FROM alert_rules WHERE id = ?")
+        .bind(&rule_id)
+        .execute(&**pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(DbError::NotFound);
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn mute_alert_rule(
+    state: AppStateHandle<'_>,
+    rule_id: String,
+    silenciada: bool,
+) -> DbResult<AlertRule> {
+    let pool = &state.pool;
+    let now = Utc::now().to_rfc3339();
+
+    let result = sqlx::query("UPDATE alert_rules SET silenciada = ?, updated_at = ? WHERE id = ?")
+        .bind(silenciada)
+        .bind(&now)
+        .bind(&rule_id)
+        .execute(&**pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(DbError::NotFound);
+    }
+
+    get_alert_rule(pool, &rule_id).await
+}
+
+#[tauri::command]
+pub async fn acknowledge_alert(
+    state: AppStateHandle<'_>,
+    alert_id: String,
+) -> DbResult<AlertEvent> {
+    let pool = &state.pool;
+    let now = Utc::now().to_rfc3339();
+
+    // Reconocer dos veces conserva la fecha del primer reconocimiento
+    sqlx::query(
+        "UPDATE alert_events SET reconocida = 1, reconocida_at = ? WHERE id = ? AND reconocida = 0",
+    )
+    .bind(&now)
+    .bind(&alert_id)
+    .execute(&**pool)
+    .await?;
+
+    get_alert_event(pool, &alert_id).await
+}
+
+#[tauri::command]
+pub async fn get_alert_events(
+    state: AppStateHandle<'_>,
+    solo_activas: Option<bool>,
+) -> DbResult<Vec<AlertEvent>> {
+    let pool = &state.pool;
+
+    let events: Vec<AlertEvent> = sqlx::query_as(
+        r#"
+        SELECT e.id, e.rule_id, r.dataset_id, r.nombre, e.estado, e.valor, e.umbral,
+               e.reconocida, e.disparada_at, e.resuelta_at, e.reconocida_at
+        FROM alert_events e
+        JOIN alert_rules r ON r.id = e.rule_id
+        WHERE ? = 0 OR e.estado = 'activa'
+        ORDER BY e.disparada_at DESC
+        "#,
+    )
+    .bind(solo_activas.unwrap_or(false))
+    .fetch_all(&**pool)
+    .await?;
+
+    Ok(events)
+}
+
+// Evalúa todas las reglas de un dataset, registrando disparos/resoluciones
+// y notificando al frontend con los eventos 'alert-fired' y 'alert-resolved'
+pub(crate) async fn evaluate_dataset_alerts(
+    app: &AppHandle,
+    pool: &SqlitePool,
+    dataset_id: &str,
+) -> DbResult<()> {
+    let rules: Vec<AlertRuleDb> = sqlx::query_as(
+        r#"
+        SELECT id, dataset_id, nombre, columna, agregacion, operador, umbral,
+               ventana_minutos, filtro, silenciada, created_at, updated_at
+        FROM alert_rules
+        WHERE dataset_id = ?
+        "#,
+    )
+    .bind(dataset_id)
+    .fetch_all(pool)
+    .await?;
+
+    for rule in rules.into_iter().map(AlertRule::from) {
+        let valor = compute_rule_value(pool, &rule).await?;
+        let cumple = valor.is_some_and(|v| compare(v, &rule.operador, rule.umbral));
+
+        let activa: Option<(String,)> =
+            sqlx::query_as("SELECT id FROM alert_events WHERE rule_id = ? AND estado = 'activa'")
+                .bind(&rule.id)
+                .fetch_optional(pool)
+                .await?;
+
+        let now = Utc::now().to_rfc3339();
+
+        match transition(cumple, activa.map(|(id,)| id)) {
+            Some(Transicion::Disparar) => {
+                let id = Uuid::new_v4().to_string();
+                sqlx::query(
+                    r#"
+                    INSERT INTO alert_events (id, rule_id, estado, valor, umbral, reconocida, disparada_at)
+                    VALUES (?, ?, 'activa', ?, ?, 0, ?)
+                    "#,
+                )
+                .bind(&id)
+                .bind(&rule.id)
+                .bind(valor)
+                .bind(rule.umbral)
+                .bind(&now)
+                .execute(pool)
+                .await?;
+
+                if !rule.silenciada {
+                    let event = get_alert_event(pool, &id).await?;
+                    if let Err(e) = app.emit("alert-fired", &event) {
+                        log::warn!("No se pudo emitir alert-fired: {}", e);
+                    }
+                }
+            }
+            Some(Transicion::Resolver(id)) => {
+                sqlx::query(
+                    "UPDATE alert_events SET estado = 'resuelta', valor = ?, resuelta_at = ? WHERE id = ?",
+                )
+                .bind(valor)
+                .bind(&now)
+                .bind(&id)
+                .execute(pool)
+                .await?;
+
+                if !rule.silenciada {
+                    let event = get_alert_event(pool, &id).await?;
+                    if let Err(e) = app.emit("alert-resolved", &event) {
+                        log::warn!("No se pudo emitir alert-resolved: {}", e);
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, PartialEq)]
+enum Transicion {
+    Disparar,
+    Resolver(String), // Id de la alerta activa
+}
+
+// Una regla dispara al empezar a cumplirse y se resuelve al dejar de hacerlo;
+// mientras sigue igual no genera eventos nuevos
+fn transition(cumple: bool, activa: Option<String>) -> Option<Transicion> {
+    match (cumple, activa) {
+        (true, None) => Some(Transicion::Disparar),
+        (false, Some(id)) => Some(Transicion::Resolver(id)),
+        _ => None,
+    }
+}
+
+// Calcula la agregación de la regla sobre las filas que cumplen ventana y filtro
+async fn compute_rule_value(pool: &SqlitePool, rule: &AlertRule) -> DbResult<Option<f64>> {
+    let desde = rule
+        .ventana_minutos
+        .map(|m| {
+            Duration::try_minutes(m)
+                .filter(|_| (1..=MAX_VENTANA_MINUTOS).contains(&m))
+                .and_then(|ventana| Utc::now().checked_sub_signed(ventana))
+                .map(|desde| desde.to_rfc3339())
+                .ok_or_else(|| DbError::InvalidData(format!("Ventana inválida: {} minutos", m)))
+        })
+        .transpose()?;
+
+    let datos_raw: Vec<(String,)> = sqlx::query_as(
+        "SELECT data FROM dataset_data WHERE dataset_id = ? AND (? IS NULL OR created_at >= ?)",
+    )
+    .bind(&rule.dataset_id)
+    .bind(&desde)
+    .bind(&desde)
+    .fetch_all(pool)
+    .await?;
+
+    let filas = datos_raw
+        .into_iter()
+        .filter_map(|(data_str,)| serde_json::from_str::<serde_json::Value>(&data_str).ok());
+    aggregate_rows(rule, filas)
+}
+
+// Agregación de la regla sobre las filas que cumplen su filtro
+fn aggregate_rows(
+    rule: &AlertRule,
+    filas: impl Iterator<Item = serde_json::Value>,
+) -> DbResult<Option<f64>> {
+    let filtro = rule.filtro.as_object();
+    let filas = filas.filter(|fila| {
+        filtro.map_or(true, |f| {
+            f.iter().all(|(col, valor)| fila.get(col) == Some(valor))
+        })
+    });
+
+    if rule.agregacion == "count" {
+        return Ok(Some(filas.count() as f64));
+    }
+
+    let columna = rule.columna.as_deref().unwrap_or_default();
+    let valores: Vec<f64> = filas
+        .filter_map(|fila| fila.get(columna).and_then(valor_numerico))
+        .collect();
+
+    if valores.is_empty() {
+        return Ok(None);
+    }
+
+    let valor = match rule.agregacion.as_str() {
+        "sum" => valores.iter().sum(),
+        "avg" => valores.iter().sum::<f64>() / valores.len() as f64,
+        "min" => valores.iter().cloned().fold(f64::INFINITY, f64::min),
+        "max" => valores.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+        otra => {
+            return Err(DbError::InvalidData(format!(
+                "Agregación desconocida: {}",
+                otra
+            )))
+        }
+    };
+
+    Ok(Some(valor))
+}
+
+fn compare(valor: f64, operador: &str, umbral: f64) -> bool {
+    match operador {
+        ">" => valor > umbral,
+        ">=" => valor >= umbral,
+        "<" => valor < umbral,
+        "<=" => valor <= umbral,
+        "=" => valor == umbral,
+        "!=" => valor != umbral,
+        _ => false,
+    }
+}
+
+async fn get_alert_rule(pool: &SqlitePool, rule_id: &str) -> DbResult<AlertRule> {
+    let rule: AlertRuleDb = sqlx::query_as(
+        r#"
+        SELECT id, dataset_id, nombre, columna, agregacion, operador, umbral,
+               ventana_minutos, filtro, silenciada, created_at, updated_at
+        FROM alert_rules
+        WHERE id = ?
+        "#,
+    )
+    .bind(rule_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(DbError::NotFound)?;
+
+    Ok(rule.into())
+}
+
+async fn get_alert_event(pool: &SqlitePool, alert_id: &str) -> DbResult<AlertEvent> {
+    let event: AlertEvent = sqlx::query_as(
+        r#"
+        SELECT e.id, e.rule_id, r.dataset_id, r.nombre, e.estado, e.valor, e.umbral,
+               e.reconocida, e.disparada_at, e.resuelta_at, e.reconocida_at
+        FROM alert_events e
+        JOIN alert_rules r ON r.id = e.rule_id
+        WHERE e.id = ?
+        "#,
+    )
+    .bind(alert_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(DbError::NotFound)?;
+
+    Ok(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn regla(agregacion: &str, operador: &str, umbral: f64) -> AlertRule {
+        AlertRule {
+            id: "r".to_string(),
+            dataset_id: "d".to_string(),
+            nombre: "Temperatura alta".to_string(),
+            columna: Some("temperatura".to_string()),
+            agregacion: agregacion.to_string(),
+            operador: operador.to_string(),
+            umbral,
+            ventana_minutos: None,
+            filtro: json!({ "sala": "A" }),
+            silenciada: false,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    // Evalúa la regla sobre las filas como lo hace evaluate_dataset_alerts
+    fn evaluar(
+        rule: &AlertRule,
+        filas: &[serde_json::Value],
+        activa: Option<&str>,
+    ) -> Option<Transicion> {
+        let valor = aggregate_rows(rule, filas.iter().cloned()).unwrap();
+        let cumple = valor.is_some_and(|v| compare(v, &rule.operador, rule.umbral));
+        transition(cumple, activa.map(String::from))
+    }
+
+    #[test]
+    fn aggregate_rows_aplica_filtro_y_agregacion() {
+        let filas = [
+            json!({ "sala": "A", "temperatura": 20 }),
+            json!({ "sala": "A", "temperatura": "30" }),
+            json!({ "sala": "B", "temperatura": 100 }),
+            json!({ "sala": "A" }),
+        ];
+        let valor =
+            |agregacion| aggregate_rows(&regla(agregacion, ">", 0.0), filas.iter().cloned());
+
+        assert_eq!(valor("avg").unwrap(), Some(25.0));
+        assert_eq!(valor("sum").unwrap(), Some(50.0));
+        assert_eq!(valor("min").unwrap(), Some(20.0));
+        assert_eq!(valor("max").unwrap(), Some(30.0));
+        assert_eq!(valor("count").unwrap(), Some(3.0));
+        // Sin valores numéricos no hay valor y la regla no se cumple
+        let vacias = [json!({ "sala": "A" })];
+        assert_eq!(
+            aggregate_rows(&regla("avg", ">", 0.0), vacias.into_iter()).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn la_regla_dispara_al_superar_el_umbral_y_se_resuelve_al_bajar() {
+        let rule = regla("avg", ">", 25.0);
+        let frio = [json!({ "sala": "A", "temperatura": 20 })];
+        let calor = [
+            json!({ "sala": "A", "temperatura": 20 }),
+            json!({ "sala": "A", "temperatura": 40 }),
+        ];
+
+        assert_eq!(evaluar(&rule, &frio, None), None);
+        assert_eq!(evaluar(&rule, &calor, None), Some(Transicion::Disparar));
+        // Mientras sigue por encima no se repite el disparo
+        assert_eq!(evaluar(&rule, &calor, Some("e1")), None);
+        assert_eq!(
+            evaluar(&rule, &frio, Some("e1")),
+            Some(Transicion::Resolver("e1".to_string()))
+        );
+        // En el umbral exacto '>' no se cumple, '>=' sí
+        let justo = [json!({ "sala": "A", "temperatura": 25 })];
+        assert_eq!(evaluar(&rule, &justo, None), None);
+        assert_eq!(
+            evaluar(&regla("avg", ">=", 25.0), &justo, None),
+            Some(Transicion::Disparar)
+        );
+        // Una regla activa cuyas filas desaparecen se resuelve
+        assert_eq!(
+            evaluar(&rule, &[], Some("e2")),
+            Some(Transicion::Resolver("e2".to_string()))
+        );
+    }
+}
@@ -74,6 +74,12 @@ pub async fn login_user(
         return Err(DbError::Unauthorized);
     }
 
+    // Las selecciones entre paneles solo duran una sesión
+    sqlx::query("DELETE FROM board_selections WHERE user_id = ?")
+        .bind(&user.id)
+        .execute(&**pool)
+        .await?;
+
     // Generar token
     let token = Uuid::new_v4().to_string();
 
@@ -1,32 +1,107 @@
+use crate::commands::filter::{
+    apply_panel_filter, load_board_filters, load_board_selections, PanelDataContext,
+};
+use crate::commands::layout::{
+    apply_breakpoint, board_layout_rules, settle_board_layout, snap_position, snap_size,
+};
+use crate::commands::list::apply_list_items;
+use crate::commands::note::apply_note_content;
+use crate::commands::page::{insert_board_page, load_board_pages, resolve_panel_page};
+use crate::commands::panel::{parse_panel_config, shift_group_panels, upgrade_panel_config};
+use crate::commands::pivot::{apply_pivot, validate_pivot_config};
+use crate::commands::search::fts_query;
+use crate::commands::stats::{apply_anomalies, apply_forecasts, apply_trend_lines};
+use crate::commands::variable::load_board_variables;
 use crate::models::{
-    board::{Board, CreateBoardDto, CreatePanelDto, Panel, PanelDb, Position, Size},
+    board::{
+        Board, BoardListQuery, CreateBoardDto, CreatePanelDto, Panel, PanelDb, Position, Size,
+        UpdatePanelDto,
+    },
     database::{AppStateHandle, DbError, DbResult},
+    panel::PanelKind,
+    pivot::PivotConfig,
 };
 use chrono::Utc;
+use sqlx::{Row, SqlitePool};
 use uuid::Uuid;
 
 #[tauri::command]
-pub async fn get_my_boards(state: AppStateHandle<'_>, user_id: String) -> DbResult<Vec<Board>> {
+pub async fn get_my_boards(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    consulta: Option<BoardListQuery>,
+) -> DbResult<Vec<Board>> {
     let pool = &state.pool;
+    let consulta = consulta.unwrap_or_default();
+
+    // Los favoritos van siempre primero; el resto según el orden pedido
+    let orden = match consulta.orden.as_deref().unwrap_or("creacion") {
+        "creacion" => "created_at DESC",
+        "actualizacion" => "updated_at DESC",
+        "titulo" => "title COLLATE NOCASE ASC",
+        "manual" => "orden ASC, created_at DESC",
+        "recientes" => "abierto_at IS NULL, abierto_at DESC, updated_at DESC",
+        otro => {
+            return Err(DbError::InvalidData(format!(
+                "Orden de boards desconocido: {}",
+                otro
+            )))
+        }
+    };
+    let texto = consulta.texto.as_deref().and_then(fts_query);
 
-    // Obtener todos los boards del usuario
-    let boards: Vec<Board> = sqlx::query_as(
+    // Obtener los boards del usuario que cumplen los filtros
+    let boards: Vec<Board> = sqlx::query_as(&format!(
         r#"
-        SELECT id, user_id, title, description, icon, color, created_at, updated_at
+        WITH RECURSIVE carpetas(id) AS (
+            SELECT ?
+            UNION ALL
+            SELECT f.id FROM board_folders f JOIN carpetas c ON f.parent_id = c.id
+            WHERE ?
+        )
+        SELECT id, user_id, title, description, icon, color, folder_id, etiquetas, favorito, orden,
+               abierto_at, cuadricula, evitar_solapes, created_at, updated_at
         FROM boards
         WHERE user_id = ?
-        ORDER BY created_at DESC
+          AND (? IS NULL OR folder_id IN (SELECT id FROM carpetas))
+          AND (? = 0 OR folder_id IS NULL)
+          AND (? = 0 OR favorito = 1)
+          AND (? IS NULL OR EXISTS (
+              SELECT 1 FROM json_each(boards.etiquetas) WHERE lower(value) = lower(?)
+          ))
+          AND (? IS NULL OR id IN (
+              SELECT board_id FROM search_index WHERE search_index MATCH ? AND user_id = ?
+          ))
+        ORDER BY favorito DESC, {}
+        LIMIT ?
         "#,
-    )
+        orden
+    ))
+    .bind(&consulta.folder_id)
+    .bind(consulta.incluir_subcarpetas)
+    .bind(&user_id)
+    .bind(&consulta.folder_id)
+    .bind(consulta.sin_carpeta)
+    .bind(consulta.solo_favoritos)
+    .bind(&consulta.etiqueta)
+    .bind(&consulta.etiqueta)
+    .bind(&texto)
+    .bind(&texto)
     .bind(&user_id)
+    .bind(consulta.limite.unwrap_or(-1))
     .fetch_all(&**pool)
     .await?;
 
     let mut result_boards = Vec::new();
 
     for mut board in boards {
+        board.filtros = load_board_filters(pool, &board.id).await?;
+        board.selecciones = load_board_selections(pool, &board.id, &user_id).await?;
+        board.variables = load_board_variables(pool, &board.id).await?;
+        board.pages = load_board_pages(pool, &board.id).await?;
+
         // Obtener paneles del board
-        let panels = get_panels_for_board(state.clone(), &board.id).await?;
+        let panels = get_panels_for_board(state.clone(), &board.id, &user_id).await?;
         board.panels = panels;
         result_boards.push(board);
     }
@@ -39,13 +114,15 @@ pub async fn get_board_by_id(
     state: AppStateHandle<'_>,
     board_id: String,
     user_id: String,
+    breakpoint: Option<String>,
 ) -> DbResult<Board> {
     let pool = &state.pool;
 
     // Obtener board verificando que pertenece al usuario
     let mut board: Board = sqlx::query_as(
         r#"
-        SELECT id, user_id, title, description, icon, color, created_at, updated_at
+        SELECT id, user_id, title, description, icon, color, folder_id, etiquetas, favorito, orden,
+               abierto_at, cuadricula, evitar_solapes, created_at, updated_at
         FROM boards
         WHERE id = ? AND user_id = ?
         "#,
@@ -56,8 +133,17 @@ pub async fn get_board_by_id(
     .await?
     .ok_or(DbError::NotFound)?;
 
-    // Obtener paneles con sus datos
-    board.panels = get_panels_for_board(state, &board_id).await?;
+    // Obtener paneles con sus datos, ya filtrados por los filtros y selecciones del board
+    board.filtros = load_board_filters(pool, &board_id).await?;
+    board.selecciones = load_board_selections(pool, &board_id, &user_id).await?;
+    board.variables = load_board_variables(pool, &board_id).await?;
+    board.pages = load_board_pages(pool, &board_id).await?;
+    board.panels = get_panels_for_board(state.clone(), &board_id, &user_id).await?;
+
+    // Posiciones del breakpoint pedido; sin breakpoint se usa el de escritorio
+    if let Some(breakpoint) = &breakpoint {
+        apply_breakpoint(pool, &mut board.panels, breakpoint).await?;
+    }
 
     Ok(board)
 }
@@ -72,6 +158,7 @@ pub async fn create_board(
 
     let id = Uuid::new_v4().to_string();
     let now = Utc::now().to_rfc3339();
+    let mut tx = pool.begin().await?;
 
     sqlx::query(
         r#"
@@ -87,10 +174,14 @@ pub async fn create_board(
     .bind(&dto.color)
     .bind(&now)
     .bind(&now)
-    .execute(&**pool)
+    .execute(&mut *tx)
     .await?;
 
-    get_board_by_id(state, id, user_id).await
+    // Todo board nace con una página
+    insert_board_page(&mut tx, &id, "Página 1").await?;
+    tx.commit().await?;
+
+    get_board_by_id(state, id, user_id, None).await
 }
 
 #[tauri::command]
@@ -102,8 +193,13 @@ pub async fn create_panel(
     let pool = &state.pool;
 
     // Verificar que el board pertenece al usuario
-    let _board: Board = sqlx::query_as(
-        "SELECT id, user_id, title, description, icon, color, created_at, updated_at FROM boards WHERE id = ? AND user_id = ?"
+    let board: Board = sqlx::query_as(
+        r#"
+        SELECT id, user_id, title, description, icon, color, folder_id, etiquetas, favorito, orden,
+               abierto_at, cuadricula, evitar_solapes, created_at, updated_at
+        FROM boards
+        WHERE id = ? AND user_id = ?
+        "#
     )
         .bind(&dto.board_id)
         .bind(&user_id)
@@ -113,45 +209,206 @@ pub async fn create_panel(
 
     let id = Uuid::new_v4().to_string();
     let now = Utc::now().to_rfc3339();
-    let config = dto.config.unwrap_or(serde_json::json!({}));
+    let mut config = upgrade_panel_config(dto.tipo, dto.config.unwrap_or(serde_json::json!({})));
+    // Los elementos de una lista se gestionan con sus propios comandos
+    if dto.tipo == PanelKind::Lista {
+        if let Some(config) = config.as_object_mut() {
+            config.remove("items");
+        }
+    }
+    validate_panel_config(dto.tipo, &config)?;
+    let page_id = resolve_panel_page(pool, &dto.board_id, dto.page_id.as_deref()).await?;
+    let posicion = snap_position(dto.posicion, board.cuadricula);
+    let tamano = snap_size(dto.tamano, board.cuadricula);
+
+    let mut tx = pool.begin().await?;
 
     sqlx::query(
         r#"
         INSERT INTO panels
-        (id, board_id, tipo, titulo, posicion_x, posicion_y, ancho, alto, z_index, activo, dataset_id, config, created_at, updated_at)
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        (id, board_id, page_id, tipo, titulo, posicion_x, posicion_y, ancho, alto, z_index, activo, dataset_id, config, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         "#
     )
         .bind(&id)
         .bind(&dto.board_id)
-        .bind(&dto.tipo)
+        .bind(&page_id)
+        .bind(dto.tipo.as_str())
         .bind(&dto.titulo)
-        .bind(dto.posicion.x)
-        .bind(dto.posicion.y)
-        .bind(dto.tamano.width)
-        .bind(dto.tamano.height)
+        .bind(posicion.x)
+        .bind(posicion.y)
+        .bind(tamano.width)
+        .bind(tamano.height)
         .bind(dto.z_index)
         .bind(0)
         .bind(&dto.dataset_id)
         .bind(config.to_string())
         .bind(&now)
         .bind(&now)
-        .execute(&**pool)
+        .execute(&mut *tx)
         .await?;
 
-    get_panel_with_data(state, &id).await
+    if board.evitar_solapes {
+        settle_board_layout(&mut tx, &dto.board_id, &id, board.cuadricula).await?;
+    }
+
+    tx.commit().await?;
+
+    get_panel_with_data(state, &id, &user_id).await
+}
+
+#[tauri::command]
+pub async fn update_panel(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    panel_id: String,
+    dto: UpdatePanelDto,
+) -> DbResult<Panel> {
+    let pool = &state.pool;
+
+    // Verificar que el panel pertenece a un board del usuario
+    let actual: PanelDb = sqlx::query_as(
+        r#"
+        SELECT p.id, p.board_id, p.tipo, p.titulo, p.posicion_x, p.posicion_y, p.ancho, p.alto,
+               p.z_index, p.activo, p.dataset_id, p.config, p.page_id, p.grupo_id, p.bloqueado,
+               p.oculto, p.created_at, p.updated_at
+        FROM panels p
+        JOIN boards b ON b.id = p.board_id
+        WHERE p.id = ? AND b.user_id = ?
+        "#,
+    )
+    .bind(&panel_id)
+    .bind(&user_id)
+    .fetch_optional(&**pool)
+    .await?
+    .ok_or(DbError::Unauthorized)?;
+
+    let tipo = PanelKind::try_from(actual.tipo.clone())?;
+    let mut dto_config = dto.config.map(|c| upgrade_panel_config(tipo, c));
+    // Los elementos de una lista se gestionan con sus propios comandos
+    if tipo == PanelKind::Lista {
+        if let Some(config) = dto_config.as_mut().and_then(|c| c.as_object_mut()) {
+            config.remove("items");
+        }
+    }
+    if let Some(config) = &dto_config {
+        validate_panel_config(tipo, config)?;
+    }
+
+    // Con cuadrícula, las posiciones y tamaños recibidos se ajustan a sus celdas
+    let (cuadricula, evitar_solapes) = board_layout_rules(pool, &actual.board_id).await?;
+    let posicion = dto
+        .posicion
+        .map(|p| snap_position(p, cuadricula))
+        .unwrap_or(Position {
+            x: actual.posicion_x,
+            y: actual.posicion_y,
+        });
+    let tamano = dto
+        .tamano
+        .map(|t| snap_size(t, cuadricula))
+        .unwrap_or(Size {
+            width: actual.ancho,
+            height: actual.alto,
+        });
+    let config = dto_config.map(|c| c.to_string()).unwrap_or(actual.config);
+    let now = Utc::now().to_rfc3339();
+
+    let dx = posicion.x - actual.posicion_x;
+    let dy = posicion.y - actual.posicion_y;
+    let redimensiona = tamano.width != actual.ancho || tamano.height != actual.alto;
+    if actual.bloqueado != 0 && (dx != 0 || dy != 0 || redimensiona) {
+        return Err(DbError::InvalidData("El panel está bloqueado".to_string()));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    // Mover un panel agrupado arrastra al resto de su grupo
+    if let Some(grupo_id) = actual.grupo_id.as_deref().filter(|_| dx != 0 || dy != 0) {
+        shift_group_panels(&mut tx, grupo_id, &panel_id, dx, dy).await?;
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE panels
+        SET titulo = ?, posicion_x = ?, posicion_y = ?, ancho = ?, alto = ?, z_index = ?,
+            activo = ?, dataset_id = ?, config = ?, updated_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(dto.titulo.unwrap_or(actual.titulo))
+    .bind(posicion.x)
+    .bind(posicion.y)
+    .bind(tamano.width)
+    .bind(tamano.height)
+    .bind(dto.z_index.unwrap_or(actual.z_index))
+    .bind(dto.activo.map(i32::from).unwrap_or(actual.activo))
+    .bind(dto.dataset_id.unwrap_or(actual.dataset_id))
+    .bind(config)
+    .bind(&now)
+    .bind(&panel_id)
+    .execute(&mut *tx)
+    .await?;
+
+    if evitar_solapes && (dx != 0 || dy != 0 || redimensiona) {
+        settle_board_layout(&mut tx, &actual.board_id, &panel_id, cuadricula).await?;
+    }
+
+    tx.commit().await?;
+
+    get_panel_with_data(state, &panel_id, &user_id).await
+}
+
+// Verifica que el board pertenece al usuario
+pub(crate) async fn ensure_board_owner(
+    pool: &SqlitePool,
+    board_id: &str,
+    user_id: &str,
+) -> DbResult<()> {
+    let _: (String,) = sqlx::query_as("SELECT id FROM boards WHERE id = ? AND user_id = ?")
+        .bind(board_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(DbError::Unauthorized)?;
+
+    Ok(())
+}
+
+// Un tipo desconocido no debe impedir abrir el panel ni el resto del board
+fn panel_kind_or_unknown(panel_id: &str, tipo: String) -> PanelKind {
+    PanelKind::try_from(tipo).unwrap_or_else(|e| {
+        log::warn!("Panel {} de tipo desconocido: {}", panel_id, e);
+        PanelKind::Desconocido
+    })
+}
+
+// Valida la config según el tipo del panel y las secciones que el backend interpreta
+fn validate_panel_config(tipo: PanelKind, config: &serde_json::Value) -> DbResult<()> {
+    parse_panel_config(tipo, config)?;
+    if let Some(pivote) = config.get("pivote").filter(|p| !p.is_null()) {
+        let pivote: PivotConfig = serde_json::from_value(pivote.clone())
+            .map_err(|e| DbError::InvalidData(format!("Tabla dinámica inválida: {}", e)))?;
+        validate_pivot_config(&pivote)?;
+    }
+    Ok(())
 }
 
 // Función helper para obtener paneles de un board con sus datos
 
 
-async fn get_panels_for_board(state: AppStateHandle<'_>, board_id: &str) -> DbResult<Vec<Panel>> {
+async fn get_panels_for_board(
+    state: AppStateHandle<'_>,
+    board_id: &str,
+    user_id: &str,
+) -> DbResult<Vec<Panel>> {
     let pool = &state.pool;
 
     let rows = sqlx::query(
         r#"
     SELECT id, board_id, tipo, titulo, posicion_x, posicion_y, ancho, alto,
-           z_index, activo, dataset_id, config, created_at, updated_at
+           z_index, activo, dataset_id, config, page_id, grupo_id, bloqueado, oculto,
+           created_at, updated_at
     FROM panels
     WHERE board_id = ?
     ORDER BY z_index
@@ -176,19 +433,26 @@ async fn get_panels_for_board(state: AppStateHandle<'_>, board_id: &str) -> DbRe
             activo: row.try_get("activo")?,
             dataset_id: row.try_get("dataset_id")?,
             config: row.try_get("config")?,
+            page_id: row.try_get("page_id")?,
+            grupo_id: row.try_get("grupo_id")?,
+            bloqueado: row.try_get("bloqueado")?,
+            oculto: row.try_get("oculto")?,
             created_at: row.try_get("created_at")?,
             updated_at: row.try_get("updated_at")?,
         };
         panels_db.push(panel_db);
     }
 
+    let contexto = PanelDataContext::load(pool, board_id, user_id).await?;
     let mut panels = Vec::new();
 
     for panel_db in panels_db {
+        let tipo = panel_kind_or_unknown(&panel_db.id, panel_db.tipo);
+        let config = serde_json::from_str(&panel_db.config).unwrap_or(serde_json::json!({}));
         let mut panel = Panel {
             id: panel_db.id.clone(),
             board_id: panel_db.board_id,
-            tipo: panel_db.tipo,
+            tipo,
             titulo: panel_db.titulo,
             posicion: Position {
                 x: panel_db.posicion_x,
@@ -201,17 +465,31 @@ async fn get_panels_for_board(state: AppStateHandle<'_>, board_id: &str) -> DbRe
             z_index: panel_db.z_index,
             activo: panel_db.activo != 0,
             dataset_id: panel_db.dataset_id.clone(),
-            config: serde_json::from_str(&panel_db.config).unwrap_or(serde_json::json!({})),
+            page_id: panel_db.page_id,
+            grupo_id: panel_db.grupo_id,
+            bloqueado: panel_db.bloqueado != 0,
+            oculto: panel_db.oculto != 0,
+            config: upgrade_panel_config(tipo, config),
+            config_plantilla: None,
             data: None,
             created_at: panel_db.created_at,
             updated_at: panel_db.updated_at,
         };
 
+        apply_note_content(pool, &mut panel).await?;
+        apply_list_items(pool, &mut panel).await?;
+        contexto.apply_variables(&mut panel);
+
         // Si el panel tiene un dataset vinculado, obtener los datos
-        if let Some(dataset_id) = &panel_db.dataset_id {
-            panel.data = get_dataset_data_for_panel(state.clone(), dataset_id).await.ok();
+        if let Some(dataset_id) = &panel.dataset_id {
+            panel.data =
+                get_dataset_data_for_panel(state.clone(), dataset_id, &panel.id, &contexto)
+                    .await
+                    .ok();
         }
 
+        apply_panel_analysis(&mut panel);
+
         panels.push(panel);
     }
 
@@ -221,13 +499,18 @@ async fn get_panels_for_board(state: AppStateHandle<'_>, board_id: &str) -> DbRe
 
 
 
-async fn get_panel_with_data(state: AppStateHandle<'_>, panel_id: &str) -> DbResult<Panel> {
+pub(crate) async fn get_panel_with_data(
+    state: AppStateHandle<'_>,
+    panel_id: &str,
+    user_id: &str,
+) -> DbResult<Panel> {
     let pool = &state.pool;
 
     let panel_db: PanelDb = sqlx::query_as::<_, PanelDb>(
         r#"
         SELECT id, board_id, tipo, titulo, posicion_x, posicion_y, ancho, alto,
-               z_index, activo, dataset_id, config, created_at, updated_at
+               z_index, activo, dataset_id, config, page_id, grupo_id, bloqueado, oculto,
+               created_at, updated_at
         FROM panels
         WHERE id = ?
         "#,
@@ -237,10 +520,12 @@ async fn get_panel_with_data(state: AppStateHandle<'_>, panel_id: &str) -> DbRes
         .await?
         .ok_or(DbError::NotFound)?;
 
+    let tipo = panel_kind_or_unknown(&panel_db.id, panel_db.tipo);
+    let config = serde_json::from_str(&panel_db.config).unwrap_or_else(|_| serde_json::json!({}));
     let mut panel = Panel {
         id: panel_db.id.clone(),
         board_id: panel_db.board_id,
-        tipo: panel_db.tipo,
+        tipo,
         titulo: panel_db.titulo,
         posicion: Position {
             x: panel_db.posicion_x,
@@ -253,22 +538,56 @@ async fn get_panel_with_data(state: AppStateHandle<'_>, panel_id: &str) -> DbRes
         z_index: panel_db.z_index,
         activo: panel_db.activo != 0,
         dataset_id: panel_db.dataset_id.clone(),
-        config: serde_json::from_str(&panel_db.config).unwrap_or_else(|_| serde_json::json!({})),
+        page_id: panel_db.page_id,
+        grupo_id: panel_db.grupo_id,
+        bloqueado: panel_db.bloqueado != 0,
+        oculto: panel_db.oculto != 0,
+        config: upgrade_panel_config(tipo, config),
+        config_plantilla: None,
         data: None,
         created_at: panel_db.created_at,
         updated_at: panel_db.updated_at,
     };
 
-    if let Some(dataset_id) = panel_db.dataset_id.as_deref() {
-        panel.data = get_dataset_data_for_panel(state.clone(), dataset_id).await.ok();
+    let contexto = PanelDataContext::load(pool, &panel.board_id, user_id).await?;
+    apply_note_content(pool, &mut panel).await?;
+    apply_list_items(pool, &mut panel).await?;
+    contexto.apply_variables(&mut panel);
+
+    if let Some(dataset_id) = panel.dataset_id.as_deref() {
+        panel.data = get_dataset_data_for_panel(state.clone(), dataset_id, &panel.id, &contexto)
+            .await
+            .ok();
     }
 
+    apply_panel_analysis(&mut panel);
+
     Ok(panel)
 }
-// Helper para obtener datos del dataset
+
+// Añade a los datos del panel los análisis pedidos en su config
+fn apply_panel_analysis(panel: &mut Panel) {
+    let Some(data) = panel.data.as_mut() else {
+        return;
+    };
+
+    apply_panel_filter(&panel.config, data);
+    if panel.tipo == PanelKind::Grafico {
+        apply_trend_lines(&panel.config, data);
+        apply_forecasts(&panel.config, data);
+    }
+    if panel.tipo == PanelKind::Tabla {
+        apply_pivot(&panel.config, data);
+    }
+    apply_anomalies(&panel.config, data);
+}
+
+// Helper para obtener datos del dataset, aplicando los filtros y selecciones del board
 async fn get_dataset_data_for_panel(
     state: AppStateHandle<'_>,
     dataset_id: &str,
+    panel_id: &str,
+    contexto: &PanelDataContext,
 ) -> DbResult<serde_json::Value> {
     let pool = &state.pool;
 
@@ -296,6 +615,7 @@ async fn get_dataset_data_for_panel(
             datos.push(data);
         }
     }
+    let datos = contexto.apply(panel_id, datos, &columnas);
 
     Ok(serde_json::json!({
         "nombre": dataset.0,
@@ -0,0 +1,1071 @@
+use crate::commands::board::{ensure_board_owner, get_panel_with_data};
+use crate::commands::panel::parse_panel_config;
+use crate::commands::stats::{valor_fecha, valor_texto};
+use crate::models::{
+    calendar::{CalendarEvent, CalendarEventDto, CalendarOccurrence},
+    database::{AppStateHandle, DbError, DbResult},
+    panel::{CalendarioConfig, PanelConfig, PanelKind},
+};
+use chrono::{
+    DateTime, Datelike, Duration, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeZone,
+    Timelike, Utc, Weekday,
+};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+// Límite de ocurrencias por consulta, para que una regla diaria sin fin no bloquee la app
+const MAX_OCURRENCIAS: usize = 5000;
+const FORMATO_FECHA: &str = "%Y-%m-%d";
+const FORMATO_FECHA_HORA: &str = "%Y-%m-%dT%H:%M:%S";
+
+#[tauri::command]
+pub async fn get_calendar_events(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    board_id: Option<String>,
+) -> DbResult<Vec<CalendarEvent>> {
+    let pool = &state.pool;
+
+    load_calendar_events(pool, &user_id, board_id.as_deref()).await
+}
+
+#[tauri::command]
+pub async fn create_calendar_event(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    dto: CalendarEventDto,
+) -> DbResult<CalendarEvent> {
+    let pool = &state.pool;
+    let event = validate_calendar_event(pool, &user_id, dto).await?;
+
+    let id = insert_calendar_event(pool, &user_id, &event, None).await?;
+
+    get_calendar_event(pool, &id, &user_id).await
+}
+
+// Reemplaza todos los datos del evento
+#[tauri::command]
+pub async fn update_calendar_event(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    event_id: String,
+    dto: CalendarEventDto,
+) -> DbResult<CalendarEvent> {
+    let pool = &state.pool;
+    get_calendar_event(pool, &event_id, &user_id).await?;
+    let event = validate_calendar_event(pool, &user_id, dto).await?;
+
+    save_calendar_event(pool, &event_id, &event).await?;
+
+    get_calendar_event(pool, &event_id, &user_id).await
+}
+
+#[tauri::command]
+pub async fn delete_calendar_event(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    event_id: String,
+) -> DbResult<()> {
+    let pool = &state.pool;
+
+    let result = sqlx::query("DELETE FROM calendar_events WHERE id = ? AND user_id = ?")
+        .bind(&event_id)
+        .bind(&user_id)
+        .execute(&**pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(DbError::NotFound);
+    }
+
+    Ok(())
+}
+
+// Ocurrencias de los eventos del usuario (o de un board) que caen en [desde, hasta)
+#[tauri::command]
+pub async fn expand_calendar_events(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    desde: String,
+    hasta: String,
+    board_id: Option<String>,
+) -> DbResult<Vec<CalendarOccurrence>> {
+    let pool = &state.pool;
+    let (desde, hasta) = parse_window(&desde, &hasta)?;
+
+    let events = load_calendar_events(pool, &user_id, board_id.as_deref()).await?;
+    let mut ocurrencias = Vec::new();
+    for event in &events {
+        ocurrencias.extend(expand_event(event, desde, hasta)?);
+    }
+    sort_occurrences(&mut ocurrencias);
+
+    Ok(ocurrencias)
+}
+
+// Ocurrencias que muestra un panel de calendario: los eventos de su board
+// y las filas de su dataset según `config.columnaInicio`
+#[tauri::command]
+pub async fn get_calendar_panel_events(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    panel_id: String,
+    desde: String,
+    hasta: String,
+) -> DbResult<Vec<CalendarOccurrence>> {
+    let pool = &state.pool;
+    let (desde, hasta) = parse_window(&desde, &hasta)?;
+
+    let (board_id,): (String,) = sqlx::query_as(
+        r#"
+        SELECT p.board_id
+        FROM panels p
+        JOIN boards b ON b.id = p.board_id
+        WHERE p.id = ? AND b.user_id = ?
+        "#,
+    )
+    .bind(&panel_id)
+    .bind(&user_id)
+    .fetch_optional(&**pool)
+    .await?
+    .ok_or(DbError::Unauthorized)?;
+
+    let panel = get_panel_with_data(state.clone(), &panel_id, &user_id).await?;
+    if panel.tipo != PanelKind::Calendario {
+        return Err(DbError::InvalidData(
+            "El panel no es un panel de calendario".to_string(),
+        ));
+    }
+    let PanelConfig::Calendario(config) = parse_panel_config(panel.tipo, &panel.config)? else {
+        return Err(DbError::InvalidData(
+            "El panel no es un panel de calendario".to_string(),
+        ));
+    };
+
+    let mut ocurrencias = Vec::new();
+    if config.incluir_eventos.unwrap_or(true) {
+        let events = load_calendar_events(pool, &user_id, Some(&board_id)).await?;
+        for event in &events {
+            ocurrencias.extend(expand_event(event, desde, hasta)?);
+        }
+    }
+    if let Some(data) = &panel.data {
+        ocurrencias.extend(dataset_occurrences(&config, data, &board_id, desde, hasta));
+    }
+    sort_occurrences(&mut ocurrencias);
+
+    Ok(ocurrencias)
+}
+
+pub(crate) async fn load_calendar_events(
+    pool: &SqlitePool,
+    user_id: &str,
+    board_id: Option<&str>,
+) -> DbResult<Vec<CalendarEvent>> {
+    let events: Vec<CalendarEvent> = sqlx::query_as(
+        r#"
+        SELECT id, user_id, board_id, uid, titulo, descripcion, inicio, fin, todo_el_dia, recurrencia,
+               excepciones, color, created_at, updated_at
+        FROM calendar_events
+        WHERE user_id = ? AND (? IS NULL OR board_id = ?)
+        ORDER BY inicio
+        "#,
+    )
+    .bind(user_id)
+    .bind(board_id)
+    .bind(board_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(events)
+}
+
+// Guarda un evento ya validado y devuelve su id; `uid` es el de origen si viene de un .ics
+pub(crate) async fn insert_calendar_event(
+    pool: &SqlitePool,
+    user_id: &str,
+    event: &CalendarEventDto,
+    uid: Option<&str>,
+) -> DbResult<String> {
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+
+    sqlx::query(
+        r#"
+        INSERT INTO calendar_events
+        (id, user_id, board_id, uid, titulo, descripcion, inicio, fin, todo_el_dia, recurrencia,
+         excepciones, color, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&id)
+    .bind(user_id)
+    .bind(&event.board_id)
+    .bind(uid)
+    .bind(&event.titulo)
+    .bind(&event.descripcion)
+    .bind(&event.inicio)
+    .bind(&event.fin)
+    .bind(event.todo_el_dia)
+    .bind(&event.recurrencia)
+    .bind(serde_json::to_string(&event.excepciones).unwrap_or_else(|_| "[]".to_string()))
+    .bind(&event.color)
+    .bind(&now)
+    .bind(&now)
+    .execute(pool)
+    .await?;
+
+    Ok(id)
+}
+
+// Reemplaza los datos de un evento existente por los de `event`, ya validado
+pub(crate) async fn save_calendar_event(
+    pool: &SqlitePool,
+    event_id: &str,
+    event: &CalendarEventDto,
+) -> DbResult<()> {
+    sqlx::query(
+        r#"
+        UPDATE calendar_events
+        SET board_id = ?, titulo = ?, descripcion = ?, inicio = ?, fin = ?, todo_el_dia = ?,
+            recurrencia = ?, excepciones = ?, color = ?, updated_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(&event.board_id)
+    .bind(&event.titulo)
+    .bind(&event.descripcion)
+    .bind(&event.inicio)
+    .bind(&event.fin)
+    .bind(event.todo_el_dia)
+    .bind(&event.recurrencia)
+    .bind(serde_json::to_string(&event.excepciones).unwrap_or_else(|_| "[]".to_string()))
+    .bind(&event.color)
+    .bind(Utc::now().to_rfc3339())
+    .bind(event_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// Comprueba el evento y normaliza sus fechas y su regla de recurrencia
+pub(crate) async fn validate_calendar_event(
+    pool: &SqlitePool,
+    user_id: &str,
+    dto: CalendarEventDto,
+) -> DbResult<CalendarEventDto> {
+    let titulo = dto.titulo.trim().to_string();
+    if titulo.is_empty() {
+        return Err(DbError::InvalidData(
+            "El evento necesita un título".to_string(),
+        ));
+    }
+    if let Some(board_id) = &dto.board_id {
+        ensure_board_owner(pool, board_id, user_id).await?;
+    }
+
+    let inicio = parse_event_time(&dto.inicio, dto.todo_el_dia)?;
+    let fin = match dto.fin.as_deref().filter(|f| !f.trim().is_empty()) {
+        Some(fin) => {
+            let fin = parse_event_time(fin, dto.todo_el_dia)?;
+            if fin < inicio {
+                return Err(DbError::InvalidData(
+                    "El evento no puede terminar antes de empezar".to_string(),
+                ));
+            }
+            Some(format_event_time(fin, dto.todo_el_dia))
+        }
+        None => None,
+    };
+
+    let recurrencia = match dto.recurrencia.as_deref().filter(|r| !r.trim().is_empty()) {
+        Some(regla) => {
+            RecurrenceRule::parse(regla)?;
+            Some(normalize_rule(regla))
+        }
+        None => None,
+    };
+    let excepciones = dto
+        .excepciones
+        .iter()
+        .map(|e| {
+            parse_event_time(e, dto.todo_el_dia).map(|e| format_event_time(e, dto.todo_el_dia))
+        })
+        .collect::<DbResult<Vec<String>>>()?;
+
+    Ok(CalendarEventDto {
+        board_id: dto.board_id,
+        titulo,
+        descripcion: dto.descripcion.filter(|d| !d.trim().is_empty()),
+        inicio: format_event_time(inicio, dto.todo_el_dia),
+        fin,
+        todo_el_dia: dto.todo_el_dia,
+        recurrencia,
+        excepciones,
+        color: dto.color.filter(|c| !c.trim().is_empty()),
+    })
+}
+
+// Apariciones del evento que se solapan con [desde, hasta)
+pub(crate) fn expand_event(
+    event: &CalendarEvent,
+    desde: NaiveDateTime,
+    hasta: NaiveDateTime,
+) -> DbResult<Vec<CalendarOccurrence>> {
+    let inicio = parse_event_time(&event.inicio, event.todo_el_dia)?;
+    let duracion = event_duration(event, inicio)?;
+
+    let inicios = match &event.recurrencia {
+        Some(regla) => {
+            // También cuentan las que empezaron antes de `desde` y aún no terminaron
+            let desde_inicio = desde.checked_sub_signed(duracion).unwrap_or(desde);
+            RecurrenceRule::parse(regla)?.occurrences(inicio, desde_inicio, hasta)
+        }
+        None => vec![inicio],
+    };
+
+    let ocurrencias = inicios
+        .into_iter()
+        .filter(|i| *i < hasta && (*i + duracion > desde || *i >= desde))
+        .filter(|i| {
+            !event
+                .excepciones
+                .contains(&format_event_time(*i, event.todo_el_dia))
+        })
+        .take(MAX_OCURRENCIAS)
+        .map(|i| CalendarOccurrence {
+            event_id: Some(event.id.clone()),
+            board_id: event.board_id.clone(),
+            titulo: event.titulo.clone(),
+            inicio: format_event_time(i, event.todo_el_dia),
+            fin: event_end(event, i + duracion),
+            todo_el_dia: event.todo_el_dia,
+            color: event.color.clone(),
+            recurrente: event.recurrencia.is_some(),
+            origen: "evento".to_string(),
+        })
+        .collect();
+
+    Ok(ocurrencias)
+}
+
+// Acepta RFC 3339 (se pasa a hora local), 'YYYY-MM-DDTHH:MM[:SS]' o 'YYYY-MM-DD'.
+// En los eventos de día completo se descarta la hora.
+pub(crate) fn parse_event_time(valor: &str, todo_el_dia: bool) -> DbResult<NaiveDateTime> {
+    let valor = valor.trim();
+    let fecha = DateTime::parse_from_rfc3339(valor)
+        .map(|f| f.with_timezone(&Local).naive_local())
+        .ok()
+        .or_else(|| {
+            [
+                "%Y-%m-%dT%H:%M:%S",
+                "%Y-%m-%dT%H:%M",
+                "%Y-%m-%d %H:%M:%S",
+                "%Y-%m-%d %H:%M",
+            ]
+            .iter()
+            .find_map(|formato| NaiveDateTime::parse_from_str(valor, formato).ok())
+        })
+        .or_else(|| {
+            NaiveDate::parse_from_str(valor, FORMATO_FECHA)
+                .ok()
+                .map(|f| f.and_time(NaiveTime::MIN))
+        })
+        .ok_or_else(|| DbError::InvalidData(format!("Fecha de evento inválida: {}", valor)))?;
+
+    Ok(if todo_el_dia {
+        fecha.date().and_time(NaiveTime::MIN)
+    } else {
+        fecha.with_nanosecond(0).unwrap_or(fecha)
+    })
+}
+
+pub(crate) fn format_event_time(fecha: NaiveDateTime, todo_el_dia: bool) -> String {
+    if todo_el_dia {
+        fecha.format(FORMATO_FECHA).to_string()
+    } else {
+        fecha.format(FORMATO_FECHA_HORA).to_string()
+    }
+}
+
+// Sin fin, un evento dura el día completo o es puntual
+fn event_duration(event: &CalendarEvent, inicio: NaiveDateTime) -> DbResult<Duration> {
+    let Some(fin) = &event.fin else {
+        return Ok(if event.todo_el_dia {
+            Duration::days(1)
+        } else {
+            Duration::zero()
+        });
+    };
+
+    let fin = parse_event_time(fin, event.todo_el_dia)?;
+    // El fin de un evento de día completo incluye ese día
+    let fin = if event.todo_el_dia {
+        fin + Duration::days(1)
+    } else {
+        fin
+    };
+    Ok(fin - inicio)
+}
+
+fn event_end(event: &CalendarEvent, fin: NaiveDateTime) -> Option<String> {
+    event.fin.as_ref()?;
+    Some(if event.todo_el_dia {
+        format_event_time(fin - Duration::days(1), true)
+    } else {
+        format_event_time(fin, false)
+    })
+}
+
+fn parse_window(desde: &str, hasta: &str) -> DbResult<(NaiveDateTime, NaiveDateTime)> {
+    let desde = parse_event_time(desde, false)?;
+    let hasta = parse_event_time(hasta, false)?;
+    if hasta <= desde {
+        return Err(DbError::InvalidData(
+            "El final de la ventana debe ser posterior al inicio".to_string(),
+        ));
+    }
+    Ok((desde, hasta))
+}
+
+// Una ocurrencia por fila con fecha válida en `columnaInicio`
+fn dataset_occurrences(
+    config: &CalendarioConfig,
+    data: &serde_json::Value,
+    board_id: &str,
+    desde: NaiveDateTime,
+    hasta: NaiveDateTime,
+) -> Vec<CalendarOccurrence> {
+    let Some(columna_inicio) = &config.columna_inicio else {
+        return Vec::new();
+    };
+    let Some(filas) = data.get("datos").and_then(|d| d.as_array()) else {
+        return Vec::new();
+    };
+
+    let mut ocurrencias = Vec::new();
+    for fila in filas {
+        let Some(valor_inicio) = fila.get(columna_inicio) else {
+            continue;
+        };
+        let Some(inicio) = valor_fecha(valor_inicio) else {
+            continue;
+        };
+        // Las fechas sin hora se muestran como eventos de día completo
+        let todo_el_dia = config
+            .todo_el_dia
+            .unwrap_or_else(|| valor_inicio.as_str().is_some_and(|v| v.trim().len() == 10));
+
+        let fin = config
+            .columna_fin
+            .as_ref()
+            .and_then(|c| fila.get(c))
+            .and_then(valor_fecha)
+            .filter(|f| *f >= inicio);
+        let fin_efectivo = match fin {
+            Some(fin) if todo_el_dia => fin.date().and_time(NaiveTime::MIN) + Duration::days(1),
+            Some(fin) => fin,
+            None if todo_el_dia => inicio.date().and_time(NaiveTime::MIN) + Duration::days(1),
+            None => inicio,
+        };
+        if inicio >= hasta || (fin_efectivo <= desde && inicio < desde) {
+            continue;
+        }
+
+        let titulo = config
+            .columna_titulo
+            .as_ref()
+            .and_then(|c| fila.get(c))
+            .filter(|v| !v.is_null())
+            .map(valor_texto)
+            .unwrap_or_else(|| format_event_time(inicio, todo_el_dia));
+        let color = config
+            .columna_color
+            .as_ref()
+            .and_then(|c| fila.get(c))
+            .and_then(|v| v.as_str())
+            .map(|c| c.to_string())
+            .or_else(|| config.color.clone());
+
+        ocurrencias.push(CalendarOccurrence {
+            event_id: None,
+            board_id: Some(board_id.to_string()),
+            titulo,
+            inicio: format_event_time(inicio, todo_el_dia),
+            fin: fin.map(|f| format_event_time(f, todo_el_dia)),
+            todo_el_dia,
+            color,
+            recurrente: false,
+            origen: "dataset".to_string(),
+        });
+        if ocurrencias.len() >= MAX_OCURRENCIAS {
+            break;
+        }
+    }
+
+    ocurrencias
+}
+
+fn sort_occurrences(ocurrencias: &mut [CalendarOccurrence]) {
+    ocurrencias.sort_by(|a, b| {
+        a.inicio
+            .cmp(&b.inicio)
+            .then_with(|| a.titulo.cmp(&b.titulo))
+    });
+}
+
+async fn get_calendar_event(
+    pool: &SqlitePool,
+    event_id: &str,
+    user_id: &str,
+) -> DbResult<CalendarEvent> {
+    sqlx::query_as(
+        r#"
+        SELECT id, user_id, board_id, uid, titulo, descripcion, inicio, fin, todo_el_dia, recurrencia,
+               excepciones, color, created_at, updated_at
+        FROM calendar_events
+        WHERE id = ? AND user_id = ?
+        "#,
+    )
+    .bind(event_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(DbError::NotFound)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+// Subconjunto de RRULE (RFC 5545): FREQ, INTERVAL, COUNT, UNTIL, BYDAY (con ordinal
+// en reglas mensuales y anuales, p. ej. '-1FR'), BYMONTHDAY y BYMONTH.
+// En reglas anuales, el ordinal de BYDAY se cuenta dentro de cada mes.
+pub(crate) struct RecurrenceRule {
+    frecuencia: Frequency,
+    intervalo: u32,
+    cuenta: Option<u32>,
+    hasta: Option<NaiveDateTime>,
+    dias_semana: Vec<(Option<i32>, Weekday)>,
+    dias_mes: Vec<i32>,
+    meses: Vec<u32>,
+}
+
+impl RecurrenceRule {
+    pub(crate) fn parse(regla: &str) -> DbResult<Self> {
+        let invalida = |detalle: &str| {
+            DbError::InvalidData(format!("Regla de recurrencia inválida: {}", detalle))
+        };
+        let regla = normalize_rule(regla);
+
+        let mut frecuencia = None;
+        let mut intervalo = 1;
+        let mut cuenta = None;
+        let mut hasta = None;
+        let mut dias_semana = Vec::new();
+        let mut dias_mes = Vec::new();
+        let mut meses = Vec::new();
+
+        for parte in regla.split(';') {
+            let (clave, valor) = parte.split_once('=').ok_or_else(|| invalida(parte))?;
+            match clave {
+                "FREQ" => {
+                    frecuencia = Some(match valor {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(invalida(parte)),
+                    })
+                }
+                "INTERVAL" => {
+                    intervalo = valor
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|i| *i > 0)
+                        .ok_or_else(|| invalida(parte))?
+                }
+                "COUNT" => {
+                    cuenta = Some(
+                        valor
+                            .parse::<u32>()
+                            .ok()
+                            .filter(|c| *c > 0)
+                            .ok_or_else(|| invalida(parte))?,
+                    )
+                }
+                "UNTIL" => hasta = Some(parse_until(valor).ok_or_else(|| invalida(parte))?),
+                "BYDAY" => {
+                    for dia in valor.split(',') {
+                        dias_semana.push(parse_weekday(dia).ok_or_else(|| invalida(parte))?);
+                    }
+                }
+                "BYMONTHDAY" => {
+                    for dia in valor.split(',') {
+                        dias_mes.push(
+                            dia.parse::<i32>()
+                                .ok()
+                                .filter(|d| *d != 0 && (-31..=31).contains(d))
+                                .ok_or_else(|| invalida(parte))?,
+                        );
+                    }
+                }
+                "BYMONTH" => {
+                    for mes in valor.split(',') {
+                        meses.push(
+                            mes.parse::<u32>()
+                                .ok()
+                                .filter(|m| (1..=12).contains(m))
+                                .ok_or_else(|| invalida(parte))?,
+                        );
+                    }
+                }
+                // El inicio de semana no cambia el resultado para los casos admitidos
+                "WKST" => {}
+                _ => return Err(invalida(parte)),
+            }
+        }
+
+        let frecuencia = frecuencia.ok_or_else(|| invalida("falta FREQ"))?;
+        if cuenta.is_some() && hasta.is_some() {
+            return Err(invalida("COUNT y UNTIL no pueden ir juntos"));
+        }
+        let con_ordinal = dias_semana.iter().any(|(n, _)| n.is_some());
+        if con_ordinal && matches!(frecuencia, Frequency::Daily | Frequency::Weekly) {
+            return Err(invalida(
+                "BYDAY con ordinal solo en reglas mensuales o anuales",
+            ));
+        }
+
+        Ok(Self {
+            frecuencia,
+            intervalo,
+            cuenta,
+            hasta,
+            dias_semana,
+            dias_mes,
+            meses,
+        })
+    }
+
+    // Inicios de las ocurrencias en [desde, limite); el primero posible es `inicio`.
+    // El máximo de ocurrencias se aplica solo a las que caen en esa ventana
+    pub(crate) fn occurrences(
+        &self,
+        inicio: NaiveDateTime,
+        desde: NaiveDateTime,
+        limite: NaiveDateTime,
+    ) -> Vec<NaiveDateTime> {
+        let limite = match self.hasta {
+            Some(hasta) => limite.min(hasta + Duration::seconds(1)),
+            None => limite,
+        };
+        // El inicio del evento siempre es la primera ocurrencia
+        let mut inicios = Vec::new();
+        if inicio >= desde && inicio < limite {
+            inicios.push(inicio);
+        }
+        let mut restantes = self.cuenta.map(|c| c - 1);
+
+        // Con COUNT hay que contar desde el principio; sin él se salta al periodo de `desde`
+        let mut periodo = match self.cuenta {
+            Some(_) => 0,
+            None => self.period_containing(inicio, desde),
+        };
+        while restantes != Some(0) && inicios.len() < MAX_OCURRENCIAS {
+            let Some((comienzo, candidatos)) = self.period_candidates(inicio, periodo) else {
+                break;
+            };
+            if comienzo >= limite {
+                break;
+            }
+
+            for candidato in candidatos {
+                if candidato <= inicio {
+                    continue;
+                }
+                if candidato >= limite || restantes == Some(0) {
+                    break;
+                }
+                restantes = restantes.map(|r| r - 1);
+                if candidato >= desde {
+                    inicios.push(candidato);
+                }
+            }
+            periodo += 1;
+        }
+
+        inicios.truncate(MAX_OCURRENCIAS);
+        inicios
+    }
+
+    // Número del periodo que contiene `desde` (0 si es anterior a `inicio`)
+    fn period_containing(&self, inicio: NaiveDateTime, desde: NaiveDateTime) -> u32 {
+        if desde <= inicio {
+            return 0;
+        }
+        let (dia_inicio, dia_desde) = (inicio.date(), desde.date());
+        let periodos = match self.frecuencia {
+            Frequency::Daily => (dia_desde - dia_inicio).num_days(),
+            Frequency::Weekly => {
+                let lunes =
+                    dia_inicio - Duration::days(dia_inicio.weekday().num_days_from_monday() as i64);
+                (dia_desde - lunes).num_days() / 7
+            }
+            Frequency::Monthly => {
+                (dia_desde.year() - dia_inicio.year()) as i64 * 12 + dia_desde.month() as i64
+                    - dia_inicio.month() as i64
+            }
+            Frequency::Yearly => (dia_desde.year() - dia_inicio.year()) as i64,
+        };
+        u32::try_from(periodos / self.intervalo as i64).unwrap_or(u32::MAX)
+    }
+
+    // Comienzo del periodo `n` y sus candidatos en orden, a la hora de `inicio`
+    fn period_candidates(
+        &self,
+        inicio: NaiveDateTime,
+        n: u32,
+    ) -> Option<(NaiveDateTime, Vec<NaiveDateTime>)> {
+        let hora = inicio.time();
+        let salto = n.checked_mul(self.intervalo)?;
+
+        let (comienzo, dias) = match self.frecuencia {
+            Frequency::Daily => {
+                let dia = inicio
+                    .date()
+                    .checked_add_signed(Duration::days(salto as i64))?;
+                let valido = self.month_matches(dia)
+                    && (self.dias_semana.is_empty()
+                        || self.dias_semana.iter().any(|(_, d)| *d == dia.weekday()))
+                    && (self.dias_mes.is_empty() || self.month_day_matches(dia));
+                (dia, if valido { vec![dia] } else { Vec::new() })
+            }
+            Frequency::Weekly => {
+                let lunes =
+                    inicio.date() - Duration::days(inicio.weekday().num_days_from_monday() as i64);
+                let lunes = lunes.checked_add_signed(Duration::weeks(salto as i64))?;
+                let mut dias: Vec<NaiveDate> = if self.dias_semana.is_empty() {
+                    vec![lunes + Duration::days(inicio.weekday().num_days_from_monday() as i64)]
+                } else {
+                    self.dias_semana
+                        .iter()
+                        .map(|(_, d)| lunes + Duration::days(d.num_days_from_monday() as i64))
+                        .collect()
+                };
+                dias.retain(|d| self.month_matches(*d));
+                (lunes, dias)
+            }
+            Frequency::Monthly => {
+                let primero = inicio
+                    .date()
+                    .with_day(1)?
+                    .checked_add_months(Months::new(salto))?;
+                let dias = if self.month_matches(primero) {
+                    self.month_days(primero, inicio.day())
+                } else {
+                    Vec::new()
+                };
+                (primero, dias)
+            }
+            Frequency::Yearly => {
+                let anio = inicio.year().checked_add(salto as i32)?;
+                let primero = NaiveDate::from_ymd_opt(anio, 1, 1)?;
+                let meses = if self.meses.is_empty() {
+                    vec![inicio.month()]
+                } else {
+                    self.meses.clone()
+                };
+                let mut dias = Vec::new();
+                for mes in meses {
+                    if let Some(primero_mes) = NaiveDate::from_ymd_opt(anio, mes, 1) {
+                        dias.extend(self.month_days(primero_mes, inicio.day()));
+                    }
+                }
+                (primero, dias)
+            }
+        };
+
+        let mut candidatos: Vec<NaiveDateTime> =
+            dias.into_iter().map(|d| d.and_time(hora)).collect();
+        candidatos.sort();
+        candidatos.dedup();
+        Some((comienzo.and_time(NaiveTime::MIN), candidatos))
+    }
+
+    // Días del mes que cumplen BYMONTHDAY y BYDAY; sin ninguno, el mismo día que el inicio
+    fn month_days(&self, primero: NaiveDate, dia_inicio: u32) -> Vec<NaiveDate> {
+        let ultimo = last_day_of_month(primero);
+        let todos = (1..=ultimo).filter_map(|d| primero.with_day(d));
+
+        if self.dias_mes.is_empty() && self.dias_semana.is_empty() {
+            return primero.with_day(dia_inicio).into_iter().collect();
+        }
+
+        todos
+            .filter(|dia| self.dias_mes.is_empty() || self.month_day_matches(*dia))
+            .filter(|dia| {
+                self.dias_semana.is_empty()
+                    || self.dias_semana.iter().any(|(ordinal, semana)| {
+                        dia.weekday() == *semana
+                            && ordinal.map_or(true, |n| weekday_ordinal_matches(*dia, n, ultimo))
+                    })
+            })
+            .collect()
+    }
+
+    fn month_matches(&self, dia: NaiveDate) -> bool {
+        self.meses.is_empty() || self.meses.contains(&dia.month())
+    }
+
+    fn month_day_matches(&self, dia: NaiveDate) -> bool {
+        let ultimo = last_day_of_month(dia) as i32;
+        self.dias_mes.iter().any(|d| {
+            let d = if *d < 0 { ultimo + d + 1 } else { *d };
+            d == dia.day() as i32
+        })
+    }
+}
+
+// Forma guardada: en mayúsculas y sin el prefijo 'RRULE:'
+fn normalize_rule(regla: &str) -> String {
+    let regla = regla.trim().to_ascii_uppercase();
+    regla
+        .strip_prefix("RRULE:")
+        .unwrap_or(&regla)
+        .trim_matches(';')
+        .to_string()
+}
+
+// '1MO' es el primer lunes del mes y '-1FR' el último viernes
+fn weekday_ordinal_matches(dia: NaiveDate, ordinal: i32, ultimo: u32) -> bool {
+    if ordinal > 0 {
+        ((dia.day() - 1) / 7 + 1) as i32 == ordinal
+    } else {
+        ((ultimo - dia.day()) / 7 + 1) as i32 == -ordinal
+    }
+}
+
+fn last_day_of_month(dia: NaiveDate) -> u32 {
+    (28..=31)
+        .rev()
+        .find(|d| dia.with_day(*d).is_some())
+        .unwrap_or(28)
+}
+
+fn parse_weekday(valor: &str) -> Option<(Option<i32>, Weekday)> {
+    let valor = valor.trim();
+    if valor.len() < 2 {
+        return None;
+    }
+    let (ordinal, dia) = valor.split_at(valor.len() - 2);
+    let dia = match dia {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    };
+    let ordinal = match ordinal.trim_start_matches('+') {
+        "" => None,
+        n => Some(
+            n.parse::<i32>()
+                .ok()
+                .filter(|n| *n != 0 && (-5..=5).contains(n))?,
+        ),
+    };
+    Some((ordinal, dia))
+}
+
+// UNTIL admite 'YYYYMMDD', 'YYYYMMDDTHHMMSS' (hora local) o 'YYYYMMDDTHHMMSSZ' (UTC)
+pub(crate) fn parse_until(valor: &str) -> Option<NaiveDateTime> {
+    if let Some(utc) = valor.strip_suffix('Z') {
+        let fecha = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(
+            Utc.from_utc_datetime(&fecha)
+                .with_timezone(&Local)
+                .naive_local(),
+        );
+    }
+    NaiveDateTime::parse_from_str(valor, "%Y%m%dT%H%M%S")
+        .ok()
+        .or_else(|| {
+            // Una fecha sola incluye todo ese día
+            NaiveDate::parse_from_str(valor, "%Y%m%d")
+                .ok()
+                .and_then(|f| f.and_hms_opt(23, 59, 59))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fecha(valor: &str) -> NaiveDateTime {
+        parse_event_time(valor, false).unwrap()
+    }
+
+    fn ocurrencias(regla: &str, inicio: &str, desde: &str, hasta: &str) -> Vec<String> {
+        RecurrenceRule::parse(regla)
+            .unwrap()
+            .occurrences(fecha(inicio), fecha(desde), fecha(hasta))
+            .into_iter()
+            .map(|f| format_event_time(f, false))
+            .collect()
+    }
+
+    fn evento(inicio: &str, fin: Option<&str>, recurrencia: &str) -> CalendarEvent {
+        CalendarEvent {
+            id: "e".to_string(),
+            user_id: "u".to_string(),
+            board_id: None,
+            uid: None,
+            titulo: "Evento".to_string(),
+            descripcion: None,
+            inicio: inicio.to_string(),
+            fin: fin.map(str::to_string),
+            todo_el_dia: false,
+            recurrencia: Some(recurrencia.to_string()),
+            excepciones: Vec::new(),
+            color: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn parse_rechaza_reglas_invalidas() {
+        for regla in [
+            "INTERVAL=2",
+            "FREQ=HOURLY",
+            "FREQ=DAILY;INTERVAL=0",
+            "FREQ=DAILY;COUNT=3;UNTIL=20240101",
+            "FREQ=WEEKLY;BYDAY=1MO",
+            "FREQ=MONTHLY;BYMONTHDAY=0",
+            "FREQ=MONTHLY;BYDAY=6MO",
+            "FREQ=YEARLY;BYMONTH=13",
+            "FREQ=DAILY;BYSETPOS=1",
+        ] {
+            assert!(RecurrenceRule::parse(regla).is_err(), "{}", regla);
+        }
+        assert!(RecurrenceRule::parse("rrule:freq=weekly;byday=mo;wkst=su;").is_ok());
+    }
+
+    #[test]
+    fn semanal_con_count_cuenta_desde_el_inicio() {
+        let regla = "FREQ=WEEKLY;BYDAY=MO,WE;COUNT=4";
+        assert_eq!(
+            ocurrencias(regla, "2024-01-01T09:00", "2024-01-01", "2024-12-31"),
+            [
+                "2024-01-01T09:00:00",
+                "2024-01-03T09:00:00",
+                "2024-01-08T09:00:00",
+                "2024-01-10T09:00:00"
+            ]
+        );
+        assert_eq!(
+            ocurrencias(regla, "2024-01-01T09:00", "2024-01-05", "2024-12-31"),
+            ["2024-01-08T09:00:00", "2024-01-10T09:00:00"]
+        );
+    }
+
+    #[test]
+    fn diaria_con_intervalo_y_until() {
+        assert_eq!(
+            ocurrencias(
+                "FREQ=DAILY;INTERVAL=2;UNTIL=20240107",
+                "2024-01-01T08:30",
+                "2024-01-01",
+                "2024-02-01"
+            ),
+            [
+                "2024-01-01T08:30:00",
+                "2024-01-03T08:30:00",
+                "2024-01-05T08:30:00",
+                "2024-01-07T08:30:00"
+            ]
+        );
+    }
+
+    #[test]
+    fn mensual_y_anual_saltan_los_dias_que_no_existen() {
+        assert_eq!(
+            ocurrencias(
+                "FREQ=MONTHLY;BYDAY=-1FR",
+                "2024-01-26T10:00",
+                "2024-01-01",
+                "2024-04-01"
+            ),
+            [
+                "2024-01-26T10:00:00",
+                "2024-02-23T10:00:00",
+                "2024-03-29T10:00:00"
+            ]
+        );
+        assert_eq!(
+            ocurrencias(
+                "FREQ=MONTHLY;BYMONTHDAY=31",
+                "2024-01-31T10:00",
+                "2024-01-01",
+                "2024-06-01"
+            ),
+            [
+                "2024-01-31T10:00:00",
+                "2024-03-31T10:00:00",
+                "2024-05-31T10:00:00"
+            ]
+        );
+        assert_eq!(
+            ocurrencias(
+                "FREQ=YEARLY",
+                "2024-02-29T00:00",
+                "2024-01-01",
+                "2030-01-01"
+            ),
+            ["2024-02-29T00:00:00", "2028-02-29T00:00:00"]
+        );
+    }
+
+    #[test]
+    fn el_maximo_se_aplica_dentro_de_la_ventana() {
+        // Un evento diario antiguo sigue apareciendo en ventanas recientes
+        assert_eq!(
+            ocurrencias("FREQ=DAILY", "2000-01-01T12:00", "2024-01-01", "2024-01-04"),
+            [
+                "2024-01-01T12:00:00",
+                "2024-01-02T12:00:00",
+                "2024-01-03T12:00:00"
+            ]
+        );
+
+        let todas = ocurrencias("FREQ=DAILY", "2000-01-01T12:00", "2010-01-01", "2100-01-01");
+        assert_eq!(todas.len(), MAX_OCURRENCIAS);
+        assert_eq!(todas[0], "2010-01-01T12:00:00");
+    }
+
+    #[test]
+    fn expand_event_incluye_las_que_siguen_en_curso_y_omite_excepciones() {
+        let mut event = evento(
+            "2024-01-01T23:00",
+            Some("2024-01-02T01:00"),
+            "FREQ=DAILY;COUNT=5",
+        );
+        event.excepciones = vec!["2024-01-04T23:00:00".to_string()];
+
+        let inicios: Vec<String> = expand_event(&event, fecha("2024-01-03"), fecha("2024-01-05"))
+            .unwrap()
+            .into_iter()
+            .map(|o| o.inicio)
+            .collect();
+        // La del día 2 termina el 3 a la 01:00 y la del 4 está anulada
+        assert_eq!(inicios, ["2024-01-02T23:00:00", "2024-01-03T23:00:00"]);
+    }
+}
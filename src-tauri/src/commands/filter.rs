@@ -0,0 +1,443 @@
+use crate::commands::board::{ensure_board_owner, get_board_by_id};
+use crate::commands::stats::{valor_fecha, valor_numerico, valor_texto};
+use crate::commands::variable::{
+    load_board_variables, resolve_variables, substitute_in_text, substitute_variables,
+};
+use crate::models::{
+    board::{Board, BoardFilter, BoardSelection, Panel, SelectionValue},
+    database::{AppStateHandle, DbError, DbResult},
+};
+use chrono::{Duration, NaiveTime, Utc};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const TIPOS_FILTRO: [&str; 3] = ["valores", "rango_fechas", "rango_numerico"];
+
+#[tauri::command]
+pub async fn set_board_filters(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    board_id: String,
+    filtros: Vec<BoardFilter>,
+) -> DbResult<Vec<BoardFilter>> {
+    let pool = &state.pool;
+
+    ensure_board_owner(pool, &board_id, &user_id).await?;
+
+    let mut definidos: Vec<BoardFilter> = Vec::with_capacity(filtros.len());
+    for mut filtro in filtros {
+        if !TIPOS_FILTRO.contains(&filtro.tipo.as_str()) {
+            return Err(DbError::InvalidData(format!(
+                "Tipo de filtro desconocido: {}",
+                filtro.tipo
+            )));
+        }
+        if filtro.id.is_empty() {
+            filtro.id = Uuid::new_v4().to_string();
+        }
+        if definidos.iter().any(|f| f.id == filtro.id) {
+            return Err(DbError::InvalidData(format!(
+                "Filtro duplicado: {}",
+                filtro.id
+            )));
+        }
+        validate_filter_value(&filtro, &filtro.valor)?;
+        definidos.push(filtro);
+    }
+
+    save_board_filters(pool, &board_id, &definidos).await?;
+
+    Ok(definidos)
+}
+
+#[tauri::command]
+pub async fn set_board_filter_values(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    board_id: String,
+    valores: HashMap<String, serde_json::Value>,
+) -> DbResult<Board> {
+    let pool = &state.pool;
+
+    ensure_board_owner(pool, &board_id, &user_id).await?;
+
+    let mut filtros = load_board_filters(pool, &board_id).await?;
+    for (id, valor) in valores {
+        let filtro = filtros
+            .iter_mut()
+            .find(|f| f.id == id)
+            .ok_or_else(|| DbError::InvalidData(format!("Filtro desconocido: {}", id)))?;
+        validate_filter_value(filtro, &valor)?;
+        filtro.valor = valor;
+    }
+
+    save_board_filters(pool, &board_id, &filtros).await?;
+
+    // Devolver el board con los paneles ya filtrados
+    get_board_by_id(state, board_id, user_id, None).await
+}
+
+#[tauri::command]
+pub async fn set_board_selection(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    board_id: String,
+    panel_id: String,
+    valores: Vec<SelectionValue>,
+) -> DbResult<Board> {
+    let pool = &state.pool;
+
+    ensure_board_owner(pool, &board_id, &user_id).await?;
+
+    let _: (String,) = sqlx::query_as("SELECT id FROM panels WHERE id = ? AND board_id = ?")
+        .bind(&panel_id)
+        .bind(&board_id)
+        .fetch_optional(&**pool)
+        .await?
+        .ok_or(DbError::NotFound)?;
+
+    // Una selección vacía equivale a limpiar la del panel
+    if valores.is_empty() {
+        delete_selections(pool, &board_id, &user_id, Some(&panel_id)).await?;
+        return get_board_by_id(state, board_id, user_id, None).await;
+    }
+
+    let valores_json =
+        serde_json::to_string(&valores).map_err(|e| DbError::InvalidData(e.to_string()))?;
+    let now = Utc::now().to_rfc3339();
+
+    sqlx::query(
+        r#"
+        INSERT INTO board_selections (board_id, user_id, panel_id, valores, updated_at)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT (board_id, user_id, panel_id)
+        DO UPDATE SET valores = excluded.valores, updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(&board_id)
+    .bind(&user_id)
+    .bind(&panel_id)
+    .bind(&valores_json)
+    .bind(&now)
+    .execute(&**pool)
+    .await?;
+
+    get_board_by_id(state, board_id, user_id, None).await
+}
+
+#[tauri::command]
+pub async fn clear_board_selection(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    board_id: String,
+    panel_id: Option<String>,
+) -> DbResult<Board> {
+    let pool = &state.pool;
+
+    ensure_board_owner(pool, &board_id, &user_id).await?;
+    delete_selections(pool, &board_id, &user_id, panel_id.as_deref()).await?;
+
+    get_board_by_id(state, board_id, user_id, None).await
+}
+
+// Filtros, selecciones y variables del board con los que se calculan los datos de cada panel
+#[derive(Default)]
+pub(crate) struct PanelDataContext {
+    pub filtros: Vec<BoardFilter>,
+    pub selecciones: Vec<BoardSelection>,
+    pub variables: HashMap<String, serde_json::Value>,
+}
+
+impl PanelDataContext {
+    pub async fn load(pool: &SqlitePool, board_id: &str, user_id: &str) -> DbResult<Self> {
+        let variables = resolve_variables(&load_board_variables(pool, board_id).await?);
+
+        // Los valores de los filtros pueden referirse a variables
+        let mut filtros = load_board_filters(pool, board_id).await?;
+        for filtro in &mut filtros {
+            filtro.valor = substitute_variables(&filtro.valor, &variables);
+        }
+
+        Ok(Self {
+            filtros,
+            selecciones: load_board_selections(pool, board_id, user_id).await?,
+            variables,
+        })
+    }
+
+    // Sustituye las variables en el título, la config y el dataset del panel,
+    // conservando la config original si cambia
+    pub fn apply_variables(&self, panel: &mut Panel) {
+        if self.variables.is_empty() {
+            return;
+        }
+
+        // Una variable sola puede ser numérica o booleana; aquí siempre hace falta texto
+        panel.titulo = valor_texto(&substitute_in_text(&panel.titulo, &self.variables));
+
+        let config = substitute_variables(&panel.config, &self.variables);
+        if config != panel.config {
+            panel.config_plantilla = Some(std::mem::replace(&mut panel.config, config));
+        }
+
+        if let Some(dataset_id) = panel.dataset_id.as_deref() {
+            panel.dataset_id = Some(valor_texto(&substitute_in_text(
+                dataset_id,
+                &self.variables,
+            )));
+        }
+    }
+
+    // Filas del dataset que ve un panel concreto
+    pub fn apply(
+        &self,
+        panel_id: &str,
+        datos: Vec<serde_json::Value>,
+        columnas: &[String],
+    ) -> Vec<serde_json::Value> {
+        let datos = apply_board_filters(datos, columnas, &self.filtros);
+        apply_board_selections(datos, columnas, panel_id, &self.selecciones)
+    }
+}
+
+pub(crate) async fn load_board_selections(
+    pool: &SqlitePool,
+    board_id: &str,
+    user_id: &str,
+) -> DbResult<Vec<BoardSelection>> {
+    let filas: Vec<(String, String)> = sqlx::query_as(
+        "SELECT panel_id, valores FROM board_selections WHERE board_id = ? AND user_id = ? ORDER BY updated_at",
+    )
+    .bind(board_id)
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(filas
+        .into_iter()
+        .filter_map(|(panel_id, valores)| {
+            Some(BoardSelection {
+                panel_id,
+                valores: serde_json::from_str(&valores).ok()?,
+            })
+        })
+        .collect())
+}
+
+async fn delete_selections(
+    pool: &SqlitePool,
+    board_id: &str,
+    user_id: &str,
+    panel_id: Option<&str>,
+) -> DbResult<()> {
+    sqlx::query(
+        "DELETE FROM board_selections WHERE board_id = ? AND user_id = ? AND (? IS NULL OR panel_id = ?)",
+    )
+    .bind(board_id)
+    .bind(user_id)
+    .bind(panel_id)
+    .bind(panel_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub(crate) async fn load_board_filters(
+    pool: &SqlitePool,
+    board_id: &str,
+) -> DbResult<Vec<BoardFilter>> {
+    let filtros: Option<(String,)> = sqlx::query_as("SELECT filtros FROM boards WHERE id = ?")
+        .bind(board_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(filtros
+        .and_then(|(json,)| serde_json::from_str(&json).ok())
+        .unwrap_or_default())
+}
+
+async fn save_board_filters(
+    pool: &SqlitePool,
+    board_id: &str,
+    filtros: &[BoardFilter],
+) -> DbResult<()> {
+    let filtros_json =
+        serde_json::to_string(filtros).map_err(|e| DbError::InvalidData(e.to_string()))?;
+    let now = Utc::now().to_rfc3339();
+
+    sqlx::query("UPDATE boards SET filtros = ?, updated_at = ? WHERE id = ?")
+        .bind(&filtros_json)
+        .bind(&now)
+        .bind(board_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+fn validate_filter_value(filtro: &BoardFilter, valor: &serde_json::Value) -> DbResult<()> {
+    let valido = match (filtro.tipo.as_str(), valor) {
+        (_, serde_json::Value::Null) => true,
+        ("valores", v) => !v.is_object(),
+        ("rango_fechas", serde_json::Value::Object(rango)) => ["desde", "hasta"]
+            .iter()
+            .filter_map(|k| rango.get(*k).filter(|v| !v.is_null()))
+            .all(|v| valor_fecha(v).is_some()),
+        ("rango_numerico", serde_json::Value::Object(rango)) => ["min", "max"]
+            .iter()
+            .filter_map(|k| rango.get(*k).filter(|v| !v.is_null()))
+            .all(|v| v.is_number()),
+        _ => false,
+    };
+
+    if !valido {
+        return Err(DbError::InvalidData(format!(
+            "Valor inválido para el filtro {}",
+            filtro.etiqueta
+        )));
+    }
+    Ok(())
+}
+
+// Deja solo las filas que cumplen los filtros activos. Los filtros sobre
+// columnas que el dataset no tiene se ignoran.
+fn apply_board_filters(
+    datos: Vec<serde_json::Value>,
+    columnas: &[String],
+    filtros: &[BoardFilter],
+) -> Vec<serde_json::Value> {
+    let aplicables: Vec<&BoardFilter> = filtros
+        .iter()
+        .filter(|f| !f.valor.is_null())
+        .filter(|f| {
+            columnas.contains(&f.columna) || datos.iter().any(|fila| fila.get(&f.columna).is_some())
+        })
+        .collect();
+
+    if aplicables.is_empty() {
+        return datos;
+    }
+
+    datos
+        .into_iter()
+        .filter(|fila| aplicables.iter().all(|f| matches_filter(fila, f)))
+        .collect()
+}
+
+// Restringe los datos de un panel con su propio `config.filtro` ({columna: valor o [valores]})
+pub(crate) fn apply_panel_filter(config: &serde_json::Value, data: &mut serde_json::Value) {
+    let Some(filtro) = config.get("filtro").and_then(|f| f.as_object()) else {
+        return;
+    };
+    let Some(datos) = data.get_mut("datos").and_then(|d| d.as_array_mut()) else {
+        return;
+    };
+
+    datos.retain(|fila| {
+        filtro.iter().all(|(columna, esperado)| {
+            let Some(valor) = fila.get(columna) else {
+                return false;
+            };
+            let texto = valor_texto(valor);
+            match esperado {
+                serde_json::Value::Array(permitidos) => {
+                    permitidos.iter().any(|p| valor_texto(p) == texto)
+                }
+                unico => valor_texto(unico) == texto,
+            }
+        })
+    });
+}
+
+// Aplica a un panel las selecciones hechas en los demás paneles del board.
+// Dentro de una selección, valores de la misma columna se combinan con OR y
+// columnas distintas con AND; solo cuentan las columnas que el dataset tiene.
+fn apply_board_selections(
+    datos: Vec<serde_json::Value>,
+    columnas: &[String],
+    panel_id: &str,
+    selecciones: &[BoardSelection],
+) -> Vec<serde_json::Value> {
+    let tiene_columna = |columna: &str| {
+        columnas.iter().any(|c| c == columna)
+            || datos.iter().any(|fila| fila.get(columna).is_some())
+    };
+
+    let mut condiciones: Vec<HashMap<&str, Vec<String>>> = Vec::new();
+    for seleccion in selecciones.iter().filter(|s| s.panel_id != panel_id) {
+        let mut por_columna: HashMap<&str, Vec<String>> = HashMap::new();
+        for v in seleccion
+            .valores
+            .iter()
+            .filter(|v| tiene_columna(&v.columna))
+        {
+            por_columna
+                .entry(v.columna.as_str())
+                .or_default()
+                .push(valor_texto(&v.valor));
+        }
+        if !por_columna.is_empty() {
+            condiciones.push(por_columna);
+        }
+    }
+
+    if condiciones.is_empty() {
+        return datos;
+    }
+
+    datos
+        .into_iter()
+        .filter(|fila| {
+            condiciones.iter().all(|por_columna| {
+                por_columna.iter().all(|(columna, permitidos)| {
+                    fila.get(*columna)
+                        .is_some_and(|v| permitidos.contains(&valor_texto(v)))
+                })
+            })
+        })
+        .collect()
+}
+
+fn matches_filter(fila: &serde_json::Value, filtro: &BoardFilter) -> bool {
+    let Some(valor) = fila.get(&filtro.columna).filter(|v| !v.is_null()) else {
+        return false;
+    };
+
+    match filtro.tipo.as_str() {
+        "valores" => {
+            let texto = valor_texto(valor);
+            match &filtro.valor {
+                serde_json::Value::Array(permitidos) => {
+                    permitidos.is_empty() || permitidos.iter().any(|p| valor_texto(p) == texto)
+                }
+                unico => valor_texto(unico) == texto,
+            }
+        }
+        "rango_fechas" => {
+            let Some(fecha) = valor_fecha(valor) else {
+                return false;
+            };
+            let desde = filtro.valor.get("desde").and_then(valor_fecha);
+            // Un 'hasta' sin hora incluye todo ese día
+            let hasta = filtro.valor.get("hasta").and_then(valor_fecha).map(|h| {
+                if h.time() == NaiveTime::MIN {
+                    h + Duration::days(1) - Duration::seconds(1)
+                } else {
+                    h
+                }
+            });
+            desde.map_or(true, |d| fecha >= d) && hasta.map_or(true, |h| fecha <= h)
+        }
+        "rango_numerico" => {
+            let Some(numero) = valor_numerico(valor) else {
+                return false;
+            };
+            let min = filtro.valor.get("min").and_then(|m| m.as_f64());
+            let max = filtro.valor.get("max").and_then(|m| m.as_f64());
+            min.map_or(true, |m| numero >= m) && max.map_or(true, |m| numero <= m)
+        }
+        _ => true,
+    }
+}
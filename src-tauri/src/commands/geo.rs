@@ -0,0 +1,774 @@
+use crate::commands::alert::evaluate_dataset_alerts;
+use crate::commands::board::get_panel_with_data;
+use crate::commands::panel::parse_panel_config;
+use crate::commands::pivot::{Accumulator, AGREGACIONES};
+use crate::commands::stats::{load_column_types, load_dataset_rows, valor_numerico, valor_texto};
+use crate::models::{
+    database::{AppStateHandle, DbError, DbResult},
+    geo::{
+        GeoCluster, GeoJsonImportResult, GeoPoint, GeoPointsQuery, GeoPointsResult, GeoRegion,
+        GeoRegionsQuery, GeoRegionsResult,
+    },
+    panel::{MapaConfig, PanelConfig, PanelKind},
+};
+use chrono::Utc;
+use serde_json::Value;
+use sqlx::SqlitePool;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::path::Path;
+use tauri::AppHandle;
+use uuid::Uuid;
+
+// Columna en la que se guarda la geometría de cada feature importada
+const COLUMNA_GEOMETRIA: &str = "geometria";
+const RADIO_CLUSTER: f64 = 60.0;
+const TAMANO_TESELA: f64 = 256.0;
+const LATITUD_MERCATOR: f64 = 85.051_128_78;
+const MAX_GEOJSON: u64 = 64 * 1024 * 1024;
+
+// Importa las features de un fichero GeoJSON como filas de un dataset: las
+// propiedades pasan a columnas y la geometría a la columna `geometria`.
+// Sin `dataset_id` se crea un dataset nuevo con el nombre del fichero.
+#[tauri::command]
+pub async fn import_geojson(
+    state: AppStateHandle<'_>,
+    app: AppHandle,
+    ruta: String,
+    nombre: Option<String>,
+    dataset_id: Option<String>,
+) -> DbResult<GeoJsonImportResult> {
+    let pool = &state.pool;
+
+    let tamano = tokio::fs::metadata(&ruta)
+        .await
+        .map_err(|e| DbError::InvalidData(format!("No se pudo leer {}: {}", ruta, e)))?
+        .len();
+    if tamano > MAX_GEOJSON {
+        return Err(DbError::InvalidData(format!(
+            "{} supera el tamaño máximo de {} MB",
+            ruta,
+            MAX_GEOJSON / (1024 * 1024)
+        )));
+    }
+    let contenido = tokio::fs::read_to_string(&ruta)
+        .await
+        .map_err(|e| DbError::InvalidData(format!("No se pudo leer {}: {}", ruta, e)))?;
+    let geojson: Value = serde_json::from_str(&contenido)
+        .map_err(|e| DbError::InvalidData(format!("GeoJSON inválido: {}", e)))?;
+    let features = geojson_features(&geojson)?;
+
+    let mut resultado = GeoJsonImportResult::default();
+    let mut filas = Vec::new();
+    for feature in features {
+        let geometria = feature.get("geometry").cloned().unwrap_or(Value::Null);
+        if geometry_bbox(&geometria).is_none() {
+            resultado.omitidas += 1;
+            continue;
+        }
+
+        let mut fila = match feature.get("properties") {
+            Some(Value::Object(propiedades)) => propiedades.clone(),
+            _ => serde_json::Map::new(),
+        };
+        for columna in fila.keys() {
+            if !resultado.columnas.contains(columna) {
+                resultado.columnas.push(columna.clone());
+            }
+        }
+        fila.insert(COLUMNA_GEOMETRIA.to_string(), geometria);
+        filas.push(Value::Object(fila));
+    }
+    if filas.is_empty() {
+        return Err(DbError::InvalidData(
+            "El fichero no contiene features con geometría válida".to_string(),
+        ));
+    }
+    resultado.columnas.retain(|c| c != COLUMNA_GEOMETRIA);
+    resultado.columnas.push(COLUMNA_GEOMETRIA.to_string());
+
+    let now = Utc::now().to_rfc3339();
+    let mut tx = pool.begin().await?;
+
+    let dataset_id = match dataset_id {
+        Some(dataset_id) => {
+            let (columnas_json, tipos_json): (String, String) =
+                sqlx::query_as("SELECT columnas, tipos_columna FROM global_datasets WHERE id = ?")
+                    .bind(&dataset_id)
+                    .fetch_optional(&mut *tx)
+                    .await?
+                    .ok_or(DbError::NotFound)?;
+
+            let mut columnas: Vec<String> =
+                serde_json::from_str(&columnas_json).unwrap_or_default();
+            for columna in &resultado.columnas {
+                if !columnas.contains(columna) {
+                    columnas.push(columna.clone());
+                }
+            }
+            let mut tipos: HashMap<String, String> =
+                serde_json::from_str(&tipos_json).unwrap_or_default();
+            tipos.insert(COLUMNA_GEOMETRIA.to_string(), "geojson".to_string());
+
+            sqlx::query(
+                "UPDATE global_datasets SET columnas = ?, tipos_columna = ?, updated_at = ? WHERE id = ?",
+            )
+            .bind(serde_json::to_string(&columnas).unwrap_or_default())
+            .bind(serde_json::to_string(&tipos).unwrap_or_default())
+            .bind(&now)
+            .bind(&dataset_id)
+            .execute(&mut *tx)
+            .await?;
+
+            dataset_id
+        }
+        None => {
+            let id = Uuid::new_v4().to_string();
+            let nombre = nombre.unwrap_or_else(|| {
+                Path::new(&ruta)
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "GeoJSON".to_string())
+            });
+            let tipos = HashMap::from([(COLUMNA_GEOMETRIA, "geojson")]);
+
+            sqlx::query(
+                r#"
+                INSERT INTO global_datasets (id, nombre, tipo, columnas, tipos_columna, created_at, updated_at)
+                VALUES (?, ?, 'mapa', ?, ?, ?, ?)
+                "#,
+            )
+            .bind(&id)
+            .bind(&nombre)
+            .bind(serde_json::to_string(&resultado.columnas).unwrap_or_default())
+            .bind(serde_json::to_string(&tipos).unwrap_or_default())
+            .bind(&now)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await?;
+
+            id
+        }
+    };
+
+    for fila in &filas {
+        sqlx::query(
+            r#"
+            INSERT INTO dataset_data (id, dataset_id, data, created_at)
+            VALUES (?, ?, ?, ?)
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(&dataset_id)
+        .bind(fila.to_string())
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    if let Err(e) = evaluate_dataset_alerts(&app, pool, &dataset_id).await {
+        log::error!("Error al evaluar alertas del dataset {}: {}", dataset_id, e);
+    }
+
+    resultado.dataset_id = dataset_id;
+    resultado.filas = filas.len();
+    Ok(resultado)
+}
+
+// Puntos del panel de mapa dentro del bbox pedido. Con zoom, los puntos que
+// caen a menos de `radio` píxeles entre sí se devuelven agrupados en clusters.
+#[tauri::command]
+pub async fn query_geo_points(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    panel_id: String,
+    query: GeoPointsQuery,
+) -> DbResult<GeoPointsResult> {
+    let (config_radio, puntos, sin_ubicacion) =
+        load_map_points(state, &user_id, &panel_id, query.columna.as_deref()).await?;
+
+    let puntos: Vec<GeoPoint> = match query.bbox {
+        Some(bbox) => {
+            validate_bbox(&bbox)?;
+            puntos
+                .into_iter()
+                .filter(|p| in_bbox(&bbox, p.lat, p.lon))
+                .collect()
+        }
+        None => puntos,
+    };
+    let total = puntos.len();
+
+    let (puntos, clusters) = match query.zoom {
+        Some(zoom) => {
+            let radio = query.radio.or(config_radio).unwrap_or(RADIO_CLUSTER);
+            if !(0.0..=24.0).contains(&zoom) || radio <= 0.0 {
+                return Err(DbError::InvalidData(
+                    "El zoom debe estar entre 0 y 24 y el radio ser positivo".to_string(),
+                ));
+            }
+            cluster_points(puntos, zoom, radio)
+        }
+        None => (puntos, Vec::new()),
+    };
+
+    Ok(GeoPointsResult {
+        total,
+        sin_ubicacion,
+        puntos,
+        clusters,
+    })
+}
+
+// Agrega los puntos del panel de mapa por los polígonos de otro dataset
+#[tauri::command]
+pub async fn aggregate_geo_regions(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    panel_id: String,
+    query: GeoRegionsQuery,
+) -> DbResult<GeoRegionsResult> {
+    let agregacion = query.agregacion.as_deref().unwrap_or("count");
+    if !AGREGACIONES.contains(&agregacion) {
+        return Err(DbError::InvalidData(format!(
+            "Agregación desconocida: {}",
+            agregacion
+        )));
+    }
+    if agregacion != "count" && query.medida.is_none() {
+        return Err(DbError::InvalidData(
+            "La agregación requiere una medida".to_string(),
+        ));
+    }
+
+    let pool = state.pool.clone();
+    let (_, puntos, _) =
+        load_map_points(state, &user_id, &panel_id, query.columna.as_deref()).await?;
+
+    let (_, _, filas) = load_dataset_rows(&pool, &query.regiones_dataset_id).await?;
+    let mut regiones: Vec<(Region, Accumulator)> = filas
+        .iter()
+        .filter_map(|fila| {
+            let geometria = geometry_value(fila.get(&query.columna_region)?)?;
+            let region = Region::from_geometry(
+                fila.get(&query.columna_nombre)
+                    .map(valor_texto)
+                    .unwrap_or_default(),
+                geometria,
+            )?;
+            Some((region, Accumulator::default()))
+        })
+        .collect();
+    if regiones.is_empty() {
+        return Err(DbError::InvalidData(format!(
+            "La columna {} no contiene polígonos",
+            query.columna_region
+        )));
+    }
+
+    let mut sin_region = 0;
+    for punto in &puntos {
+        match regiones
+            .iter_mut()
+            .find(|(region, _)| region.contains(punto.lat, punto.lon))
+        {
+            Some((_, acumulado)) => acumulado.add(
+                query
+                    .medida
+                    .as_ref()
+                    .and_then(|m| punto.fila.get(m))
+                    .and_then(valor_numerico),
+            ),
+            None => sin_region += 1,
+        }
+    }
+
+    Ok(GeoRegionsResult {
+        regiones: regiones
+            .into_iter()
+            .map(|(region, acumulado)| GeoRegion {
+                region: region.nombre,
+                conteo: acumulado.registros,
+                valor: acumulado.value(agregacion),
+                geometria: region.geometria,
+            })
+            .collect(),
+        sin_region,
+    })
+}
+
+// Lee las filas del panel de mapa, ya filtradas por el board, y las ubica.
+// Devuelve el radio de agrupación de la config, los puntos y las filas sin ubicación.
+async fn load_map_points(
+    state: AppStateHandle<'_>,
+    user_id: &str,
+    panel_id: &str,
+    columna: Option<&str>,
+) -> DbResult<(Option<f64>, Vec<GeoPoint>, usize)> {
+    let pool = state.pool.clone();
+    let (config, dataset_id, filas) = load_map_panel(state, user_id, panel_id).await?;
+    let Some(columna) = columna.or(config.columna_geo.as_deref()) else {
+        return Err(DbError::InvalidData(
+            "El panel no tiene columna de ubicación".to_string(),
+        ));
+    };
+
+    let tipos = load_column_types(&pool, &dataset_id).await?;
+    let tipo = tipos.get(columna).map(String::as_str);
+
+    let mut puntos = Vec::new();
+    let mut sin_ubicacion = 0;
+    for fila in filas {
+        match fila.get(columna).and_then(|v| geo_point(v, tipo)) {
+            Some((lat, lon)) => puntos.push(GeoPoint { lat, lon, fila }),
+            None => sin_ubicacion += 1,
+        }
+    }
+
+    Ok((config.radio_cluster, puntos, sin_ubicacion))
+}
+
+// Config, dataset y filas (ya filtradas por el board) de un panel de mapa del usuario
+pub(crate) async fn load_map_panel(
+    state: AppStateHandle<'_>,
+    user_id: &str,
+    panel_id: &str,
+) -> DbResult<(MapaConfig, String, Vec<Value>)> {
+    ensure_panel_owner(&state.pool, panel_id, user_id).await?;
+
+    let panel = get_panel_with_data(state.clone(), panel_id, user_id).await?;
+    let PanelConfig::Mapa(config) = parse_panel_config(panel.tipo, &panel.config)? else {
+        return Err(DbError::InvalidData(
+            "El panel no es un panel de mapa".to_string(),
+        ));
+    };
+    let Some(dataset_id) = panel.dataset_id else {
+        return Err(DbError::InvalidData(
+            "El panel no tiene un dataset vinculado".to_string(),
+        ));
+    };
+
+    let filas = panel
+        .data
+        .as_ref()
+        .and_then(|d| d.get("datos"))
+        .and_then(|d| d.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    Ok((config, dataset_id, filas))
+}
+
+async fn ensure_panel_owner(pool: &SqlitePool, panel_id: &str, user_id: &str) -> DbResult<()> {
+    let (tipo,): (String,) = sqlx::query_as(
+        r#"
+        SELECT p.tipo
+        FROM panels p
+        JOIN boards b ON b.id = p.board_id
+        WHERE p.id = ? AND b.user_id = ?
+        "#,
+    )
+    .bind(panel_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(DbError::Unauthorized)?;
+
+    if PanelKind::try_from(tipo)? != PanelKind::Mapa {
+        return Err(DbError::InvalidData(
+            "El panel no es un panel de mapa".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// Features de un FeatureCollection, de una Feature suelta o de una geometría suelta
+fn geojson_features(geojson: &Value) -> DbResult<Vec<Value>> {
+    match geojson.get("type").and_then(|t| t.as_str()) {
+        Some("FeatureCollection") => Ok(geojson
+            .get("features")
+            .and_then(|f| f.as_array())
+            .cloned()
+            .unwrap_or_default()),
+        Some("Feature") => Ok(vec![geojson.clone()]),
+        Some(_) if geojson.get("coordinates").is_some() || geojson.get("geometries").is_some() => {
+            Ok(vec![
+                serde_json::json!({ "type": "Feature", "geometry": geojson }),
+            ])
+        }
+        _ => Err(DbError::InvalidData(
+            "El fichero no es un GeoJSON (falta \"type\")".to_string(),
+        )),
+    }
+}
+
+// Ubicación (lat, lon) de un valor según el tipo de su columna. Sin tipo
+// declarado se prueba como geometría GeoJSON y después como par lat/lon.
+pub(crate) fn geo_point(valor: &Value, tipo: Option<&str>) -> Option<(f64, f64)> {
+    match tipo {
+        Some("geojson") => geometry_point(&geometry_value(valor)?),
+        Some("latlon") => lat_lon(valor),
+        _ => geometry_value(valor)
+            .and_then(|g| geometry_point(&g))
+            .or_else(|| lat_lon(valor)),
+    }
+}
+
+// Una geometría puede venir como objeto o serializada en texto
+fn geometry_value(valor: &Value) -> Option<Value> {
+    let geometria = match valor {
+        Value::String(texto) => serde_json::from_str(texto).ok()?,
+        Value::Object(_) => valor.clone(),
+        _ => return None,
+    };
+    geometria.get("type")?;
+    Some(geometria)
+}
+
+// Un punto da su coordenada; el resto de geometrías, el centro de su extensión
+fn geometry_point(geometria: &Value) -> Option<(f64, f64)> {
+    let [oeste, sur, este, norte] = geometry_bbox(geometria)?;
+    Some(((sur + norte) / 2.0, (oeste + este) / 2.0))
+}
+
+// Par lat/lon como {lat, lon|lng}, [lat, lon] o "lat,lon"
+fn lat_lon(valor: &Value) -> Option<(f64, f64)> {
+    let (lat, lon) = match valor {
+        Value::Object(obj) => (
+            obj.get("lat").or_else(|| obj.get("latitud"))?,
+            obj.get("lon")
+                .or_else(|| obj.get("lng"))
+                .or_else(|| obj.get("longitud"))?,
+        ),
+        Value::Array(par) if par.len() == 2 => (&par[0], &par[1]),
+        Value::String(texto) => {
+            let (lat, lon) = texto.split_once(',')?;
+            let lat = lat.trim().parse::<f64>().ok()?;
+            let lon = lon.trim().parse::<f64>().ok()?;
+            return valid_position(lon, lat).then_some((lat, lon));
+        }
+        _ => return None,
+    };
+    let (lat, lon) = (valor_numerico(lat)?, valor_numerico(lon)?);
+    valid_position(lon, lat).then_some((lat, lon))
+}
+
+fn valid_position(lon: f64, lat: f64) -> bool {
+    (-180.0..=180.0).contains(&lon) && (-90.0..=90.0).contains(&lat)
+}
+
+// Extensión [oeste, sur, este, norte] de una geometría; None si no es válida
+pub(crate) fn geometry_bbox(geometria: &Value) -> Option<[f64; 4]> {
+    let mut bbox = [
+        f64::INFINITY,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        f64::NEG_INFINITY,
+    ];
+    let mut extend = |coordenadas: &Value| -> Option<()> {
+        for [lon, lat] in positions(coordenadas)? {
+            bbox = [
+                bbox[0].min(lon),
+                bbox[1].min(lat),
+                bbox[2].max(lon),
+                bbox[3].max(lat),
+            ];
+        }
+        Some(())
+    };
+
+    match geometria.get("type")?.as_str()? {
+        "GeometryCollection" => {
+            for parte in geometria.get("geometries")?.as_array()? {
+                let [oeste, sur, este, norte] = geometry_bbox(parte)?;
+                extend(&serde_json::json!([[oeste, sur], [este, norte]]))?;
+            }
+        }
+        "Point" | "MultiPoint" | "LineString" | "MultiLineString" | "Polygon" | "MultiPolygon" => {
+            extend(geometria.get("coordinates")?)?
+        }
+        _ => return None,
+    }
+
+    bbox[0].is_finite().then_some(bbox)
+}
+
+// Todas las posiciones [lon, lat] de unas coordenadas anidadas a cualquier nivel
+fn positions(coordenadas: &Value) -> Option<Vec<[f64; 2]>> {
+    let lista = coordenadas.as_array()?;
+    if lista.first().is_some_and(|c| c.is_number()) {
+        let lon = lista.first()?.as_f64()?;
+        let lat = lista.get(1)?.as_f64()?;
+        return valid_position(lon, lat).then_some(vec![[lon, lat]]);
+    }
+
+    let mut todas = Vec::new();
+    for parte in lista {
+        todas.extend(positions(parte)?);
+    }
+    Some(todas)
+}
+
+fn validate_bbox(bbox: &[f64; 4]) -> DbResult<()> {
+    let [oeste, sur, este, norte] = *bbox;
+    if !valid_position(oeste, sur) || !valid_position(este, norte) || sur > norte {
+        return Err(DbError::InvalidData(
+            "El bbox debe ser [oeste, sur, este, norte] en grados".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// Un bbox con oeste > este cruza el antimeridiano
+fn in_bbox(bbox: &[f64; 4], lat: f64, lon: f64) -> bool {
+    let [oeste, sur, este, norte] = *bbox;
+    let dentro_lon = if oeste <= este {
+        (oeste..=este).contains(&lon)
+    } else {
+        lon >= oeste || lon <= este
+    };
+    dentro_lon && (sur..=norte).contains(&lat)
+}
+
+// Agrupa los puntos en celdas de `radio` píxeles de la proyección web
+// mercator al zoom dado; las celdas con un solo punto lo devuelven tal cual
+fn cluster_points(
+    puntos: Vec<GeoPoint>,
+    zoom: f64,
+    radio: f64,
+) -> (Vec<GeoPoint>, Vec<GeoCluster>) {
+    let escala = TAMANO_TESELA * 2f64.powf(zoom);
+    let celda_de = |lat: f64, lon: f64| -> (i64, i64) {
+        let lat = lat.clamp(-LATITUD_MERCATOR, LATITUD_MERCATOR).to_radians();
+        let x = (lon + 180.0) / 360.0 * escala;
+        // En el límite de la proyección el redondeo puede dejar y apenas por debajo de 0
+        let y = ((1.0 - (lat.tan() + 1.0 / lat.cos()).ln() / PI) / 2.0 * escala).max(0.0);
+        ((x / radio).floor() as i64, (y / radio).floor() as i64)
+    };
+
+    let mut indices: HashMap<(i64, i64), usize> = HashMap::new();
+    let mut celdas: Vec<Vec<GeoPoint>> = Vec::new();
+    for punto in puntos {
+        let celda = celda_de(punto.lat, punto.lon);
+        let indice = *indices.entry(celda).or_insert_with(|| {
+            celdas.push(Vec::new());
+            celdas.len() - 1
+        });
+        celdas[indice].push(punto);
+    }
+
+    let mut sueltos = Vec::new();
+    let mut clusters = Vec::new();
+    for mut celda in celdas {
+        if celda.len() == 1 {
+            sueltos.append(&mut celda);
+            continue;
+        }
+        let conteo = celda.len();
+        let mut bbox = [
+            f64::INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NEG_INFINITY,
+        ];
+        let (mut lat, mut lon) = (0.0, 0.0);
+        for punto in &celda {
+            lat += punto.lat;
+            lon += punto.lon;
+            bbox = [
+                bbox[0].min(punto.lon),
+                bbox[1].min(punto.lat),
+                bbox[2].max(punto.lon),
+                bbox[3].max(punto.lat),
+            ];
+        }
+        clusters.push(GeoCluster {
+            lat: lat / conteo as f64,
+            lon: lon / conteo as f64,
+            conteo,
+            bbox,
+        });
+    }
+    clusters.sort_by_key(|c| Reverse(c.conteo));
+
+    (sueltos, clusters)
+}
+
+// Polígonos de una región; cada polígono es su anillo exterior seguido de sus huecos
+struct Region {
+    nombre: String,
+    geometria: Value,
+    bbox: [f64; 4],
+    poligonos: Vec<Vec<Vec<[f64; 2]>>>,
+}
+
+impl Region {
+    fn from_geometry(nombre: String, geometria: Value) -> Option<Self> {
+        let coordenadas = geometria.get("coordinates")?;
+        let poligonos = match geometria.get("type")?.as_str()? {
+            "Polygon" => vec![rings(coordenadas)?],
+            "MultiPolygon" => coordenadas
+                .as_array()?
+                .iter()
+                .map(rings)
+                .collect::<Option<Vec<_>>>()?,
+            _ => return None,
+        };
+        let bbox = geometry_bbox(&geometria)?;
+        Some(Self {
+            nombre,
+            geometria,
+            bbox,
+            poligonos,
+        })
+    }
+
+    fn contains(&self, lat: f64, lon: f64) -> bool {
+        if !in_bbox(&self.bbox, lat, lon) {
+            return false;
+        }
+        self.poligonos.iter().any(|anillos| {
+            let mut anillos = anillos.iter();
+            anillos
+                .next()
+                .is_some_and(|exterior| ring_contains(exterior, lon, lat))
+                && !anillos.any(|hueco| ring_contains(hueco, lon, lat))
+        })
+    }
+}
+
+fn rings(coordenadas: &Value) -> Option<Vec<Vec<[f64; 2]>>> {
+    coordenadas.as_array()?.iter().map(positions).collect()
+}
+
+// Regla par-impar con un rayo hacia el este
+fn ring_contains(anillo: &[[f64; 2]], x: f64, y: f64) -> bool {
+    let mut dentro = false;
+    let mut j = anillo.len().wrapping_sub(1);
+    for i in 0..anillo.len() {
+        let [xi, yi] = anillo[i];
+        let [xj, yj] = anillo[j];
+        if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+            dentro = !dentro;
+        }
+        j = i;
+    }
+    dentro
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn punto(lat: f64, lon: f64) -> GeoPoint {
+        GeoPoint {
+            lat,
+            lon,
+            fila: Value::Null,
+        }
+    }
+
+    #[test]
+    fn ring_contains_poligonos_convexos_y_concavos() {
+        let cuadrado = [
+            [0.0, 0.0],
+            [10.0, 0.0],
+            [10.0, 10.0],
+            [0.0, 10.0],
+            [0.0, 0.0],
+        ];
+        assert!(ring_contains(&cuadrado, 5.0, 5.0));
+        assert!(!ring_contains(&cuadrado, 15.0, 5.0));
+        assert!(!ring_contains(&cuadrado, 5.0, -1.0));
+
+        // Forma de L: el hueco de la esquina superior derecha queda fuera
+        let ele = [
+            [0.0, 0.0],
+            [10.0, 0.0],
+            [10.0, 4.0],
+            [4.0, 4.0],
+            [4.0, 10.0],
+            [0.0, 10.0],
+        ];
+        assert!(ring_contains(&ele, 2.0, 8.0));
+        assert!(ring_contains(&ele, 8.0, 2.0));
+        assert!(!ring_contains(&ele, 8.0, 8.0));
+
+        assert!(!ring_contains(&[], 0.0, 0.0));
+    }
+
+    #[test]
+    fn region_respeta_huecos_y_multipoligonos() {
+        let region = Region::from_geometry(
+            "R".to_string(),
+            json!({
+                "type": "MultiPolygon",
+                "coordinates": [
+                    [
+                        [[0, 0], [10, 0], [10, 10], [0, 10], [0, 0]],
+                        [[4, 4], [6, 4], [6, 6], [4, 6], [4, 4]]
+                    ],
+                    [[[20, 20], [22, 20], [22, 22], [20, 22], [20, 20]]]
+                ]
+            }),
+        )
+        .unwrap();
+
+        assert!(region.contains(2.0, 2.0));
+        assert!(!region.contains(5.0, 5.0));
+        assert!(region.contains(21.0, 21.0));
+        assert!(!region.contains(15.0, 15.0));
+        assert_eq!(region.bbox, [0.0, 0.0, 22.0, 22.0]);
+
+        assert!(Region::from_geometry(
+            "P".to_string(),
+            json!({ "type": "Point", "coordinates": [0, 0] })
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn cluster_points_agrupa_segun_el_zoom() {
+        let puntos = || {
+            vec![
+                punto(40.4168, -3.7038),
+                punto(40.4170, -3.7040),
+                punto(40.4200, -3.7000),
+                punto(41.3874, 2.1686),
+            ]
+        };
+
+        // A zoom bajo Madrid es un grupo y Barcelona queda suelta
+        let (sueltos, clusters) = cluster_points(puntos(), 5.0, RADIO_CLUSTER);
+        assert_eq!(sueltos.len(), 1);
+        assert_eq!(sueltos[0].lon, 2.1686);
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].conteo, 3);
+        assert_eq!(clusters[0].bbox, [-3.7040, 40.4168, -3.7000, 40.4200]);
+        assert!((clusters[0].lat - (40.4168 + 40.4170 + 40.4200) / 3.0).abs() < 1e-9);
+
+        // Muy cerca ya no hay grupos
+        let (sueltos, clusters) = cluster_points(puntos(), 18.0, RADIO_CLUSTER);
+        assert_eq!(sueltos.len(), 4);
+        assert!(clusters.is_empty());
+
+        // Todo el mundo en una celda; los polos se recortan a la proyección
+        let (sueltos, clusters) =
+            cluster_points(vec![punto(89.9, 0.0), punto(85.0, 1.0)], 0.0, 256.0);
+        assert!(sueltos.is_empty());
+        assert_eq!(clusters[0].conteo, 2);
+    }
+
+    #[test]
+    fn in_bbox_cruzando_el_antimeridiano() {
+        let bbox = [170.0, -10.0, -170.0, 10.0];
+        assert!(in_bbox(&bbox, 0.0, 175.0));
+        assert!(in_bbox(&bbox, 0.0, -175.0));
+        assert!(!in_bbox(&bbox, 0.0, 0.0));
+        assert!(!in_bbox(&bbox, 20.0, 175.0));
+    }
+}
@@ -0,0 +1,732 @@
+use crate::commands::calendar::{
+    format_event_time, insert_calendar_event, load_calendar_events, parse_until,
+    save_calendar_event, validate_calendar_event,
+};
+use crate::models::{
+    calendar::{CalendarEvent, CalendarEventDto, IcsExportResult, IcsImportResult},
+    database::{AppStateHandle, DbError, DbResult},
+    panel::PanelKind,
+};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use sqlx::SqlitePool;
+use std::path::Path;
+
+const PRODID: &str = "-//panelCanvaEsc//Calendario//ES";
+const MAX_ICS: u64 = 16 * 1024 * 1024;
+
+// Importa los VEVENT de un .ics en el board del panel de calendario.
+// Un UID ya importado actualiza su evento en lugar de crear otro.
+#[tauri::command]
+pub async fn import_calendar_ics(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    panel_id: String,
+    ruta: String,
+) -> DbResult<IcsImportResult> {
+    let pool = &state.pool;
+    let board_id = get_calendar_panel_board(pool, &panel_id, &user_id).await?;
+
+    let tamano = tokio::fs::metadata(&ruta)
+        .await
+        .map_err(|e| DbError::InvalidData(format!("No se pudo leer {}: {}", ruta, e)))?
+        .len();
+    if tamano > MAX_ICS {
+        return Err(DbError::InvalidData(format!(
+            "{} supera el tamaño máximo de {} MB",
+            ruta,
+            MAX_ICS / (1024 * 1024)
+        )));
+    }
+    let contenido = tokio::fs::read_to_string(&ruta)
+        .await
+        .map_err(|e| DbError::InvalidData(format!("No se pudo leer {}: {}", ruta, e)))?;
+    let vevents = parse_ics(&contenido);
+    if vevents.is_empty() {
+        return Err(DbError::InvalidData(
+            "El fichero no contiene eventos (VEVENT)".to_string(),
+        ));
+    }
+
+    let mut resultado = IcsImportResult::default();
+    for vevent in &vevents {
+        let uid = vevent.value("UID").map(|u| u.to_string());
+        let nombre = uid.clone().unwrap_or_else(|| "(sin UID)".to_string());
+
+        // Las modificaciones de una sola ocurrencia y los eventos cancelados no se importan
+        if vevent.property("RECURRENCE-ID").is_some() || vevent.value("STATUS") == Some("CANCELLED")
+        {
+            resultado.omitidos += 1;
+            continue;
+        }
+
+        let dto = match vevent_to_event(vevent, &board_id) {
+            Ok(dto) => dto,
+            Err(e) => {
+                resultado.errores.push(format!("{}: {}", nombre, e));
+                continue;
+            }
+        };
+        let dto = match validate_calendar_event(pool, &user_id, dto).await {
+            Ok(dto) => dto,
+            Err(e) => {
+                resultado.errores.push(format!("{}: {}", nombre, e));
+                continue;
+            }
+        };
+
+        let existente: Option<(String,)> = match &uid {
+            Some(uid) => {
+                // El mismo .ics importado en otro board crea allí sus propios eventos
+                sqlx::query_as(
+                    "SELECT id FROM calendar_events WHERE user_id = ? AND board_id = ? AND uid = ?",
+                )
+                .bind(&user_id)
+                .bind(&board_id)
+                .bind(uid)
+                .fetch_optional(&**pool)
+                .await?
+            }
+            None => None,
+        };
+        match existente {
+            Some((event_id,)) => {
+                save_calendar_event(pool, &event_id, &dto).await?;
+                resultado.actualizados += 1;
+            }
+            None => {
+                insert_calendar_event(pool, &user_id, &dto, uid.as_deref()).await?;
+                resultado.creados += 1;
+            }
+        }
+    }
+
+    Ok(resultado)
+}
+
+// Escribe en `ruta` los eventos del board del panel de calendario
+#[tauri::command]
+pub async fn export_calendar_ics(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    panel_id: String,
+    ruta: String,
+) -> DbResult<IcsExportResult> {
+    let pool = &state.pool;
+    let board_id = get_calendar_panel_board(pool, &panel_id, &user_id).await?;
+
+    let events = load_calendar_events(pool, &user_id, Some(&board_id)).await?;
+    let ics = write_ics(&events);
+
+    let ruta = Path::new(&ruta);
+    if let Some(directorio) = ruta.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(directorio).map_err(|e| {
+            DbError::InvalidData(format!("No se pudo crear {}: {}", directorio.display(), e))
+        })?;
+    }
+    std::fs::write(ruta, ics).map_err(|e| {
+        DbError::InvalidData(format!("No se pudo escribir {}: {}", ruta.display(), e))
+    })?;
+
+    Ok(IcsExportResult {
+        ruta: ruta.to_string_lossy().to_string(),
+        eventos: events.len(),
+    })
+}
+
+async fn get_calendar_panel_board(
+    pool: &SqlitePool,
+    panel_id: &str,
+    user_id: &str,
+) -> DbResult<String> {
+    let (board_id, tipo): (String, String) = sqlx::query_as(
+        r#"
+        SELECT p.board_id, p.tipo
+        FROM panels p
+        JOIN boards b ON b.id = p.board_id
+        WHERE p.id = ? AND b.user_id = ?
+        "#,
+    )
+    .bind(panel_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(DbError::Unauthorized)?;
+
+    if PanelKind::try_from(tipo)? != PanelKind::Calendario {
+        return Err(DbError::InvalidData(
+            "El panel no es un panel de calendario".to_string(),
+        ));
+    }
+    Ok(board_id)
+}
+
+// Propiedad de un componente: 'NOMBRE;PARAM=VALOR:valor'
+struct IcsProperty {
+    nombre: String,
+    parametros: Vec<(String, String)>,
+    valor: String,
+}
+
+impl IcsProperty {
+    fn param(&self, nombre: &str) -> Option<&str> {
+        self.parametros
+            .iter()
+            .find(|(n, _)| n == nombre)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+struct VEvent {
+    propiedades: Vec<IcsProperty>,
+}
+
+impl VEvent {
+    fn property(&self, nombre: &str) -> Option<&IcsProperty> {
+        self.propiedades.iter().find(|p| p.nombre == nombre)
+    }
+
+    fn value(&self, nombre: &str) -> Option<&str> {
+        self.property(nombre).map(|p| p.valor.as_str())
+    }
+}
+
+// Extrae los VEVENT del fichero; los VTIMEZONE se ignoran y los TZID se
+// resuelven con la base de zonas IANA
+fn parse_ics(contenido: &str) -> Vec<VEvent> {
+    let mut vevents = Vec::new();
+    let mut actual: Option<Vec<IcsProperty>> = None;
+    // Profundidad de componentes anidados dentro del VEVENT (p. ej. VALARM)
+    let mut anidado = 0;
+
+    for linea in unfold_lines(contenido) {
+        let Some(propiedad) = parse_property(&linea) else {
+            continue;
+        };
+        match (
+            propiedad.nombre.as_str(),
+            propiedad.valor.to_ascii_uppercase().as_str(),
+        ) {
+            ("BEGIN", "VEVENT") if actual.is_none() => actual = Some(Vec::new()),
+            ("BEGIN", _) if actual.is_some() => anidado += 1,
+            ("END", "VEVENT") if anidado == 0 => {
+                if let Some(propiedades) = actual.take() {
+                    vevents.push(VEvent { propiedades });
+                }
+            }
+            ("END", _) if anidado > 0 => anidado -= 1,
+            _ => {
+                if let (Some(propiedades), 0) = (actual.as_mut(), anidado) {
+                    propiedades.push(propiedad);
+                }
+            }
+        }
+    }
+
+    vevents
+}
+
+// Une las líneas plegadas (las que empiezan por espacio o tabulador continúan la anterior)
+fn unfold_lines(contenido: &str) -> Vec<String> {
+    let mut lineas: Vec<String> = Vec::new();
+    for linea in contenido.lines() {
+        let linea = linea.trim_end_matches('\r');
+        match (linea.strip_prefix([' ', '\t']), lineas.last_mut()) {
+            (Some(resto), Some(anterior)) => anterior.push_str(resto),
+            _ if linea.is_empty() => {}
+            _ => lineas.push(linea.to_string()),
+        }
+    }
+    lineas
+}
+
+fn parse_property(linea: &str) -> Option<IcsProperty> {
+    // El valor empieza en el primer ':' fuera de comillas
+    let mut entre_comillas = false;
+    let separador = linea.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            entre_comillas = !entre_comillas;
+            None
+        }
+        ':' if !entre_comillas => Some(i),
+        _ => None,
+    })?;
+    let (cabecera, valor) = (&linea[..separador], &linea[separador + 1..]);
+
+    let mut partes = cabecera.split(';');
+    let nombre = partes.next()?.trim().to_ascii_uppercase();
+    let parametros = partes
+        .filter_map(|p| p.split_once('='))
+        .map(|(n, v)| {
+            (
+                n.trim().to_ascii_uppercase(),
+                v.trim_matches('"').to_string(),
+            )
+        })
+        .collect();
+
+    Some(IcsProperty {
+        nombre,
+        parametros,
+        valor: valor.to_string(),
+    })
+}
+
+fn vevent_to_event(vevent: &VEvent, board_id: &str) -> DbResult<CalendarEventDto> {
+    let dtstart = vevent
+        .property("DTSTART")
+        .ok_or_else(|| DbError::InvalidData("Falta DTSTART".to_string()))?;
+    let (inicio, todo_el_dia) = parse_ics_time(dtstart)?;
+
+    // En los eventos de día completo DTEND es exclusivo; aquí el fin incluye su día
+    let fin = match (vevent.property("DTEND"), vevent.value("DURATION")) {
+        (Some(dtend), _) => Some(parse_ics_time(dtend)?.0),
+        (None, Some(duracion)) => Some(
+            inicio
+                .checked_add_signed(parse_duration(duracion)?)
+                .ok_or_else(|| DbError::InvalidData(format!("Duración inválida: {}", duracion)))?,
+        ),
+        (None, None) => None,
+    };
+    let fin = fin
+        .map(|f| {
+            if todo_el_dia {
+                f.checked_sub_signed(Duration::days(1)).unwrap_or(f)
+            } else {
+                f
+            }
+        })
+        .filter(|f| *f > inicio)
+        .map(|f| format_event_time(f, todo_el_dia));
+
+    let mut excepciones = Vec::new();
+    for exdate in vevent.propiedades.iter().filter(|p| p.nombre == "EXDATE") {
+        for valor in exdate.valor.split(',') {
+            let parcial = IcsProperty {
+                nombre: exdate.nombre.clone(),
+                parametros: exdate.parametros.clone(),
+                valor: valor.to_string(),
+            };
+            let (fecha, _) = parse_ics_time(&parcial)?;
+            excepciones.push(format_event_time(fecha, todo_el_dia));
+        }
+    }
+
+    Ok(CalendarEventDto {
+        board_id: Some(board_id.to_string()),
+        titulo: vevent
+            .value("SUMMARY")
+            .map(unescape_text)
+            .filter(|t| !t.trim().is_empty())
+            .unwrap_or_else(|| "Sin título".to_string()),
+        descripcion: vevent.value("DESCRIPTION").map(unescape_text),
+        inicio: format_event_time(inicio, todo_el_dia),
+        fin,
+        todo_el_dia,
+        recurrencia: vevent.value("RRULE").map(|r| r.to_string()),
+        excepciones,
+        color: vevent.value("COLOR").map(|c| c.to_string()),
+    })
+}
+
+// Fecha de DTSTART/DTEND/EXDATE en hora local y si es de día completo.
+// Admite fechas ('VALUE=DATE'), horas UTC ('Z'), con TZID o flotantes.
+fn parse_ics_time(propiedad: &IcsProperty) -> DbResult<(NaiveDateTime, bool)> {
+    let valor = propiedad.valor.trim();
+    let invalida =
+        || DbError::InvalidData(format!("Fecha inválida en {}: {}", propiedad.nombre, valor));
+
+    if propiedad.param("VALUE") == Some("DATE") || valor.len() == 8 {
+        let fecha = NaiveDate::parse_from_str(valor, "%Y%m%d").map_err(|_| invalida())?;
+        return Ok((fecha.and_time(NaiveTime::MIN), true));
+    }
+
+    if let Some(utc) = valor.strip_suffix('Z') {
+        let fecha = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").map_err(|_| invalida())?;
+        return Ok((
+            Utc.from_utc_datetime(&fecha)
+                .with_timezone(&Local)
+                .naive_local(),
+            false,
+        ));
+    }
+
+    let fecha = NaiveDateTime::parse_from_str(valor, "%Y%m%dT%H%M%S").map_err(|_| invalida())?;
+    // Una zona desconocida (p. ej. nombres de Windows) se trata como hora local
+    let zona = propiedad.param("TZID").and_then(|z| z.parse::<Tz>().ok());
+    let fecha = match zona {
+        Some(zona) => zona
+            .from_local_datetime(&fecha)
+            .earliest()
+            .map(|f| f.with_timezone(&Local).naive_local())
+            .unwrap_or(fecha),
+        None => fecha,
+    };
+    Ok((fecha, false))
+}
+
+// Duración ISO 8601 como la usa iCalendar: 'P1W', 'P1DT2H', 'PT30M', '-PT15M'
+fn parse_duration(valor: &str) -> DbResult<Duration> {
+    let invalida = || DbError::InvalidData(format!("Duración inválida: {}", valor));
+    let (signo, resto) = match valor.trim().strip_prefix('-') {
+        Some(resto) => (-1, resto),
+        None => (1, valor.trim().trim_start_matches('+')),
+    };
+    let resto = resto.strip_prefix('P').ok_or_else(invalida)?;
+
+    let mut total = Duration::zero();
+    let mut numero = String::new();
+    for c in resto.chars() {
+        match c {
+            '0'..='9' => numero.push(c),
+            'T' => {}
+            'W' | 'D' | 'H' | 'M' | 'S' => {
+                let n: i64 = numero.parse().map_err(|_| invalida())?;
+                numero.clear();
+                let parte = match c {
+                    'W' => Duration::try_weeks(n),
+                    'D' => Duration::try_days(n),
+                    'H' => Duration::try_hours(n),
+                    'M' => Duration::try_minutes(n),
+                    _ => Duration::try_seconds(n),
+                };
+                total = parte
+                    .and_then(|parte| total.checked_add(&parte))
+                    .ok_or_else(invalida)?;
+            }
+            _ => return Err(invalida()),
+        }
+    }
+    if !numero.is_empty() {
+        return Err(invalida());
+    }
+
+    Ok(total * signo)
+}
+
+fn write_ics(events: &[CalendarEvent]) -> String {
+    let mut lineas = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODID),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+
+    for event in events {
+        let todo_el_dia = event.todo_el_dia;
+        let Ok(inicio) = parse_local(&event.inicio, todo_el_dia) else {
+            continue;
+        };
+        let fin = event
+            .fin
+            .as_deref()
+            .and_then(|f| parse_local(f, todo_el_dia).ok());
+
+        lineas.push("BEGIN:VEVENT".to_string());
+        lineas.push(format!(
+            "UID:{}",
+            event
+                .uid
+                .clone()
+                .unwrap_or_else(|| format!("{}@panelcanvaesc", event.id))
+        ));
+        lineas.push(format!("DTSTAMP:{}", ics_stamp(&event.updated_at)));
+        if todo_el_dia {
+            lineas.push(format!("DTSTART;VALUE=DATE:{}", inicio.format("%Y%m%d")));
+            let fin = fin.unwrap_or(inicio) + Duration::days(1);
+            lineas.push(format!("DTEND;VALUE=DATE:{}", fin.format("%Y%m%d")));
+        } else {
+            lineas.push(format!("DTSTART:{}", ics_utc(inicio)));
+            if let Some(fin) = fin {
+                lineas.push(format!("DTEND:{}", ics_utc(fin)));
+            }
+        }
+        lineas.push(format!("SUMMARY:{}", escape_text(&event.titulo)));
+        if let Some(descripcion) = &event.descripcion {
+            lineas.push(format!("DESCRIPTION:{}", escape_text(descripcion)));
+        }
+        if let Some(regla) = &event.recurrencia {
+            lineas.push(format!("RRULE:{}", export_rule(regla, todo_el_dia)));
+        }
+        if !event.excepciones.is_empty() {
+            let fechas: Vec<String> = event
+                .excepciones
+                .iter()
+                .filter_map(|e| parse_local(e, todo_el_dia).ok())
+                .map(|e| {
+                    if todo_el_dia {
+                        e.format("%Y%m%d").to_string()
+                    } else {
+                        ics_utc(e)
+                    }
+                })
+                .collect();
+            let parametro = if todo_el_dia { ";VALUE=DATE" } else { "" };
+            lineas.push(format!("EXDATE{}:{}", parametro, fechas.join(",")));
+        }
+        if let Some(color) = &event.color {
+            lineas.push(format!("COLOR:{}", color));
+        }
+        lineas.push("END:VEVENT".to_string());
+    }
+    lineas.push("END:VCALENDAR".to_string());
+
+    let mut ics = String::new();
+    for linea in lineas {
+        fold_line(&mut ics, &linea);
+    }
+    ics
+}
+
+fn parse_local(valor: &str, todo_el_dia: bool) -> DbResult<NaiveDateTime> {
+    let formato = if todo_el_dia {
+        "%Y-%m-%d"
+    } else {
+        "%Y-%m-%dT%H:%M:%S"
+    };
+    if todo_el_dia {
+        NaiveDate::parse_from_str(valor, formato).map(|f| f.and_time(NaiveTime::MIN))
+    } else {
+        NaiveDateTime::parse_from_str(valor, formato)
+    }
+    .map_err(|_| DbError::InvalidData(format!("Fecha de evento inválida: {}", valor)))
+}
+
+// Hora local a UTC en formato básico; las horas inexistentes por cambio de horario se adelantan
+fn ics_utc(fecha: NaiveDateTime) -> String {
+    let utc = Local
+        .from_local_datetime(&fecha)
+        .earliest()
+        .or_else(|| {
+            Local
+                .from_local_datetime(&(fecha + Duration::hours(1)))
+                .earliest()
+        })
+        .map(|f| f.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&fecha));
+    utc.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn ics_stamp(rfc3339: &str) -> String {
+    DateTime::parse_from_rfc3339(rfc3339)
+        .map(|f| f.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now())
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+// Con DTSTART en UTC, UNTIL también debe ir en UTC; en días completos, como fecha
+fn export_rule(regla: &str, todo_el_dia: bool) -> String {
+    regla
+        .split(';')
+        .map(|parte| match parte.split_once('=') {
+            Some(("UNTIL", valor)) => match parse_until(valor) {
+                Some(hasta) if todo_el_dia => format!("UNTIL={}", hasta.format("%Y%m%d")),
+                Some(hasta) => format!("UNTIL={}", ics_utc(hasta)),
+                None => parte.to_string(),
+            },
+            _ => parte.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+fn escape_text(texto: &str) -> String {
+    texto
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+fn unescape_text(texto: &str) -> String {
+    let mut resultado = String::with_capacity(texto.len());
+    let mut caracteres = texto.chars();
+    while let Some(c) = caracteres.next() {
+        if c != '\\' {
+            resultado.push(c);
+            continue;
+        }
+        match caracteres.next() {
+            Some('n' | 'N') => resultado.push('\n'),
+            Some(otro) => resultado.push(otro),
+            None => resultado.push('\\'),
+        }
+    }
+    resultado
+}
+
+// Pliega la línea en trozos de 75 bytes como pide RFC 5545, sin partir caracteres
+fn fold_line(ics: &mut String, linea: &str) {
+    let mut longitud = 0;
+    for c in linea.chars() {
+        if longitud + c.len_utf8() > 75 {
+            ics.push_str("\r\n ");
+            longitud = 1;
+        }
+        ics.push(c);
+        longitud += c.len_utf8();
+    }
+    ics.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evento(ics: &str) -> CalendarEventDto {
+        let vevents = parse_ics(ics);
+        assert_eq!(vevents.len(), 1);
+        vevent_to_event(&vevents[0], "b").unwrap()
+    }
+
+    fn hora_local_de_utc(anio: i32, mes: u32, dia: u32, hora: u32) -> String {
+        let utc = NaiveDate::from_ymd_opt(anio, mes, dia)
+            .unwrap()
+            .and_hms_opt(hora, 0, 0)
+            .unwrap();
+        format_event_time(
+            Utc.from_utc_datetime(&utc)
+                .with_timezone(&Local)
+                .naive_local(),
+            false,
+        )
+    }
+
+    #[test]
+    fn parse_ics_despliega_lineas_e_ignora_componentes_anidados() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+                   BEGIN:VEVENT\r\n\
+                   UID:1@ejemplo\r\n\
+                   DTSTART;VALUE=DATE:20240110\r\n\
+                   SUMMARY:Reunión\\, revisión\r\n  y cierre\r\n\
+                   DESCRIPTION;ALTREP=\"cid:parte:1\":Línea 1\\nLínea 2\r\n\
+                   BEGIN:VALARM\r\n\
+                   DESCRIPTION:Aviso\r\n\
+                   END:VALARM\r\n\
+                   END:VEVENT\r\n\
+                   END:VCALENDAR\r\n";
+
+        let vevents = parse_ics(ics);
+        assert_eq!(vevents.len(), 1);
+        assert_eq!(vevents[0].value("UID"), Some("1@ejemplo"));
+        assert_eq!(
+            vevents[0].property("DESCRIPTION").unwrap().param("ALTREP"),
+            Some("cid:parte:1")
+        );
+
+        let dto = vevent_to_event(&vevents[0], "b").unwrap();
+        assert_eq!(dto.titulo, "Reunión, revisión y cierre");
+        assert_eq!(dto.descripcion.as_deref(), Some("Línea 1\nLínea 2"));
+        assert!(dto.todo_el_dia);
+        assert_eq!(dto.inicio, "2024-01-10");
+        // DTEND ausente: evento de un solo día
+        assert_eq!(dto.fin, None);
+    }
+
+    #[test]
+    fn vevent_to_event_convierte_fechas_y_duraciones() {
+        // En días completos DTEND es exclusivo
+        let dto = evento(
+            "BEGIN:VEVENT\nDTSTART;VALUE=DATE:20240110\nDTEND;VALUE=DATE:20240113\nEND:VEVENT\n",
+        );
+        assert_eq!(dto.fin.as_deref(), Some("2024-01-12"));
+
+        let dto = evento("BEGIN:VEVENT\nDTSTART:20240115T090000Z\nDURATION:PT90M\nEND:VEVENT\n");
+        assert_eq!(dto.inicio, hora_local_de_utc(2024, 1, 15, 9));
+        assert_eq!(
+            parse_local(dto.fin.as_deref().unwrap(), false).unwrap()
+                - parse_local(&dto.inicio, false).unwrap(),
+            Duration::minutes(90)
+        );
+
+        // Madrid está en UTC+1 en invierno
+        let dto = evento(
+            "BEGIN:VEVENT\n\
+             DTSTART;TZID=Europe/Madrid:20240115T100000\n\
+             RRULE:FREQ=WEEKLY;COUNT=3\n\
+             EXDATE;TZID=Europe/Madrid:20240122T100000,20240129T100000\n\
+             END:VEVENT\n",
+        );
+        assert_eq!(dto.inicio, hora_local_de_utc(2024, 1, 15, 9));
+        assert_eq!(dto.titulo, "Sin título");
+        assert_eq!(dto.recurrencia.as_deref(), Some("FREQ=WEEKLY;COUNT=3"));
+        assert_eq!(
+            dto.excepciones,
+            [
+                hora_local_de_utc(2024, 1, 22, 9),
+                hora_local_de_utc(2024, 1, 29, 9)
+            ]
+        );
+
+        let vevents = parse_ics("BEGIN:VEVENT\nSUMMARY:Sin inicio\nEND:VEVENT\n");
+        assert!(vevent_to_event(&vevents[0], "b").is_err());
+    }
+
+    #[test]
+    fn parse_duration_formatos_ics() {
+        assert_eq!(parse_duration("P1W").unwrap(), Duration::weeks(1));
+        assert_eq!(
+            parse_duration("P1DT2H").unwrap(),
+            Duration::days(1) + Duration::hours(2)
+        );
+        assert_eq!(parse_duration("-PT15M").unwrap(), Duration::minutes(-15));
+        assert_eq!(parse_duration("+PT30S").unwrap(), Duration::seconds(30));
+        // Las que no caben en un Duration son inválidas en vez de desbordar
+        for invalida in [
+            "1H",
+            "PT5",
+            "P1Y",
+            "PTxM",
+            "P9999999999999W",
+            "P99999999999999D",
+        ] {
+            assert!(parse_duration(invalida).is_err(), "{}", invalida);
+        }
+
+        let vevents =
+            parse_ics("BEGIN:VEVENT\nDTSTART:20240115T100000\nDURATION:P1000000000D\nEND:VEVENT\n");
+        assert!(vevent_to_event(&vevents[0], "b").is_err());
+    }
+
+    #[test]
+    fn write_ics_se_vuelve_a_leer_igual() {
+        let event = CalendarEvent {
+            id: "e".to_string(),
+            user_id: "u".to_string(),
+            board_id: Some("b".to_string()),
+            uid: None,
+            titulo: "Revisión; trimestral, con un título que se pliega en dos líneas".repeat(2),
+            descripcion: Some("Primera línea\nSegunda \\ línea".to_string()),
+            inicio: "2024-01-15T10:00:00".to_string(),
+            fin: Some("2024-01-15T11:30:00".to_string()),
+            todo_el_dia: false,
+            recurrencia: Some("FREQ=WEEKLY;BYDAY=MO;UNTIL=20240304T100000".to_string()),
+            excepciones: vec!["2024-01-22T10:00:00".to_string()],
+            color: Some("#ff0000".to_string()),
+            created_at: String::new(),
+            updated_at: "2024-01-01T00:00:00+00:00".to_string(),
+        };
+
+        let ics = write_ics(std::slice::from_ref(&event));
+        assert!(ics.lines().all(|l| l.len() <= 75));
+        assert!(ics.contains("UID:e@panelcanvaesc\r\n"));
+
+        let dto = evento(&ics);
+        assert_eq!(dto.titulo, event.titulo);
+        assert_eq!(dto.descripcion, event.descripcion);
+        assert_eq!(dto.inicio, event.inicio);
+        assert_eq!(dto.fin, event.fin);
+        assert_eq!(dto.excepciones, event.excepciones);
+        assert_eq!(dto.color, event.color);
+        // UNTIL se exporta en UTC y vuelve a la misma hora local
+        let regla = dto.recurrencia.unwrap();
+        let until = regla
+            .split(';')
+            .find_map(|p| p.strip_prefix("UNTIL="))
+            .unwrap();
+        assert!(until.ends_with('Z'));
+        assert_eq!(parse_until(until), parse_until("20240304T100000"));
+    }
+}
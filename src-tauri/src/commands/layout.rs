@@ -0,0 +1,908 @@
+use crate::commands::board::ensure_board_owner;
+use crate::models::{
+    board::{Panel, Position, Size},
+    database::{AppStateHandle, DbError, DbResult},
+    layout::{AutoArrangeDto, BoardLayoutOptions, PanelLayoutDto, PanelOverlap, PanelRect},
+};
+use chrono::Utc;
+use sqlx::{FromRow, Sqlite, SqlitePool, Transaction};
+
+pub(crate) const ESTRATEGIAS: [&str; 3] = ["grid", "masonry", "flow"];
+const ANCHO_POR_DEFECTO: i32 = 1200;
+const ANCHO_COLUMNA: i32 = 360; // Para calcular columnas cuando no se indican
+const ESPACIADO_POR_DEFECTO: i32 = 16;
+// Límites de la organización automática
+const MAX_COLUMNAS: i32 = 64;
+const MAX_ANCHO: i32 = 20_000;
+const MAX_ESPACIADO: i32 = 1_000;
+// Ancho de referencia del lienzo en cada breakpoint
+pub(crate) const BREAKPOINTS: [(&str, i32); 3] =
+    [("desktop", 1920), ("laptop", 1366), ("tablet", 768)];
+const ANCHO_MINIMO_PANEL: i32 = 280; // Al reducir un panel para otro breakpoint
+
+// Guarda las reglas de maquetación y las aplica a los paneles actuales
+#[tauri::command]
+pub async fn set_board_layout(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    board_id: String,
+    opciones: BoardLayoutOptions,
+) -> DbResult<Vec<PanelRect>> {
+    let pool = &state.pool;
+    ensure_board_owner(pool, &board_id, &user_id).await?;
+
+    let mut tx = pool.begin().await?;
+    let rects = save_layout_rules(&mut tx, &board_id, &opciones).await?;
+    tx.commit().await?;
+
+    Ok(rects)
+}
+
+// Guarda las reglas del board y coloca sus paneles según ellas
+pub(crate) async fn save_layout_rules(
+    tx: &mut Transaction<'_, Sqlite>,
+    board_id: &str,
+    opciones: &BoardLayoutOptions,
+) -> DbResult<Vec<PanelRect>> {
+    if opciones.cuadricula.is_some_and(|c| c <= 0) {
+        return Err(DbError::InvalidData(
+            "El tamaño de la cuadrícula debe ser positivo".to_string(),
+        ));
+    }
+
+    sqlx::query(
+        "UPDATE boards SET cuadricula = ?, evitar_solapes = ?, updated_at = ? WHERE id = ?",
+    )
+    .bind(opciones.cuadricula)
+    .bind(opciones.evitar_solapes)
+    .bind(Utc::now().to_rfc3339())
+    .bind(board_id)
+    .execute(&mut **tx)
+    .await?;
+
+    let mut bloques = load_blocks(tx, board_id).await?;
+    if let Some(cuadricula) = opciones.cuadricula {
+        for bloque in bloques.iter_mut().filter(|b| !b.fijo) {
+            bloque.snap(cuadricula);
+        }
+    }
+    if opciones.evitar_solapes {
+        push_neighbours(&mut bloques, &[], opciones.cuadricula);
+    }
+    save_blocks(tx, &bloques).await
+}
+
+// Recoloca todos los paneles visibles; los bloqueados se quedan donde están
+#[tauri::command]
+pub async fn auto_arrange_board(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    board_id: String,
+    opciones: AutoArrangeDto,
+) -> DbResult<Vec<PanelRect>> {
+    let pool = &state.pool;
+    ensure_board_owner(pool, &board_id, &user_id).await?;
+
+    if !ESTRATEGIAS.contains(&opciones.estrategia.as_str()) {
+        return Err(DbError::InvalidData(format!(
+            "Estrategia de organización inválida: {}",
+            opciones.estrategia
+        )));
+    }
+    if opciones.columnas.is_some_and(|c| c <= 0) || opciones.ancho.is_some_and(|a| a <= 0) {
+        return Err(DbError::InvalidData(
+            "Las columnas y el ancho deben ser positivos".to_string(),
+        ));
+    }
+    if opciones.columnas.is_some_and(|c| c > MAX_COLUMNAS)
+        || opciones.ancho.is_some_and(|a| a > MAX_ANCHO)
+        || opciones.espaciado.is_some_and(|e| e > MAX_ESPACIADO)
+    {
+        return Err(DbError::InvalidData(format!(
+            "Como máximo {} columnas, {} de ancho y {} de espaciado",
+            MAX_COLUMNAS, MAX_ANCHO, MAX_ESPACIADO
+        )));
+    }
+
+    let (cuadricula, _) = board_layout_rules(pool, &board_id).await?;
+    let espaciado = opciones
+        .espaciado
+        .unwrap_or(cuadricula.unwrap_or(ESPACIADO_POR_DEFECTO))
+        .clamp(0, MAX_ESPACIADO);
+    let ancho = opciones.ancho.unwrap_or(ANCHO_POR_DEFECTO);
+    let columnas = opciones
+        .columnas
+        .unwrap_or(((ancho + espaciado) / (ANCHO_COLUMNA + espaciado)).max(1));
+
+    let mut tx = pool.begin().await?;
+
+    let mut bloques = load_blocks(&mut tx, &board_id).await?;
+    arrange(
+        &mut bloques,
+        &opciones.estrategia,
+        columnas,
+        espaciado,
+        ancho,
+    );
+    if let Some(cuadricula) = cuadricula {
+        for bloque in bloques.iter_mut().filter(|b| !b.fijo) {
+            bloque.snap(cuadricula);
+        }
+    }
+    // Quita los solapes con paneles bloqueados o grupos más anchos que una columna
+    push_neighbours(&mut bloques, &[], cuadricula);
+    let rects = save_blocks(&mut tx, &bloques).await?;
+
+    tx.commit().await?;
+
+    Ok(rects)
+}
+
+#[tauri::command]
+pub async fn get_panel_overlaps(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    board_id: String,
+) -> DbResult<Vec<PanelOverlap>> {
+    let pool = &state.pool;
+    ensure_board_owner(pool, &board_id, &user_id).await?;
+
+    let mut tx = pool.begin().await?;
+    let bloques = load_blocks(&mut tx, &board_id).await?;
+    tx.commit().await?;
+
+    // Solo se solapan paneles de la misma página
+    let paneles: Vec<(&Option<String>, &Miembro)> = bloques
+        .iter()
+        .flat_map(|b| b.miembros.iter().map(move |m| (&b.page_id, m)))
+        .collect();
+    let mut solapes = Vec::new();
+    for (i, (pagina, panel)) in paneles.iter().enumerate() {
+        for (otra_pagina, otro) in &paneles[i + 1..] {
+            if pagina != otra_pagina {
+                continue;
+            }
+            let area = panel.rect.overlap_area(&otro.rect);
+            if area > 0 {
+                solapes.push(PanelOverlap {
+                    panel_id: panel.id.clone(),
+                    otro_panel_id: otro.id.clone(),
+                    area,
+                });
+            }
+        }
+    }
+
+    Ok(solapes)
+}
+
+// Deja el board sin solapes; si se indica un panel, ese (y su grupo) no se mueve
+#[tauri::command]
+pub async fn resolve_panel_collisions(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    board_id: String,
+    panel_id: Option<String>,
+) -> DbResult<Vec<PanelRect>> {
+    let pool = &state.pool;
+    ensure_board_owner(pool, &board_id, &user_id).await?;
+    let (cuadricula, _) = board_layout_rules(pool, &board_id).await?;
+
+    let mut tx = pool.begin().await?;
+
+    let mut bloques = load_blocks(&mut tx, &board_id).await?;
+    let anclas: Vec<usize> = panel_id
+        .and_then(|id| block_of(&bloques, &id))
+        .into_iter()
+        .collect();
+    push_neighbours(&mut bloques, &anclas, cuadricula);
+    let rects = save_blocks(&mut tx, &bloques).await?;
+
+    tx.commit().await?;
+
+    Ok(rects)
+}
+
+// Guarda la posición y tamaño de un panel para un breakpoint distinto de escritorio
+#[tauri::command]
+pub async fn update_panel_layout(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    panel_id: String,
+    dto: PanelLayoutDto,
+) -> DbResult<PanelRect> {
+    let pool = &state.pool;
+    breakpoint_width(&dto.breakpoint)?;
+    if dto.breakpoint == BREAKPOINTS[0].0 {
+        return Err(DbError::InvalidData(
+            "El layout de escritorio se guarda en el propio panel".to_string(),
+        ));
+    }
+
+    let (board_id, bloqueado): (String, bool) = sqlx::query_as(
+        r#"
+        SELECT p.board_id, p.bloqueado
+        FROM panels p
+        JOIN boards b ON b.id = p.board_id
+        WHERE p.id = ? AND b.user_id = ?
+        "#,
+    )
+    .bind(&panel_id)
+    .bind(&user_id)
+    .fetch_optional(&**pool)
+    .await?
+    .ok_or(DbError::Unauthorized)?;
+
+    if bloqueado {
+        return Err(DbError::InvalidData("El panel está bloqueado".to_string()));
+    }
+
+    let (cuadricula, _) = board_layout_rules(pool, &board_id).await?;
+    let posicion = snap_position(dto.posicion, cuadricula);
+    let tamano = snap_size(dto.tamano, cuadricula);
+
+    sqlx::query(
+        r#"
+        INSERT INTO panel_layouts (panel_id, breakpoint, posicion_x, posicion_y, ancho, alto, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(panel_id, breakpoint) DO UPDATE SET
+            posicion_x = excluded.posicion_x,
+            posicion_y = excluded.posicion_y,
+            ancho = excluded.ancho,
+            alto = excluded.alto,
+            updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(&panel_id)
+    .bind(&dto.breakpoint)
+    .bind(posicion.x)
+    .bind(posicion.y)
+    .bind(tamano.width)
+    .bind(tamano.height)
+    .bind(Utc::now().to_rfc3339())
+    .execute(&**pool)
+    .await?;
+
+    Ok(PanelRect {
+        panel_id,
+        posicion,
+        tamano,
+    })
+}
+
+// Descarta el layout guardado de un breakpoint para volver al generado
+#[tauri::command]
+pub async fn reset_breakpoint_layout(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    board_id: String,
+    breakpoint: String,
+) -> DbResult<()> {
+    let pool = &state.pool;
+    ensure_board_owner(pool, &board_id, &user_id).await?;
+    breakpoint_width(&breakpoint)?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM panel_layouts
+        WHERE breakpoint = ? AND panel_id IN (SELECT id FROM panels WHERE board_id = ?)
+        "#,
+    )
+    .bind(&breakpoint)
+    .bind(&board_id)
+    .execute(&**pool)
+    .await?;
+
+    Ok(())
+}
+
+// (cuadricula, evitar_solapes) del board
+pub(crate) async fn board_layout_rules(
+    pool: &SqlitePool,
+    board_id: &str,
+) -> DbResult<(Option<i32>, bool)> {
+    let reglas: (Option<i32>, bool) =
+        sqlx::query_as("SELECT cuadricula, evitar_solapes FROM boards WHERE id = ?")
+            .bind(board_id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or(DbError::NotFound)?;
+
+    Ok(reglas)
+}
+
+pub(crate) fn snap_position(posicion: Position, cuadricula: Option<i32>) -> Position {
+    match cuadricula {
+        Some(c) => Position {
+            x: snap(posicion.x, c),
+            y: snap(posicion.y, c),
+        },
+        None => posicion,
+    }
+}
+
+pub(crate) fn snap_size(tamano: Size, cuadricula: Option<i32>) -> Size {
+    match cuadricula {
+        Some(c) => Size {
+            width: snap(tamano.width, c).max(c),
+            height: snap(tamano.height, c).max(c),
+        },
+        None => tamano,
+    }
+}
+
+// Empuja los paneles que pisan al panel editado, con las reglas del board
+pub(crate) async fn settle_board_layout(
+    tx: &mut Transaction<'_, Sqlite>,
+    board_id: &str,
+    panel_id: &str,
+    cuadricula: Option<i32>,
+) -> DbResult<()> {
+    let mut bloques = load_blocks(tx, board_id).await?;
+    if let Some(ancla) = block_of(&bloques, panel_id) {
+        push_neighbours(&mut bloques, &[ancla], cuadricula);
+        save_blocks(tx, &bloques).await?;
+    }
+    Ok(())
+}
+
+// Sustituye posición y tamaño de los paneles por los del breakpoint. Los paneles
+// sin layout guardado lo generan a partir del de escritorio: se escalan al ancho
+// del breakpoint y se empujan hacia abajo si pisan a otro.
+pub(crate) async fn apply_breakpoint(
+    pool: &SqlitePool,
+    panels: &mut [Panel],
+    breakpoint: &str,
+) -> DbResult<()> {
+    let ancho = breakpoint_width(breakpoint)?;
+    let Some(board_id) = panels.first().map(|p| p.board_id.clone()) else {
+        return Ok(());
+    };
+    if breakpoint == BREAKPOINTS[0].0 {
+        return Ok(());
+    }
+
+    let guardados: Vec<(String, i32, i32, i32, i32)> = sqlx::query_as(
+        r#"
+        SELECT l.panel_id, l.posicion_x, l.posicion_y, l.ancho, l.alto
+        FROM panel_layouts l
+        JOIN panels p ON p.id = l.panel_id
+        WHERE p.board_id = ? AND l.breakpoint = ?
+        "#,
+    )
+    .bind(&board_id)
+    .bind(breakpoint)
+    .fetch_all(pool)
+    .await?;
+
+    let ancho_escritorio = panels
+        .iter()
+        .map(|p| p.posicion.x + p.tamano.width)
+        .max()
+        .unwrap_or(0)
+        .max(BREAKPOINTS[0].1);
+    let escala = ancho as f64 / ancho_escritorio as f64;
+
+    // Los layouts guardados cuentan como fijos: solo se recolocan los generados
+    let mut bloques: Vec<Bloque> = panels
+        .iter()
+        .filter(|p| !p.oculto)
+        .map(|p| {
+            let guardado = guardados.iter().find(|g| g.0 == p.id);
+            let rect = match guardado {
+                Some(&(_, x, y, ancho, alto)) => Rect { x, y, ancho, alto },
+                None => scale_to_breakpoint(
+                    Rect {
+                        x: p.posicion.x,
+                        y: p.posicion.y,
+                        ancho: p.tamano.width,
+                        alto: p.tamano.height,
+                    },
+                    escala,
+                    ancho,
+                ),
+            };
+            Bloque {
+                page_id: p.page_id.clone(),
+                grupo_id: None,
+                miembros: vec![Miembro {
+                    id: p.id.clone(),
+                    rect,
+                }],
+                original: rect,
+                rect,
+                fijo: guardado.is_some(),
+            }
+        })
+        .collect();
+    push_neighbours(&mut bloques, &[], None);
+
+    for bloque in &bloques {
+        if let Some(panel) = panels.iter_mut().find(|p| p.id == bloque.miembros[0].id) {
+            panel.posicion = Position {
+                x: bloque.rect.x,
+                y: bloque.rect.y,
+            };
+            panel.tamano = Size {
+                width: bloque.rect.ancho,
+                height: bloque.rect.alto,
+            };
+        }
+    }
+
+    Ok(())
+}
+
+fn breakpoint_width(breakpoint: &str) -> DbResult<i32> {
+    BREAKPOINTS
+        .iter()
+        .find(|(nombre, _)| *nombre == breakpoint)
+        .map(|(_, ancho)| *ancho)
+        .ok_or_else(|| DbError::InvalidData(format!("Breakpoint desconocido: {}", breakpoint)))
+}
+
+// Escala x y ancho sin bajar del ancho mínimo ni salirse del lienzo; la altura se conserva
+fn scale_to_breakpoint(rect: Rect, escala: f64, ancho: i32) -> Rect {
+    let ancho_panel = ((rect.ancho as f64 * escala).round() as i32)
+        .max(ANCHO_MINIMO_PANEL)
+        .min(ancho);
+    let x = ((rect.x as f64 * escala).round() as i32)
+        .min(ancho - ancho_panel)
+        .max(0);
+    Rect {
+        ancho: ancho_panel,
+        x,
+        ..rect
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Rect {
+    x: i32,
+    y: i32,
+    ancho: i32,
+    alto: i32,
+}
+
+impl Rect {
+    fn right(&self) -> i32 {
+        self.x.saturating_add(self.ancho)
+    }
+
+    fn bottom(&self) -> i32 {
+        self.y.saturating_add(self.alto)
+    }
+
+    fn union(&self, otro: &Rect) -> Rect {
+        let x = self.x.min(otro.x);
+        let y = self.y.min(otro.y);
+        Rect {
+            x,
+            y,
+            ancho: self.right().max(otro.right()) - x,
+            alto: self.bottom().max(otro.bottom()) - y,
+        }
+    }
+
+    // Los bordes que solo se tocan no cuentan como solape
+    fn overlap_area(&self, otro: &Rect) -> i64 {
+        let ancho = self.right().min(otro.right()) - self.x.max(otro.x);
+        let alto = self.bottom().min(otro.bottom()) - self.y.max(otro.y);
+        if ancho > 0 && alto > 0 {
+            ancho as i64 * alto as i64
+        } else {
+            0
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct PanelFila {
+    id: String,
+    page_id: Option<String>,
+    grupo_id: Option<String>,
+    posicion_x: i32,
+    posicion_y: i32,
+    ancho: i32,
+    alto: i32,
+    bloqueado: bool,
+}
+
+struct Miembro {
+    id: String,
+    rect: Rect,
+}
+
+// Un panel suelto o un grupo entero; se recoloca como una sola pieza
+struct Bloque {
+    page_id: Option<String>,
+    grupo_id: Option<String>,
+    miembros: Vec<Miembro>,
+    original: Rect,
+    rect: Rect,
+    fijo: bool, // Contiene algún panel bloqueado
+}
+
+impl Bloque {
+    // Solo los paneles sueltos cambian de tamaño; los grupos conservan sus proporciones
+    fn redimensionable(&self) -> bool {
+        self.miembros.len() == 1 && !self.fijo
+    }
+
+    fn snap(&mut self, cuadricula: i32) {
+        self.rect.x = snap(self.rect.x, cuadricula);
+        self.rect.y = snap(self.rect.y, cuadricula);
+        if self.redimensionable() {
+            self.rect.ancho = snap(self.rect.ancho, cuadricula).max(cuadricula);
+            self.rect.alto = snap(self.rect.alto, cuadricula).max(cuadricula);
+        }
+    }
+}
+
+fn snap(valor: i32, cuadricula: i32) -> i32 {
+    (valor as f64 / cuadricula as f64).round() as i32 * cuadricula
+}
+
+fn snap_up(valor: i32, cuadricula: i32) -> i32 {
+    (valor as f64 / cuadricula as f64).ceil() as i32 * cuadricula
+}
+
+fn block_of(bloques: &[Bloque], panel_id: &str) -> Option<usize> {
+    bloques
+        .iter()
+        .position(|b| b.miembros.iter().any(|m| m.id == panel_id))
+}
+
+// Paneles visibles del board agrupados en bloques; los ocultos no ocupan sitio
+async fn load_blocks(tx: &mut Transaction<'_, Sqlite>, board_id: &str) -> DbResult<Vec<Bloque>> {
+    let filas: Vec<PanelFila> = sqlx::query_as(
+        r#"
+        SELECT id, page_id, grupo_id, posicion_x, posicion_y, ancho, alto, bloqueado
+        FROM panels
+        WHERE board_id = ? AND oculto = 0
+        ORDER BY z_index, created_at
+        "#,
+    )
+    .bind(board_id)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    let mut bloques: Vec<Bloque> = Vec::new();
+    for PanelFila {
+        id,
+        page_id,
+        grupo_id,
+        posicion_x,
+        posicion_y,
+        ancho,
+        alto,
+        bloqueado,
+    } in filas
+    {
+        let rect = Rect {
+            x: posicion_x,
+            y: posicion_y,
+            ancho,
+            alto,
+        };
+        let existente = grupo_id
+            .as_ref()
+            .and_then(|g| bloques.iter_mut().find(|b| b.grupo_id.as_ref() == Some(g)));
+
+        match existente {
+            Some(bloque) => {
+                bloque.rect = bloque.rect.union(&rect);
+                bloque.original = bloque.rect;
+                bloque.fijo |= bloqueado;
+                bloque.miembros.push(Miembro { id, rect });
+            }
+            None => bloques.push(Bloque {
+                page_id,
+                grupo_id,
+                miembros: vec![Miembro { id, rect }],
+                original: rect,
+                rect,
+                fijo: bloqueado,
+            }),
+        }
+    }
+
+    Ok(bloques)
+}
+
+// Escribe los paneles que cambiaron y devuelve la posición final de todos
+async fn save_blocks(
+    tx: &mut Transaction<'_, Sqlite>,
+    bloques: &[Bloque],
+) -> DbResult<Vec<PanelRect>> {
+    let now = Utc::now().to_rfc3339();
+    let mut rects = Vec::new();
+
+    for bloque in bloques {
+        let dx = bloque.rect.x - bloque.original.x;
+        let dy = bloque.rect.y - bloque.original.y;
+
+        for miembro in &bloque.miembros {
+            let rect = if bloque.redimensionable() {
+                bloque.rect
+            } else {
+                Rect {
+                    x: miembro.rect.x + dx,
+                    y: miembro.rect.y + dy,
+                    ..miembro.rect
+                }
+            };
+
+            if rect != miembro.rect {
+                sqlx::query(
+                    "UPDATE panels SET posicion_x = ?, posicion_y = ?, ancho = ?, alto = ?, updated_at = ? WHERE id = ?",
+                )
+                .bind(rect.x)
+                .bind(rect.y)
+                .bind(rect.ancho)
+                .bind(rect.alto)
+                .bind(&now)
+                .bind(&miembro.id)
+                .execute(&mut **tx)
+                .await?;
+            }
+
+            rects.push(PanelRect {
+                panel_id: miembro.id.clone(),
+                posicion: Position {
+                    x: rect.x,
+                    y: rect.y,
+                },
+                tamano: Size {
+                    width: rect.ancho,
+                    height: rect.alto,
+                },
+            });
+        }
+    }
+
+    Ok(rects)
+}
+
+// Coloca los bloques no fijos de cada página en orden de lectura según la estrategia
+fn arrange(bloques: &mut [Bloque], estrategia: &str, columnas: i32, espaciado: i32, ancho: i32) {
+    let mut paginas: Vec<Option<String>> = Vec::new();
+    for bloque in bloques.iter() {
+        if !paginas.contains(&bloque.page_id) {
+            paginas.push(bloque.page_id.clone());
+        }
+    }
+
+    for pagina in paginas {
+        let mut orden: Vec<usize> = (0..bloques.len())
+            .filter(|&i| !bloques[i].fijo && bloques[i].page_id == pagina)
+            .collect();
+        orden.sort_by_key(|&i| (bloques[i].rect.y, bloques[i].rect.x));
+        arrange_page(bloques, &orden, estrategia, columnas, espaciado, ancho);
+    }
+}
+
+fn arrange_page(
+    bloques: &mut [Bloque],
+    orden: &[usize],
+    estrategia: &str,
+    columnas: i32,
+    espaciado: i32,
+    ancho: i32,
+) {
+    let columnas = columnas.max(1);
+    let ancho_columna =
+        (ancho.saturating_sub(espaciado.saturating_mul(columnas - 1)) / columnas).max(1);
+    let paso = ancho_columna.saturating_add(espaciado);
+
+    match estrategia {
+        // Filas de `columnas` celdas; cada fila mide lo que su panel más alto
+        "grid" => {
+            let mut y = 0i32;
+            for fila in orden.chunks(columnas as usize) {
+                let mut alto_fila = 0;
+                for (columna, &i) in fila.iter().enumerate() {
+                    let bloque = &mut bloques[i];
+                    if bloque.redimensionable() {
+                        bloque.rect.ancho = ancho_columna;
+                    }
+                    bloque.rect.x = paso.saturating_mul(columna as i32);
+                    bloque.rect.y = y;
+                    alto_fila = alto_fila.max(bloque.rect.alto);
+                }
+                y = y.saturating_add(alto_fila).saturating_add(espaciado);
+            }
+        }
+        // Cada panel va a la columna que menos ocupa
+        "masonry" => {
+            // No hacen falta más columnas que bloques
+            let mut alturas = vec![0; (columnas as usize).min(orden.len())];
+            for &i in orden {
+                let columna = (0..alturas.len()).min_by_key(|&c| alturas[c]).unwrap_or(0);
+                let bloque = &mut bloques[i];
+                if bloque.redimensionable() {
+                    bloque.rect.ancho = ancho_columna;
+                }
+                bloque.rect.x = paso.saturating_mul(columna as i32);
+                bloque.rect.y = alturas[columna];
+                alturas[columna] = bloque.rect.bottom().saturating_add(espaciado);
+            }
+        }
+        // De izquierda a derecha conservando tamaños, saltando de fila al llenar el ancho
+        _ => {
+            let (mut x, mut y, mut alto_fila) = (0i32, 0i32, 0);
+            for &i in orden {
+                let bloque = &mut bloques[i];
+                if x > 0 && x.saturating_add(bloque.rect.ancho) > ancho {
+                    x = 0;
+                    y = y.saturating_add(alto_fila).saturating_add(espaciado);
+                    alto_fila = 0;
+                }
+                bloque.rect.x = x;
+                bloque.rect.y = y;
+                x = bloque.rect.right().saturating_add(espaciado);
+                alto_fila = alto_fila.max(bloque.rect.alto);
+            }
+        }
+    }
+}
+
+// Empuja hacia abajo los bloques que pisan a otro ya colocado de su página. Los
+// fijos y las anclas no se mueven; el resto se asienta en orden de lectura.
+fn push_neighbours(bloques: &mut [Bloque], anclas: &[usize], cuadricula: Option<i32>) {
+    let espaciado = cuadricula.unwrap_or(ESPACIADO_POR_DEFECTO);
+    let (mut asentados, mut resto): (Vec<usize>, Vec<usize>) =
+        (0..bloques.len()).partition(|i| bloques[*i].fijo || anclas.contains(i));
+    resto.sort_by_key(|&i| (bloques[i].rect.y, bloques[i].rect.x));
+
+    for i in resto {
+        while let Some(j) = asentados.iter().copied().find(|&j| {
+            bloques[j].page_id == bloques[i].page_id
+                && bloques[i].rect.overlap_area(&bloques[j].rect) > 0
+        }) {
+            let y = bloques[j].rect.bottom() + espaciado;
+            bloques[i].rect.y = cuadricula.map_or(y, |c| snap_up(y, c));
+        }
+        asentados.push(i);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: i32, y: i32, ancho: i32, alto: i32) -> Rect {
+        Rect { x, y, ancho, alto }
+    }
+
+    fn bloque(id: &str, r: Rect) -> Bloque {
+        Bloque {
+            page_id: None,
+            grupo_id: None,
+            miembros: vec![Miembro {
+                id: id.to_string(),
+                rect: r,
+            }],
+            original: r,
+            rect: r,
+            fijo: false,
+        }
+    }
+
+    fn posiciones(bloques: &[Bloque]) -> Vec<(i32, i32)> {
+        bloques.iter().map(|b| (b.rect.x, b.rect.y)).collect()
+    }
+
+    #[test]
+    fn snap_a_la_celda_mas_cercana() {
+        let posicion = snap_position(Position { x: 29, y: 31 }, Some(20));
+        assert_eq!((posicion.x, posicion.y), (20, 40));
+        let posicion = snap_position(Position { x: 29, y: 31 }, None);
+        assert_eq!((posicion.x, posicion.y), (29, 31));
+
+        // Un tamaño nunca baja de una celda
+        let tamano = snap_size(
+            Size {
+                width: 5,
+                height: 55,
+            },
+            Some(20),
+        );
+        assert_eq!((tamano.width, tamano.height), (20, 60));
+
+        assert_eq!(snap_up(41, 20), 60);
+        assert_eq!(snap_up(40, 20), 40);
+    }
+
+    #[test]
+    fn overlap_area_ignora_bordes_que_se_tocan() {
+        let a = rect(0, 0, 100, 100);
+        assert_eq!(a.overlap_area(&rect(100, 0, 50, 50)), 0);
+        assert_eq!(a.overlap_area(&rect(50, 50, 100, 100)), 2500);
+        assert_eq!(a.union(&rect(50, 150, 100, 10)), rect(0, 0, 150, 160));
+    }
+
+    #[test]
+    fn push_neighbours_baja_los_que_pisan_al_ancla() {
+        let mut bloques = vec![
+            bloque("a", rect(0, 0, 200, 200)),
+            bloque("b", rect(100, 100, 200, 100)),
+            bloque("c", rect(150, 250, 100, 100)),
+            bloque("d", rect(400, 0, 100, 100)),
+        ];
+        push_neighbours(&mut bloques, &[0], None);
+
+        // b baja bajo a y c en cascada bajo b; d no pisa a nadie
+        assert_eq!(
+            posiciones(&bloques),
+            [(0, 0), (100, 216), (150, 332), (400, 0)]
+        );
+    }
+
+    #[test]
+    fn push_neighbours_respeta_fijos_paginas_y_cuadricula() {
+        let mut bloques = vec![
+            bloque("ancla", rect(0, 0, 100, 100)),
+            bloque("fijo", rect(0, 50, 100, 100)),
+            bloque("otro", rect(0, 10, 100, 100)),
+            bloque("otra_pagina", rect(0, 0, 100, 100)),
+        ];
+        bloques[1].fijo = true;
+        bloques[3].page_id = Some("p2".to_string());
+        push_neighbours(&mut bloques, &[0], Some(30));
+
+        // El fijo se queda aunque pise al ancla; el resto salta a la siguiente celda libre
+        assert_eq!(posiciones(&bloques), [(0, 0), (0, 50), (0, 150), (0, 0)]);
+    }
+
+    #[test]
+    fn arrange_por_estrategia() {
+        let nuevos = || {
+            vec![
+                bloque("a", rect(0, 0, 300, 100)),
+                bloque("b", rect(10, 0, 300, 200)),
+                bloque("c", rect(0, 50, 300, 100)),
+            ]
+        };
+
+        let mut bloques = nuevos();
+        arrange(&mut bloques, "grid", 2, 10, 610);
+        assert_eq!(posiciones(&bloques), [(0, 0), (310, 0), (0, 210)]);
+        assert!(bloques.iter().all(|b| b.rect.ancho == 300));
+
+        let mut bloques = nuevos();
+        arrange(&mut bloques, "masonry", 2, 10, 610);
+        assert_eq!(posiciones(&bloques), [(0, 0), (310, 0), (0, 110)]);
+
+        let mut bloques = nuevos();
+        arrange(&mut bloques, "flow", 2, 10, 700);
+        assert_eq!(posiciones(&bloques), [(0, 0), (310, 0), (0, 210)]);
+    }
+
+    #[test]
+    fn arrange_no_desborda_con_valores_extremos() {
+        for estrategia in ESTRATEGIAS {
+            let mut bloques = vec![
+                bloque("a", rect(0, 0, i32::MAX, i32::MAX)),
+                bloque("b", rect(0, 10, 300, 100)),
+            ];
+            arrange(&mut bloques, estrategia, i32::MAX, i32::MAX, i32::MAX);
+            assert!(bloques.iter().all(|b| b.rect.x >= 0 && b.rect.y >= 0));
+        }
+    }
+
+    #[test]
+    fn scale_to_breakpoint_mantiene_el_panel_en_el_lienzo() {
+        assert_eq!(
+            scale_to_breakpoint(rect(960, 40, 960, 300), 0.4, 768),
+            rect(384, 40, 384, 300)
+        );
+        // Sin bajar del ancho mínimo ni salirse por la derecha
+        assert_eq!(
+            scale_to_breakpoint(rect(1800, 0, 100, 80), 0.4, 768),
+            rect(488, 0, ANCHO_MINIMO_PANEL, 80)
+        );
+        assert_eq!(breakpoint_width("tablet").unwrap(), 768);
+        assert!(breakpoint_width("movil").is_err());
+    }
+}
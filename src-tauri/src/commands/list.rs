@@ -0,0 +1,421 @@
+use crate::commands::calendar::{format_event_time, parse_event_time};
+use crate::models::{
+    board::Panel,
+    database::{AppStateHandle, DbError, DbResult},
+    list::{ListCounts, ListItem, ListItemDto},
+    panel::PanelKind,
+};
+use chrono::{Local, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+const PRIORIDADES: [&str; 3] = ["baja", "media", "alta"];
+pub(crate) const CONTEOS_LISTA: [&str; 4] = ["total", "abiertos", "completados", "vencidos"];
+
+// Columnas de `ListItem`; los dos parámetros son la fecha y la hora locales actuales
+const ITEM_COLUMNS: &str = r#"
+    i.id, i.panel_id, i.texto, i.completado, i.fecha_limite, i.asignado_id,
+    u.username AS asignado_nombre, i.prioridad, i.orden,
+    (i.completado = 0 AND i.fecha_limite IS NOT NULL AND CASE
+        WHEN length(i.fecha_limite) = 10 THEN i.fecha_limite < ?
+        ELSE i.fecha_limite < ?
+    END) AS vencido,
+    i.completado_at, i.created_at, i.updated_at
+"#;
+
+#[tauri::command]
+pub async fn get_list_items(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    panel_id: String,
+) -> DbResult<Vec<ListItem>> {
+    let pool = &state.pool;
+    get_list_panel(pool, &panel_id, &user_id).await?;
+
+    load_list_items(pool, &panel_id).await
+}
+
+// Añade el elemento al final de la lista
+#[tauri::command]
+pub async fn add_list_item(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    panel_id: String,
+    dto: ListItemDto,
+) -> DbResult<ListItem> {
+    let pool = &state.pool;
+    get_list_panel(pool, &panel_id, &user_id).await?;
+    let dto = validate_list_item(pool, dto).await?;
+
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+
+    sqlx::query(
+        r#"
+        INSERT INTO panel_list_items
+        (id, panel_id, texto, fecha_limite, asignado_id, prioridad, orden, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, (
+            SELECT COALESCE(MAX(orden) + 1, 0) FROM panel_list_items WHERE panel_id = ?
+        ), ?, ?)
+        "#,
+    )
+    .bind(&id)
+    .bind(&panel_id)
+    .bind(&dto.texto)
+    .bind(&dto.fecha_limite)
+    .bind(&dto.asignado_id)
+    .bind(&dto.prioridad)
+    .bind(&panel_id)
+    .bind(&now)
+    .bind(&now)
+    .execute(&**pool)
+    .await?;
+
+    get_list_item(pool, &id).await
+}
+
+// Reemplaza el texto, la fecha límite, el asignado y la prioridad del elemento
+#[tauri::command]
+pub async fn update_list_item(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    item_id: String,
+    dto: ListItemDto,
+) -> DbResult<ListItem> {
+    let pool = &state.pool;
+    get_owned_item(pool, &item_id, &user_id).await?;
+    let dto = validate_list_item(pool, dto).await?;
+
+    sqlx::query(
+        r#"
+        UPDATE panel_list_items
+        SET texto = ?, fecha_limite = ?, asignado_id = ?, prioridad = ?, updated_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(&dto.texto)
+    .bind(&dto.fecha_limite)
+    .bind(&dto.asignado_id)
+    .bind(&dto.prioridad)
+    .bind(Utc::now().to_rfc3339())
+    .bind(&item_id)
+    .execute(&**pool)
+    .await?;
+
+    get_list_item(pool, &item_id).await
+}
+
+// Marca el elemento como hecho o lo reabre
+#[tauri::command]
+pub async fn toggle_list_item(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    item_id: String,
+) -> DbResult<ListItem> {
+    let pool = &state.pool;
+    get_owned_item(pool, &item_id, &user_id).await?;
+    let now = Utc::now().to_rfc3339();
+
+    sqlx::query(
+        r#"
+        UPDATE panel_list_items
+        SET completado = 1 - completado,
+            completado_at = CASE WHEN completado = 0 THEN ? ELSE NULL END,
+            updated_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(&now)
+    .bind(&now)
+    .bind(&item_id)
+    .execute(&**pool)
+    .await?;
+
+    get_list_item(pool, &item_id).await
+}
+
+// Cada elemento toma la posición que ocupa en `item_ids`, que debe incluirlos todos
+#[tauri::command]
+pub async fn reorder_list_items(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    panel_id: String,
+    item_ids: Vec<String>,
+) -> DbResult<Vec<ListItem>> {
+    let pool = &state.pool;
+    get_list_panel(pool, &panel_id, &user_id).await?;
+
+    let actuales: Vec<(String,)> =
+        sqlx::query_as("SELECT id FROM panel_list_items WHERE panel_id = ?")
+            .bind(&panel_id)
+            .fetch_all(&**pool)
+            .await?;
+
+    let mut esperados: Vec<&str> = actuales.iter().map(|(id,)| id.as_str()).collect();
+    let mut recibidos: Vec<&str> = item_ids.iter().map(String::as_str).collect();
+    esperados.sort_unstable();
+    recibidos.sort_unstable();
+    if esperados != recibidos {
+        return Err(DbError::InvalidData(
+            "El nuevo orden debe incluir exactamente los elementos de la lista".to_string(),
+        ));
+    }
+
+    let mut tx = pool.begin().await?;
+    for (orden, item_id) in item_ids.iter().enumerate() {
+        sqlx::query("UPDATE panel_list_items SET orden = ? WHERE id = ?")
+            .bind(orden as i64)
+            .bind(item_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    load_list_items(pool, &panel_id).await
+}
+
+#[tauri::command]
+pub async fn delete_list_item(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    item_id: String,
+) -> DbResult<()> {
+    let pool = &state.pool;
+    get_owned_item(pool, &item_id, &user_id).await?;
+
+    sqlx::query("DELETE FROM panel_list_items WHERE id = ?")
+        .bind(&item_id)
+        .execute(&**pool)
+        .await?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_list_counts(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    panel_id: String,
+) -> DbResult<ListCounts> {
+    let pool = &state.pool;
+    get_list_panel(pool, &panel_id, &user_id).await?;
+
+    load_list_counts(pool, &panel_id).await
+}
+
+// Los paneles de lista exponen sus elementos en `config.items` y los de
+// estadística con `config.listaPanelId` muestran el conteo pedido en `config.valor`
+pub(crate) async fn apply_list_items(pool: &SqlitePool, panel: &mut Panel) -> DbResult<()> {
+    match panel.tipo {
+        PanelKind::Lista => {
+            let items = load_list_items(pool, &panel.id).await?;
+            let items: Vec<serde_json::Value> = items
+                .iter()
+                .map(|item| {
+                    serde_json::json!({
+                        "id": item.id,
+                        "titulo": item.texto,
+                        "estado": if item.completado { "completado" } else { "pendiente" },
+                        "fechaLimite": item.fecha_limite,
+                        "prioridad": item.prioridad,
+                        "asignadoId": item.asignado_id,
+                        "vencido": item.vencido,
+                    })
+                })
+                .collect();
+            if let Some(config) = panel.config.as_object_mut() {
+                config.insert("items".to_string(), serde_json::json!(items));
+            }
+        }
+        PanelKind::Estadistica => {
+            let Some(lista_id) = panel.config.get("listaPanelId").and_then(|l| l.as_str()) else {
+                return Ok(());
+            };
+            // Solo se leen listas del mismo board
+            let lista: Option<(String,)> = sqlx::query_as(
+                "SELECT id FROM panels WHERE id = ? AND board_id = ? AND tipo = 'lista'",
+            )
+            .bind(lista_id)
+            .bind(&panel.board_id)
+            .fetch_optional(pool)
+            .await?;
+            let Some((lista_id,)) = lista else {
+                return Ok(());
+            };
+
+            let conteos = load_list_counts(pool, &lista_id).await?;
+            let valor = match panel.config.get("conteoLista").and_then(|c| c.as_str()) {
+                Some("total") => conteos.total,
+                Some("completados") => conteos.completados,
+                Some("vencidos") => conteos.vencidos,
+                _ => conteos.abiertos,
+            };
+            if let Some(config) = panel.config.as_object_mut() {
+                config.insert("valor".to_string(), serde_json::json!(valor));
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+async fn load_list_items(pool: &SqlitePool, panel_id: &str) -> DbResult<Vec<ListItem>> {
+    let (hoy, ahora) = local_now();
+
+    let items: Vec<ListItem> = sqlx::query_as(&format!(
+        r#"
+        SELECT {}
+        FROM panel_list_items i
+        LEFT JOIN users u ON u.id = i.asignado_id
+        WHERE i.panel_id = ?
+        ORDER BY i.orden, i.created_at
+        "#,
+        ITEM_COLUMNS
+    ))
+    .bind(&hoy)
+    .bind(&ahora)
+    .bind(panel_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(items)
+}
+
+async fn load_list_counts(pool: &SqlitePool, panel_id: &str) -> DbResult<ListCounts> {
+    let (hoy, ahora) = local_now();
+
+    let conteos: ListCounts = sqlx::query_as(
+        r#"
+        SELECT COUNT(*) AS total,
+               COALESCE(SUM(completado = 0), 0) AS abiertos,
+               COALESCE(SUM(completado = 1), 0) AS completados,
+               COALESCE(SUM(completado = 0 AND fecha_limite IS NOT NULL AND CASE
+                   WHEN length(fecha_limite) = 10 THEN fecha_limite < ?
+                   ELSE fecha_limite < ?
+               END), 0) AS vencidos
+        FROM panel_list_items
+        WHERE panel_id = ?
+        "#,
+    )
+    .bind(&hoy)
+    .bind(&ahora)
+    .bind(panel_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(conteos)
+}
+
+async fn validate_list_item(pool: &SqlitePool, dto: ListItemDto) -> DbResult<ListItemDto> {
+    let texto = dto.texto.trim().to_string();
+    if texto.is_empty() {
+        return Err(DbError::InvalidData(
+            "El elemento necesita un texto".to_string(),
+        ));
+    }
+
+    let prioridad = dto.prioridad.unwrap_or_else(|| "media".to_string());
+    if !PRIORIDADES.contains(&prioridad.as_str()) {
+        return Err(DbError::InvalidData(format!(
+            "Prioridad desconocida: {}",
+            prioridad
+        )));
+    }
+
+    // Una fecha sin hora vence al acabar ese día
+    let fecha_limite = match dto.fecha_limite.as_deref().map(str::trim) {
+        Some(fecha) if !fecha.is_empty() => {
+            let solo_fecha = fecha.len() == 10;
+            let valor = parse_event_time(fecha, solo_fecha)?;
+            Some(format_event_time(valor, solo_fecha))
+        }
+        _ => None,
+    };
+
+    if let Some(asignado_id) = &dto.asignado_id {
+        let _: (String,) = sqlx::query_as("SELECT id FROM users WHERE id = ?")
+            .bind(asignado_id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or_else(|| {
+                DbError::InvalidData(format!("Usuario asignado desconocido: {}", asignado_id))
+            })?;
+    }
+
+    Ok(ListItemDto {
+        texto,
+        fecha_limite,
+        asignado_id: dto.asignado_id,
+        prioridad: Some(prioridad),
+    })
+}
+
+fn local_now() -> (String, String) {
+    let ahora = Local::now().naive_local();
+    (
+        ahora.format("%Y-%m-%d").to_string(),
+        ahora.format("%Y-%m-%dT%H:%M:%S").to_string(),
+    )
+}
+
+async fn get_list_panel(pool: &SqlitePool, panel_id: &str, user_id: &str) -> DbResult<()> {
+    let (tipo,): (String,) = sqlx::query_as(
+        r#"
+        SELECT p.tipo
+        FROM panels p
+        JOIN boards b ON b.id = p.board_id
+        WHERE p.id = ? AND b.user_id = ?
+        "#,
+    )
+    .bind(panel_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(DbError::Unauthorized)?;
+
+    if PanelKind::try_from(tipo)? != PanelKind::Lista {
+        return Err(DbError::InvalidData(
+            "El panel no es un panel de lista".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+async fn get_owned_item(pool: &SqlitePool, item_id: &str, user_id: &str) -> DbResult<()> {
+    let _: (String,) = sqlx::query_as(
+        r#"
+        SELECT i.id
+        FROM panel_list_items i
+        JOIN panels p ON p.id = i.panel_id
+        JOIN boards b ON b.id = p.board_id
+        WHERE i.id = ? AND b.user_id = ?
+        "#,
+    )
+    .bind(item_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(DbError::Unauthorized)?;
+
+    Ok(())
+}
+
+async fn get_list_item(pool: &SqlitePool, item_id: &str) -> DbResult<ListItem> {
+    let (hoy, ahora) = local_now();
+
+    sqlx::query_as(&format!(
+        r#"
+        SELECT {}
+        FROM panel_list_items i
+        LEFT JOIN users u ON u.id = i.asignado_id
+        WHERE i.id = ?
+        "#,
+        ITEM_COLUMNS
+    ))
+    .bind(&hoy)
+    .bind(&ahora)
+    .bind(item_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(DbError::NotFound)
+}
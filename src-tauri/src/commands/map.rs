@@ -0,0 +1,509 @@
+use crate::commands::geo::load_map_panel;
+use crate::commands::pivot::{Accumulator, AGREGACIONES};
+use crate::commands::stats::{valor_numerico, valor_texto};
+use crate::models::{
+    database::{AppStateHandle, DbError, DbResult},
+    geo::{GeoChoroplethQuery, GeoChoroplethResult, MapPack, MapPackStore},
+};
+use flate2::read::GzDecoder;
+use serde_json::{json, Value};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use std::collections::{BTreeSet, HashMap};
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use tauri::http::{header, Request, Response, StatusCode};
+use tauri::State;
+
+// Esquema del protocolo propio con el que el frontend pide límites y teselas
+pub const PROTOCOLO_MAPAS: &str = "mapas";
+const MANIFIESTO: &str = "paquetes.json";
+const TIPOS_PAQUETE: [&str; 2] = ["limites", "teselas"];
+const PROPIEDAD_CODIGO: &str = "codigo";
+const MAX_TESELA: u64 = 8 * 1024 * 1024;
+
+#[tauri::command]
+pub async fn list_map_packs(store: State<'_, MapPackStore>) -> DbResult<Vec<MapPack>> {
+    Ok(refresh_map_packs(&store).await)
+}
+
+// Coropleta del panel de mapa: agrega las filas por código de región y
+// devuelve las regiones del paquete de límites con su valor
+#[tauri::command]
+pub async fn get_boundary_choropleth(
+    state: AppStateHandle<'_>,
+    store: State<'_, MapPackStore>,
+    user_id: String,
+    panel_id: String,
+    query: GeoChoroplethQuery,
+) -> DbResult<GeoChoroplethResult> {
+    let (config, _, filas) = load_map_panel(state, &user_id, &panel_id).await?;
+
+    let Some(limites) = query.limites.or(config.limites) else {
+        return Err(DbError::InvalidData(
+            "El panel no tiene un paquete de límites".to_string(),
+        ));
+    };
+    let Some(columna_codigo) = query.columna_codigo.or(config.columna_codigo) else {
+        return Err(DbError::InvalidData(
+            "El panel no tiene columna de código de región".to_string(),
+        ));
+    };
+    let medida = query.medida.or(config.medida);
+    let agregacion = query
+        .agregacion
+        .or(config.agregacion)
+        .unwrap_or_else(|| "count".to_string());
+    if !AGREGACIONES.contains(&agregacion.as_str()) {
+        return Err(DbError::InvalidData(format!(
+            "Agregación desconocida: {}",
+            agregacion
+        )));
+    }
+    if agregacion != "count" && medida.is_none() {
+        return Err(DbError::InvalidData(
+            "La agregación requiere una medida".to_string(),
+        ));
+    }
+
+    let paquete = find_map_pack(&store, &limites, "limites").await?;
+    let geojson = load_boundaries(&store, &paquete).await?;
+    let propiedad_codigo = paquete
+        .propiedad_codigo
+        .as_deref()
+        .unwrap_or(PROPIEDAD_CODIGO);
+
+    // Acumulado por código normalizado, junto al código tal como viene en el dataset
+    let mut acumulados: HashMap<String, (String, Accumulator)> = HashMap::new();
+    for fila in &filas {
+        let Some(codigo) = fila.get(&columna_codigo).filter(|c| !c.is_null()) else {
+            continue;
+        };
+        let codigo = valor_texto(codigo);
+        acumulados
+            .entry(region_code(&codigo))
+            .or_insert_with(|| (codigo.trim().to_string(), Accumulator::default()))
+            .1
+            .add(
+                medida
+                    .as_ref()
+                    .and_then(|m| fila.get(m))
+                    .and_then(valor_numerico),
+            );
+    }
+
+    let mut emparejados = BTreeSet::new();
+    let mut min: Option<f64> = None;
+    let mut max: Option<f64> = None;
+    let mut features = Vec::new();
+    for feature in geojson
+        .get("features")
+        .and_then(|f| f.as_array())
+        .into_iter()
+        .flatten()
+    {
+        let propiedades = feature.get("properties");
+        let codigo = propiedades
+            .and_then(|p| p.get(propiedad_codigo))
+            .map(valor_texto)
+            .unwrap_or_default();
+        let nombre = paquete
+            .propiedad_nombre
+            .as_deref()
+            .and_then(|n| propiedades.and_then(|p| p.get(n)))
+            .map(valor_texto);
+
+        let clave = region_code(&codigo);
+        let (valor, conteo) = match acumulados.get(&clave) {
+            Some((_, acumulado)) => {
+                emparejados.insert(clave);
+                (acumulado.value(&agregacion), acumulado.registros)
+            }
+            None => (None, 0),
+        };
+        if let Some(v) = valor {
+            min = Some(min.map_or(v, |m| m.min(v)));
+            max = Some(max.map_or(v, |m| m.max(v)));
+        }
+
+        features.push(json!({
+            "type": "Feature",
+            "properties": {
+                "codigo": codigo,
+                "nombre": nombre,
+                "valor": valor,
+                "conteo": conteo,
+            },
+            "geometry": feature.get("geometry"),
+        }));
+    }
+
+    let sin_coincidencia = acumulados
+        .iter()
+        .filter(|(clave, _)| !emparejados.contains(*clave))
+        .map(|(_, (codigo, _))| codigo.clone())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    Ok(GeoChoroplethResult {
+        limites,
+        geojson: json!({ "type": "FeatureCollection", "features": features }),
+        min,
+        max,
+        sin_coincidencia,
+    })
+}
+
+// Responde a las peticiones del protocolo `mapas`:
+//   limites/{paquete}              GeoJSON del paquete de límites
+//   teselas/{paquete}              TileJSON del paquete de teselas
+//   teselas/{paquete}/{z}/{x}/{y}  Tesela (la extensión es opcional)
+pub async fn map_protocol_response(
+    store: &MapPackStore,
+    request: &Request<Vec<u8>>,
+) -> Response<Vec<u8>> {
+    let uri = request.uri();
+    let partes: Vec<&str> = uri.path().split('/').filter(|p| !p.is_empty()).collect();
+
+    let respuesta = match partes.as_slice() {
+        ["limites", id] => serve_boundaries(store, id).await,
+        ["teselas", id] => {
+            // En Windows el protocolo llega como http://mapas.localhost
+            let base = format!(
+                "{}://{}",
+                uri.scheme_str().unwrap_or(PROTOCOLO_MAPAS),
+                uri.authority().map(|a| a.as_str()).unwrap_or("localhost")
+            );
+            serve_tilejson(store, id, &base).await
+        }
+        ["teselas", id, z, x, y] => serve_tile(store, id, z, x, y).await,
+        _ => Err(DbError::NotFound),
+    };
+
+    respuesta.unwrap_or_else(|e| {
+        let estado = match e {
+            DbError::NotFound => StatusCode::NOT_FOUND,
+            DbError::InvalidData(_) => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        protocol_response(estado, "text/plain", e.to_string().into_bytes())
+    })
+}
+
+async fn serve_boundaries(store: &MapPackStore, id: &str) -> DbResult<Response<Vec<u8>>> {
+    let paquete = find_map_pack(store, id, "limites").await?;
+    let geojson = load_boundaries(store, &paquete).await?;
+
+    Ok(protocol_response(
+        StatusCode::OK,
+        "application/geo+json",
+        geojson.to_string().into_bytes(),
+    ))
+}
+
+async fn serve_tilejson(store: &MapPackStore, id: &str, base: &str) -> DbResult<Response<Vec<u8>>> {
+    let paquete = find_map_pack(store, id, "teselas").await?;
+    let pool = tiles_pool(store, &paquete).await?;
+
+    let metadatos: HashMap<String, String> =
+        sqlx::query_as::<_, (String, String)>("SELECT name, value FROM metadata")
+            .fetch_all(&pool)
+            .await?
+            .into_iter()
+            .collect();
+    let numeros = |clave: &str| -> Option<Vec<f64>> {
+        metadatos
+            .get(clave)?
+            .split(',')
+            .map(|n| n.trim().parse().ok())
+            .collect()
+    };
+    // Las teselas vectoriales describen sus capas en `json`
+    let capas = metadatos
+        .get("json")
+        .and_then(|j| serde_json::from_str::<Value>(j).ok())
+        .and_then(|j| j.get("vector_layers").cloned());
+
+    let tilejson = json!({
+        "tilejson": "3.0.0",
+        "name": metadatos.get("name").unwrap_or(&paquete.nombre),
+        "attribution": paquete.atribucion.as_ref().or(metadatos.get("attribution")),
+        "format": metadatos.get("format").map(String::as_str).unwrap_or("png"),
+        "scheme": "xyz",
+        "tiles": [format!("{}/teselas/{}/{{z}}/{{x}}/{{y}}", base, paquete.id)],
+        "minzoom": metadatos.get("minzoom").and_then(|z| z.parse::<u32>().ok()).unwrap_or(0),
+        "maxzoom": metadatos.get("maxzoom").and_then(|z| z.parse::<u32>().ok()).unwrap_or(14),
+        "bounds": numeros("bounds"),
+        "center": numeros("center"),
+        "vector_layers": capas,
+    });
+
+    Ok(protocol_response(
+        StatusCode::OK,
+        "application/json",
+        tilejson.to_string().into_bytes(),
+    ))
+}
+
+async fn serve_tile(
+    store: &MapPackStore,
+    id: &str,
+    z: &str,
+    x: &str,
+    y: &str,
+) -> DbResult<Response<Vec<u8>>> {
+    let coordenada = |valor: &str| -> DbResult<u32> {
+        valor
+            .split('.')
+            .next()
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(|| {
+                DbError::InvalidData(format!("Coordenada de tesela inválida: {}", valor))
+            })
+    };
+    let (z, x, y) = (coordenada(z)?, coordenada(x)?, coordenada(y)?);
+    if z > 24 || x >= 1 << z || y >= 1 << z {
+        return Err(DbError::InvalidData(format!(
+            "Tesela fuera de rango: {}/{}/{}",
+            z, x, y
+        )));
+    }
+
+    let paquete = find_map_pack(store, id, "teselas").await?;
+    let pool = tiles_pool(store, &paquete).await?;
+
+    // MBTiles numera las filas desde el sur (esquema TMS)
+    let tesela: Option<(Vec<u8>,)> = sqlx::query_as(
+        "SELECT tile_data FROM tiles WHERE zoom_level = ? AND tile_column = ? AND tile_row = ?",
+    )
+    .bind(z)
+    .bind(x)
+    .bind((1u32 << z) - 1 - y)
+    .fetch_optional(&pool)
+    .await?;
+
+    // Sin tesela se responde vacío para que el mapa no lo trate como error
+    let Some((datos,)) = tesela else {
+        return Ok(protocol_response(
+            StatusCode::NO_CONTENT,
+            "text/plain",
+            Vec::new(),
+        ));
+    };
+
+    // Las teselas vectoriales suelen guardarse comprimidas con gzip. Se
+    // descomprimen aquí porque no todos los webviews aplican Content-Encoding
+    // a un protocolo propio, con un límite para no agotar la memoria
+    let datos = if datos.starts_with(&[0x1f, 0x8b]) {
+        let mut descomprimidos = Vec::new();
+        GzDecoder::new(datos.as_slice())
+            .take(MAX_TESELA + 1)
+            .read_to_end(&mut descomprimidos)
+            .map_err(|e| DbError::InvalidData(format!("Tesela comprimida inválida: {}", e)))?;
+        if descomprimidos.len() as u64 > MAX_TESELA {
+            return Err(DbError::InvalidData(format!(
+                "La tesela {}/{}/{} supera el tamaño máximo",
+                z, x, y
+            )));
+        }
+        descomprimidos
+    } else {
+        datos
+    };
+
+    Ok(protocol_response(
+        StatusCode::OK,
+        tile_content_type(&datos),
+        datos,
+    ))
+}
+
+fn tile_content_type(datos: &[u8]) -> &'static str {
+    if datos.starts_with(&[0x89, b'P', b'N', b'G']) {
+        "image/png"
+    } else if datos.starts_with(&[0xff, 0xd8, 0xff]) {
+        "image/jpeg"
+    } else if datos.starts_with(b"RIFF") && datos.get(8..12) == Some(b"WEBP") {
+        "image/webp"
+    } else {
+        "application/x-protobuf"
+    }
+}
+
+fn protocol_response(estado: StatusCode, tipo: &str, cuerpo: Vec<u8>) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(estado)
+        .header(header::CONTENT_TYPE, tipo)
+        // El frontend pide al protocolo desde su propio origen
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(cuerpo)
+        .unwrap_or_default()
+}
+
+// Vuelve a leer los manifiestos; así se detectan los paquetes instalados
+// después de abrir la app
+pub(crate) async fn refresh_map_packs(store: &MapPackStore) -> Vec<MapPack> {
+    let paquetes = read_map_packs(&store.directorios).await;
+    *store.paquetes.write().await = Some(paquetes.clone());
+    paquetes
+}
+
+// Paquetes de todas las carpetas de mapas; un id repetido en una carpeta
+// posterior (los datos de la app) sustituye al de los recursos incluidos
+async fn read_map_packs(directorios: &[(PathBuf, String)]) -> Vec<MapPack> {
+    let mut paquetes: Vec<MapPack> = Vec::new();
+
+    for (directorio, origen) in directorios {
+        let manifiesto = directorio.join(MANIFIESTO);
+        let Ok(contenido) = tokio::fs::read_to_string(&manifiesto).await else {
+            continue;
+        };
+        let declarados: Vec<MapPack> = match serde_json::from_str(&contenido) {
+            Ok(declarados) => declarados,
+            Err(e) => {
+                log::warn!(
+                    "Manifiesto de mapas inválido {}: {}",
+                    manifiesto.display(),
+                    e
+                );
+                continue;
+            }
+        };
+
+        for mut paquete in declarados {
+            if !TIPOS_PAQUETE.contains(&paquete.tipo.as_str()) {
+                log::warn!(
+                    "Paquete de mapas {} con tipo desconocido: {}",
+                    paquete.id,
+                    paquete.tipo
+                );
+                continue;
+            }
+            paquete.ruta = directorio.join(&paquete.fichero);
+            // El fichero debe quedar dentro de la carpeta del manifiesto
+            paquete.disponible = Path::new(&paquete.fichero)
+                .components()
+                .all(|c| matches!(c, Component::Normal(_)))
+                && tokio::fs::metadata(&paquete.ruta)
+                    .await
+                    .is_ok_and(|m| m.is_file());
+            paquete.origen = origen.clone();
+
+            paquetes.retain(|p| p.id != paquete.id);
+            paquetes.push(paquete);
+        }
+    }
+
+    paquetes
+}
+
+async fn find_map_pack(store: &MapPackStore, id: &str, tipo: &str) -> DbResult<MapPack> {
+    let cargados = store.paquetes.read().await.is_some();
+    if !cargados {
+        refresh_map_packs(store).await;
+    }
+    let paquete = store
+        .paquetes
+        .read()
+        .await
+        .iter()
+        .flatten()
+        .find(|p| p.id == id)
+        .cloned()
+        .ok_or(DbError::NotFound)?;
+
+    if paquete.tipo != tipo {
+        return Err(DbError::InvalidData(format!(
+            "El paquete {} no es de {}",
+            id, tipo
+        )));
+    }
+    if !paquete.disponible {
+        return Err(DbError::InvalidData(format!(
+            "El fichero del paquete {} no está instalado",
+            id
+        )));
+    }
+    Ok(paquete)
+}
+
+// El GeoJSON de cada fichero se lee una sola vez
+async fn load_boundaries(store: &MapPackStore, paquete: &MapPack) -> DbResult<Arc<Value>> {
+    let mut limites = store.limites.lock().await;
+    if let Some(geojson) = limites.get(&paquete.ruta) {
+        return Ok(geojson.clone());
+    }
+
+    let contenido = tokio::fs::read_to_string(&paquete.ruta)
+        .await
+        .map_err(|e| DbError::InvalidData(format!("No se pudo leer {}: {}", paquete.fichero, e)))?;
+    let geojson: Value = serde_json::from_str(&contenido)
+        .map_err(|e| DbError::InvalidData(format!("GeoJSON inválido en {}: {}", paquete.id, e)))?;
+    if geojson.get("type").and_then(|t| t.as_str()) != Some("FeatureCollection") {
+        return Err(DbError::InvalidData(format!(
+            "El paquete {} no es un FeatureCollection",
+            paquete.id
+        )));
+    }
+
+    let geojson = Arc::new(geojson);
+    limites.insert(paquete.ruta.clone(), geojson.clone());
+    Ok(geojson)
+}
+
+// Cada MBTiles se abre en solo lectura y su conexión se reutiliza
+async fn tiles_pool(store: &MapPackStore, paquete: &MapPack) -> DbResult<SqlitePool> {
+    let mut teselas = store.teselas.lock().await;
+    if let Some(pool) = teselas.get(&paquete.ruta) {
+        return Ok(pool.clone());
+    }
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(2)
+        .connect_with(
+            SqliteConnectOptions::new()
+                .filename(&paquete.ruta)
+                .read_only(true),
+        )
+        .await?;
+
+    teselas.insert(paquete.ruta.clone(), pool.clone());
+    Ok(pool)
+}
+
+// Los códigos se comparan sin espacios ni mayúsculas y los numéricos sin
+// ceros a la izquierda, para que '09', 9 y 9.0 sean la misma región
+fn region_code(codigo: &str) -> String {
+    let codigo = codigo.trim().to_uppercase();
+    if !codigo
+        .chars()
+        .all(|c| c.is_ascii_digit() || c == '.' || c == '-')
+    {
+        return codigo;
+    }
+    match codigo.parse::<f64>() {
+        Ok(n) if n.fract() == 0.0 && n.abs() < 1e15 => format!("{}", n as i64),
+        _ => codigo,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn region_code_normaliza_codigos() {
+        assert_eq!(region_code("09"), "9");
+        assert_eq!(region_code("9.0"), "9");
+        assert_eq!(region_code(" 9 "), "9");
+        assert_eq!(region_code("-0"), "0");
+        assert_eq!(region_code("9.5"), "9.5");
+        assert_eq!(region_code(" es-md "), "ES-MD");
+        assert_eq!(region_code("1e3"), "1E3");
+        assert_eq!(region_code("-"), "-");
+        assert_eq!(region_code(""), "");
+        // Los códigos muy largos no pasan por f64 para no perder dígitos
+        assert_eq!(region_code("01234567890123456789"), "01234567890123456789");
+    }
+}
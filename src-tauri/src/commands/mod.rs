@@ -1,3 +1,21 @@
 pub mod board;
 pub mod stats;
-pub mod auth;
\ No newline at end of file
+pub mod auth;
+pub mod alert;
+pub mod pivot;
+pub mod filter;
+pub mod variable;
+pub mod report;
+pub mod schedule;
+pub mod search;
+pub mod organization;
+pub mod panel;
+pub mod layout;
+pub mod page;
+pub mod preference;
+pub mod note;
+pub mod calendar;
+pub mod ics;
+pub mod list;
+pub mod geo;
+pub mod map;
\ No newline at end of file
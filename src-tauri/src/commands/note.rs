@@ -0,0 +1,478 @@
+use crate::commands::panel::upgrade_panel_config;
+use crate::models::{
+    board::Panel,
+    database::{AppState, AppStateHandle, DbError, DbResult},
+    note::{AddNoteAttachmentDto, NoteAttachment, NoteRevision, PanelNote, UpdatePanelNoteDto},
+    panel::PanelKind,
+};
+use chrono::Utc;
+use sqlx::SqlitePool;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use uuid::Uuid;
+
+// Versiones que se guardan por nota; las más antiguas se descartan
+const MAX_REVISIONES: i64 = 50;
+const TAMANO_MAXIMO_ADJUNTO: usize = 10 * 1024 * 1024;
+// Antigüedad mínima de un fichero sin fila para borrarlo al limpiar adjuntos
+const PERIODO_GRACIA_ADJUNTOS: Duration = Duration::from_secs(60 * 60);
+
+#[tauri::command]
+pub async fn get_panel_note(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    panel_id: String,
+) -> DbResult<PanelNote> {
+    let pool = &state.pool;
+    let config = get_notes_panel(pool, &panel_id, &user_id).await?;
+
+    load_panel_note(pool, &panel_id, &config).await
+}
+
+// Guarda la nota solo si nadie la cambió desde el `updatedAt` que leyó el cliente
+#[tauri::command]
+pub async fn update_panel_note(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    panel_id: String,
+    dto: UpdatePanelNoteDto,
+) -> DbResult<PanelNote> {
+    let pool = &state.pool;
+    let config = get_notes_panel(pool, &panel_id, &user_id).await?;
+
+    save_panel_note(
+        pool,
+        &panel_id,
+        &user_id,
+        &dto.contenido,
+        dto.updated_at.as_deref(),
+    )
+    .await?;
+
+    load_panel_note(pool, &panel_id, &config).await
+}
+
+// Versiones guardadas de la nota, de la más reciente a la más antigua
+#[tauri::command]
+pub async fn get_note_revisions(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    panel_id: String,
+) -> DbResult<Vec<NoteRevision>> {
+    let pool = &state.pool;
+    get_notes_panel(pool, &panel_id, &user_id).await?;
+
+    let revisions: Vec<NoteRevision> = sqlx::query_as(
+        r#"
+        SELECT id, panel_id, user_id, contenido, created_at
+        FROM panel_note_revisions
+        WHERE panel_id = ?
+        ORDER BY created_at DESC
+        "#,
+    )
+    .bind(&panel_id)
+    .fetch_all(&**pool)
+    .await?;
+
+    Ok(revisions)
+}
+
+// Vuelve a una versión anterior guardándola como una versión nueva
+#[tauri::command]
+pub async fn restore_note_revision(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    revision_id: String,
+    updated_at: Option<String>,
+) -> DbResult<PanelNote> {
+    let pool = &state.pool;
+
+    let (panel_id, contenido): (String, String) =
+        sqlx::query_as("SELECT panel_id, contenido FROM panel_note_revisions WHERE id = ?")
+            .bind(&revision_id)
+            .fetch_optional(&**pool)
+            .await?
+            .ok_or(DbError::NotFound)?;
+    let config = get_notes_panel(pool, &panel_id, &user_id).await?;
+
+    save_panel_note(pool, &panel_id, &user_id, &contenido, updated_at.as_deref()).await?;
+
+    load_panel_note(pool, &panel_id, &config).await
+}
+
+// Guarda una imagen en el directorio de datos de la app y la asocia a la nota
+#[tauri::command]
+pub async fn add_note_attachment(
+    app: AppHandle,
+    state: AppStateHandle<'_>,
+    user_id: String,
+    panel_id: String,
+    dto: AddNoteAttachmentDto,
+) -> DbResult<NoteAttachment> {
+    let pool = &state.pool;
+    get_notes_panel(pool, &panel_id, &user_id).await?;
+
+    let nombre = dto.nombre.trim();
+    if nombre.is_empty() {
+        return Err(DbError::InvalidData(
+            "El adjunto necesita un nombre".to_string(),
+        ));
+    }
+    if dto.datos.len() > TAMANO_MAXIMO_ADJUNTO {
+        return Err(DbError::InvalidData(format!(
+            "El adjunto supera el máximo de {} MB",
+            TAMANO_MAXIMO_ADJUNTO / (1024 * 1024)
+        )));
+    }
+    let (mime, extension) = image_type(&dto.datos).ok_or_else(|| {
+        DbError::InvalidData("Solo se admiten imágenes PNG, JPEG, GIF o WebP".to_string())
+    })?;
+
+    let id = Uuid::new_v4().to_string();
+    let directorio = attachments_dir(&app)?.join(&panel_id);
+    let ruta = directorio.join(format!("{}.{}", id, extension));
+    std::fs::create_dir_all(&directorio).map_err(|e| {
+        DbError::InvalidData(format!("No se pudo crear {}: {}", directorio.display(), e))
+    })?;
+    std::fs::write(&ruta, &dto.datos).map_err(|e| {
+        DbError::InvalidData(format!("No se pudo escribir {}: {}", ruta.display(), e))
+    })?;
+
+    let attachment = NoteAttachment {
+        id,
+        panel_id,
+        nombre: nombre.to_string(),
+        mime: mime.to_string(),
+        tamano: dto.datos.len() as i64,
+        ruta: ruta.to_string_lossy().to_string(),
+        created_at: Utc::now().to_rfc3339(),
+    };
+
+    let insertado = sqlx::query(
+        r#"
+        INSERT INTO panel_note_attachments (id, panel_id, nombre, mime, tamano, ruta, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&attachment.id)
+    .bind(&attachment.panel_id)
+    .bind(&attachment.nombre)
+    .bind(&attachment.mime)
+    .bind(attachment.tamano)
+    .bind(&attachment.ruta)
+    .bind(&attachment.created_at)
+    .execute(&**pool)
+    .await;
+
+    if let Err(e) = insertado {
+        let _ = std::fs::remove_file(&ruta);
+        return Err(e.into());
+    }
+
+    Ok(attachment)
+}
+
+#[tauri::command]
+pub async fn delete_note_attachment(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    attachment_id: String,
+) -> DbResult<()> {
+    let pool = &state.pool;
+
+    let (ruta,): (String,) = sqlx::query_as(
+        r#"
+        SELECT a.ruta
+        FROM panel_note_attachments a
+        JOIN panels p ON p.id = a.panel_id
+        JOIN boards b ON b.id = p.board_id
+        WHERE a.id = ? AND b.user_id = ?
+        "#,
+    )
+    .bind(&attachment_id)
+    .bind(&user_id)
+    .fetch_optional(&**pool)
+    .await?
+    .ok_or(DbError::Unauthorized)?;
+
+    sqlx::query("DELETE FROM panel_note_attachments WHERE id = ?")
+        .bind(&attachment_id)
+        .execute(&**pool)
+        .await?;
+
+    if let Err(e) = std::fs::remove_file(&ruta) {
+        log::warn!("No se pudo borrar el adjunto {}: {}", ruta, e);
+    }
+
+    Ok(())
+}
+
+// Al borrar paneles o boards las filas caen en cascada; aquí se borran los
+// ficheros que ya no tienen fila
+pub async fn prune_note_attachments(app: AppHandle) {
+    let state = app.state::<AppState>();
+    if let Err(e) = prune_orphan_attachments(&app, &state.pool).await {
+        log::error!("Error al limpiar adjuntos de notas: {}", e);
+    }
+}
+
+// Usa el contenido guardado de la nota en lugar del `config.contenido` del panel
+pub(crate) async fn apply_note_content(pool: &SqlitePool, panel: &mut Panel) -> DbResult<()> {
+    if panel.tipo != PanelKind::Notas {
+        return Ok(());
+    }
+
+    let contenido: Option<(String,)> =
+        sqlx::query_as("SELECT contenido FROM panel_notes WHERE panel_id = ?")
+            .bind(&panel.id)
+            .fetch_optional(pool)
+            .await?;
+
+    if let (Some((contenido,)), Some(config)) = (contenido, panel.config.as_object_mut()) {
+        config.insert("contenido".to_string(), serde_json::json!(contenido));
+    }
+    Ok(())
+}
+
+async fn prune_orphan_attachments(app: &AppHandle, pool: &SqlitePool) -> DbResult<()> {
+    let directorio = attachments_dir(app)?;
+    let Ok(paneles) = std::fs::read_dir(&directorio) else {
+        return Ok(());
+    };
+
+    let rutas: Vec<(String,)> = sqlx::query_as("SELECT ruta FROM panel_note_attachments")
+        .fetch_all(pool)
+        .await?;
+    let rutas: HashSet<PathBuf> = rutas.into_iter().map(|(r,)| PathBuf::from(r)).collect();
+
+    for panel in paneles.flatten() {
+        let Ok(ficheros) = std::fs::read_dir(panel.path()) else {
+            continue;
+        };
+        let mut vacio = true;
+        for fichero in ficheros.flatten() {
+            let ruta = fichero.path();
+            if rutas.contains(&ruta) || is_recent(&fichero) {
+                vacio = false;
+            } else if let Err(e) = std::fs::remove_file(&ruta) {
+                log::warn!("No se pudo borrar {}: {}", ruta.display(), e);
+            }
+        }
+        if vacio {
+            let _ = std::fs::remove_dir(panel.path());
+        }
+    }
+
+    Ok(())
+}
+
+// Un adjunto recién escrito puede no tener fila todavía; se respeta un margen
+fn is_recent(fichero: &std::fs::DirEntry) -> bool {
+    fichero
+        .metadata()
+        .and_then(|m| m.modified())
+        .map(|modificado| {
+            modificado
+                .elapsed()
+                .map_or(true, |edad| edad < PERIODO_GRACIA_ADJUNTOS)
+        })
+        .unwrap_or(true)
+}
+
+fn attachments_dir(app: &AppHandle) -> DbResult<PathBuf> {
+    let app_dir = app.path().app_data_dir().map_err(|e| {
+        DbError::InvalidData(format!("No se pudo obtener el directorio de datos: {}", e))
+    })?;
+    Ok(app_dir.join("adjuntos"))
+}
+
+// Tipo de imagen según los primeros bytes del fichero
+fn image_type(datos: &[u8]) -> Option<(&'static str, &'static str)> {
+    if datos.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some(("image/png", "png"))
+    } else if datos.starts_with(b"\xff\xd8\xff") {
+        Some(("image/jpeg", "jpg"))
+    } else if datos.starts_with(b"GIF87a") || datos.starts_with(b"GIF89a") {
+        Some(("image/gif", "gif"))
+    } else if datos.len() >= 12 && &datos[..4] == b"RIFF" && &datos[8..12] == b"WEBP" {
+        Some(("image/webp", "webp"))
+    } else {
+        None
+    }
+}
+
+// Comprueba que el panel es una nota del usuario y devuelve su config
+async fn get_notes_panel(
+    pool: &SqlitePool,
+    panel_id: &str,
+    user_id: &str,
+) -> DbResult<serde_json::Value> {
+    let (tipo, config): (String, Option<String>) = sqlx::query_as(
+        r#"
+        SELECT p.tipo, p.config
+        FROM panels p
+        JOIN boards b ON b.id = p.board_id
+        WHERE p.id = ? AND b.user_id = ?
+        "#,
+    )
+    .bind(panel_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(DbError::Unauthorized)?;
+
+    if PanelKind::try_from(tipo)? != PanelKind::Notas {
+        return Err(DbError::InvalidData(
+            "El panel no es un panel de notas".to_string(),
+        ));
+    }
+
+    let config = config
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_else(|| serde_json::json!({}));
+    Ok(upgrade_panel_config(PanelKind::Notas, config))
+}
+
+// Nota guardada o, si aún no existe, la creada a partir de `config.contenido`
+async fn load_panel_note(
+    pool: &SqlitePool,
+    panel_id: &str,
+    config: &serde_json::Value,
+) -> DbResult<PanelNote> {
+    let note: Option<PanelNote> = sqlx::query_as(
+        "SELECT panel_id, contenido, created_at, updated_at FROM panel_notes WHERE panel_id = ?",
+    )
+    .bind(panel_id)
+    .fetch_optional(pool)
+    .await?;
+
+    let mut note = note.unwrap_or_else(|| PanelNote {
+        panel_id: panel_id.to_string(),
+        contenido: config
+            .get("contenido")
+            .and_then(|c| c.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        adjuntos: Vec::new(),
+        created_at: None,
+        updated_at: None,
+    });
+
+    note.adjuntos = sqlx::query_as(
+        r#"
+        SELECT id, panel_id, nombre, mime, tamano, ruta, created_at
+        FROM panel_note_attachments
+        WHERE panel_id = ?
+        ORDER BY created_at
+        "#,
+    )
+    .bind(panel_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(note)
+}
+
+// Cada guardado con cambios crea una versión nueva con su autor
+async fn save_panel_note(
+    pool: &SqlitePool,
+    panel_id: &str,
+    user_id: &str,
+    contenido: &str,
+    leido: Option<&str>,
+) -> DbResult<()> {
+    let now = Utc::now().to_rfc3339();
+    let mut tx = pool.begin().await?;
+
+    // La escritura solo ocurre si la nota sigue como la leyó el cliente, así dos
+    // guardados simultáneos no pueden pisarse
+    let escrita = match leido {
+        Some(leido) => {
+            sqlx::query(
+                r#"
+            UPDATE panel_notes SET contenido = ?, updated_at = ?
+            WHERE panel_id = ? AND updated_at = ? AND contenido <> ?
+            "#,
+            )
+            .bind(contenido)
+            .bind(&now)
+            .bind(panel_id)
+            .bind(leido)
+            .bind(contenido)
+            .execute(&mut *tx)
+            .await?
+        }
+        None => {
+            sqlx::query(
+                r#"
+            INSERT INTO panel_notes (panel_id, contenido, created_at, updated_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT (panel_id) DO NOTHING
+            "#,
+            )
+            .bind(panel_id)
+            .bind(contenido)
+            .bind(&now)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await?
+        }
+    };
+
+    if escrita.rows_affected() == 0 {
+        // Sin cambios no es un conflicto: la nota sigue en la versión leída
+        let sin_cambios: Option<(i64,)> = match leido {
+            Some(leido) => {
+                sqlx::query_as("SELECT 1 FROM panel_notes WHERE panel_id = ? AND updated_at = ?")
+                    .bind(panel_id)
+                    .bind(leido)
+                    .fetch_optional(&mut *tx)
+                    .await?
+            }
+            None => None,
+        };
+        if sin_cambios.is_some() {
+            return Ok(());
+        }
+        return Err(DbError::InvalidData(
+            "La nota cambió desde que se abrió; recárgala antes de guardar".to_string(),
+        ));
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO panel_note_revisions (id, panel_id, user_id, contenido, created_at)
+        VALUES (?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(panel_id)
+    .bind(user_id)
+    .bind(contenido)
+    .bind(&now)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM panel_note_revisions
+        WHERE panel_id = ? AND id NOT IN (
+            SELECT id FROM panel_note_revisions
+            WHERE panel_id = ?
+            ORDER BY created_at DESC
+            LIMIT ?
+        )
+        "#,
+    )
+    .bind(panel_id)
+    .bind(panel_id)
+    .bind(MAX_REVISIONES)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
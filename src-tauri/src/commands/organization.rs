@@ -0,0 +1,331 @@
+use crate::commands::board::ensure_board_owner;
+use crate::models::{
+    board::BoardFolder,
+    database::{AppStateHandle, DbError, DbResult},
+};
+use chrono::Utc;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+#[tauri::command]
+pub async fn get_board_folders(
+    state: AppStateHandle<'_>,
+    user_id: String,
+) -> DbResult<Vec<BoardFolder>> {
+    let pool = &state.pool;
+
+    // Lista plana; el frontend arma el árbol con parent_id
+    let folders: Vec<BoardFolder> = sqlx::query_as(
+        r#"
+        SELECT id, user_id, nombre, parent_id, orden, created_at, updated_at
+        FROM board_folders
+        WHERE user_id = ?
+        ORDER BY orden, nombre COLLATE NOCASE
+        "#,
+    )
+    .bind(&user_id)
+    .fetch_all(&**pool)
+    .await?;
+
+    Ok(folders)
+}
+
+#[tauri::command]
+pub async fn create_board_folder(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    nombre: String,
+    parent_id: Option<String>,
+) -> DbResult<BoardFolder> {
+    let pool = &state.pool;
+    let nombre = validate_folder_name(&nombre)?;
+
+    if let Some(parent_id) = &parent_id {
+        get_folder(pool, parent_id, &user_id).await?;
+    }
+
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+
+    // Las carpetas nuevas van al final de su nivel
+    sqlx::query(
+        r#"
+        INSERT INTO board_folders (id, user_id, nombre, parent_id, orden, created_at, updated_at)
+        VALUES (?, ?, ?, ?, (
+            SELECT COALESCE(MAX(orden) + 1, 0) FROM board_folders
+            WHERE user_id = ? AND parent_id IS ?
+        ), ?, ?)
+        "#,
+    )
+    .bind(&id)
+    .bind(&user_id)
+    .bind(&nombre)
+    .bind(&parent_id)
+    .bind(&user_id)
+    .bind(&parent_id)
+    .bind(&now)
+    .bind(&now)
+    .execute(&**pool)
+    .await?;
+
+    get_folder(pool, &id, &user_id).await
+}
+
+#[tauri::command]
+pub async fn rename_board_folder(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    folder_id: String,
+    nombre: String,
+) -> DbResult<BoardFolder> {
+    let pool = &state.pool;
+    let nombre = validate_folder_name(&nombre)?;
+    get_folder(pool, &folder_id, &user_id).await?;
+
+    sqlx::query("UPDATE board_folders SET nombre = ?, updated_at = ? WHERE id = ?")
+        .bind(&nombre)
+        .bind(Utc::now().to_rfc3339())
+        .bind(&folder_id)
+        .execute(&**pool)
+        .await?;
+
+    get_folder(pool, &folder_id, &user_id).await
+}
+
+#[tauri::command]
+pub async fn move_board_folder(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    folder_id: String,
+    parent_id: Option<String>,
+) -> DbResult<BoardFolder> {
+    let pool = &state.pool;
+    get_folder(pool, &folder_id, &user_id).await?;
+
+    // Una carpeta no puede quedar dentro de sí misma ni de sus descendientes
+    let mut ancestro = parent_id.clone();
+    while let Some(id) = ancestro {
+        if id == folder_id {
+            return Err(DbError::InvalidData(
+                "Una carpeta no puede moverse dentro de sí misma".to_string(),
+            ));
+        }
+        ancestro = get_folder(pool, &id, &user_id).await?.parent_id;
+    }
+
+    sqlx::query("UPDATE board_folders SET parent_id = ?, updated_at = ? WHERE id = ?")
+        .bind(&parent_id)
+        .bind(Utc::now().to_rfc3339())
+        .bind(&folder_id)
+        .execute(&**pool)
+        .await?;
+
+    get_folder(pool, &folder_id, &user_id).await
+}
+
+// Borra la carpeta; sus boards y subcarpetas pasan a la carpeta superior
+#[tauri::command]
+pub async fn delete_board_folder(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    folder_id: String,
+) -> DbResult<()> {
+    let pool = &state.pool;
+    let folder = get_folder(pool, &folder_id, &user_id).await?;
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("UPDATE boards SET folder_id = ? WHERE folder_id = ?")
+        .bind(&folder.parent_id)
+        .bind(&folder_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("UPDATE board_folders SET parent_id = ? WHERE parent_id = ?")
+        .bind(&folder.parent_id)
+        .bind(&folder_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM board_folders WHERE id = ?")
+        .bind(&folder_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn move_board_to_folder(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    board_id: String,
+    folder_id: Option<String>,
+) -> DbResult<()> {
+    let pool = &state.pool;
+    ensure_board_owner(pool, &board_id, &user_id).await?;
+    if let Some(folder_id) = &folder_id {
+        get_folder(pool, folder_id, &user_id).await?;
+    }
+
+    sqlx::query("UPDATE boards SET folder_id = ? WHERE id = ?")
+        .bind(&folder_id)
+        .bind(&board_id)
+        .execute(&**pool)
+        .await?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_board_tags(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    board_id: String,
+    etiquetas: Vec<String>,
+) -> DbResult<Vec<String>> {
+    let pool = &state.pool;
+    ensure_board_owner(pool, &board_id, &user_id).await?;
+
+    // Sin vacías ni repetidas (sin distinguir mayúsculas), conservando el orden
+    let mut normalizadas: Vec<String> = Vec::new();
+    for etiqueta in etiquetas {
+        let etiqueta = etiqueta.trim();
+        if !etiqueta.is_empty()
+            && !normalizadas
+                .iter()
+                .any(|e| e.to_lowercase() == etiqueta.to_lowercase())
+        {
+            normalizadas.push(etiqueta.to_string());
+        }
+    }
+
+    sqlx::query("UPDATE boards SET etiquetas = ? WHERE id = ?")
+        .bind(serde_json::json!(normalizadas).to_string())
+        .bind(&board_id)
+        .execute(&**pool)
+        .await?;
+
+    Ok(normalizadas)
+}
+
+// Todas las etiquetas en uso por los boards del usuario, para autocompletar
+#[tauri::command]
+pub async fn get_board_tags(state: AppStateHandle<'_>, user_id: String) -> DbResult<Vec<String>> {
+    let pool = &state.pool;
+
+    let etiquetas: Vec<(String,)> = sqlx::query_as(
+        r#"
+        SELECT MIN(e.value)
+        FROM boards b, json_each(b.etiquetas) e
+        WHERE b.user_id = ?
+        GROUP BY lower(e.value)
+        ORDER BY lower(e.value)
+        "#,
+    )
+    .bind(&user_id)
+    .fetch_all(&**pool)
+    .await?;
+
+    Ok(etiquetas.into_iter().map(|(e,)| e).collect())
+}
+
+#[tauri::command]
+pub async fn set_board_favorite(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    board_id: String,
+    favorito: bool,
+) -> DbResult<()> {
+    let pool = &state.pool;
+    ensure_board_owner(pool, &board_id, &user_id).await?;
+
+    sqlx::query("UPDATE boards SET favorito = ? WHERE id = ?")
+        .bind(favorito)
+        .bind(&board_id)
+        .execute(&**pool)
+        .await?;
+
+    Ok(())
+}
+
+// Guarda el orden manual: cada board toma la posición que ocupa en la lista
+#[tauri::command]
+pub async fn reorder_boards(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    board_ids: Vec<String>,
+) -> DbResult<()> {
+    let pool = &state.pool;
+    let mut tx = pool.begin().await?;
+
+    for (orden, board_id) in board_ids.iter().enumerate() {
+        let result = sqlx::query("UPDATE boards SET orden = ? WHERE id = ? AND user_id = ?")
+            .bind(orden as i64)
+            .bind(board_id)
+            .bind(&user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(DbError::Unauthorized);
+        }
+    }
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+// Registra la apertura del board para el orden 'recientes'
+#[tauri::command]
+pub async fn mark_board_opened(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    board_id: String,
+) -> DbResult<()> {
+    let pool = &state.pool;
+
+    let result = sqlx::query("UPDATE boards SET abierto_at = ? WHERE id = ? AND user_id = ?")
+        .bind(Utc::now().to_rfc3339())
+        .bind(&board_id)
+        .bind(&user_id)
+        .execute(&**pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(DbError::NotFound);
+    }
+
+    Ok(())
+}
+
+fn validate_folder_name(nombre: &str) -> DbResult<String> {
+    let nombre = nombre.trim();
+    if nombre.is_empty() {
+        return Err(DbError::InvalidData(
+            "El nombre de la carpeta no puede estar vacío".to_string(),
+        ));
+    }
+    Ok(nombre.to_string())
+}
+
+async fn get_folder(pool: &SqlitePool, folder_id: &str, user_id: &str) -> DbResult<BoardFolder> {
+    let folder: BoardFolder = sqlx::query_as(
+        r#"
+        SELECT id, user_id, nombre, parent_id, orden, created_at, updated_at
+        FROM board_folders
+        WHERE id = ?
+        "#,
+    )
+    .bind(folder_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(DbError::NotFound)?;
+
+    if folder.user_id != user_id {
+        return Err(DbError::Unauthorized);
+    }
+
+    Ok(folder)
+}
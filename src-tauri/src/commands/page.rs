@@ -0,0 +1,343 @@
+use crate::commands::board::{ensure_board_owner, get_panel_with_data};
+use crate::commands::layout::{board_layout_rules, settle_board_layout};
+use crate::commands::panel::delete_empty_groups;
+use crate::models::{
+    board::{BoardPage, PageViewport, Panel},
+    database::{AppStateHandle, DbError, DbResult},
+};
+use chrono::Utc;
+use sqlx::{SqliteConnection, SqlitePool};
+use uuid::Uuid;
+
+#[tauri::command]
+pub async fn get_board_pages(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    board_id: String,
+) -> DbResult<Vec<BoardPage>> {
+    let pool = &state.pool;
+    ensure_board_owner(pool, &board_id, &user_id).await?;
+
+    load_board_pages(pool, &board_id).await
+}
+
+#[tauri::command]
+pub async fn create_board_page(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    board_id: String,
+    nombre: String,
+) -> DbResult<BoardPage> {
+    let pool = &state.pool;
+    ensure_board_owner(pool, &board_id, &user_id).await?;
+    let nombre = validate_page_name(&nombre)?;
+
+    let id = insert_board_page(&mut *pool.acquire().await?, &board_id, &nombre).await?;
+
+    get_page(pool, &id, &user_id).await
+}
+
+#[tauri::command]
+pub async fn rename_board_page(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    page_id: String,
+    nombre: String,
+) -> DbResult<BoardPage> {
+    let pool = &state.pool;
+    let nombre = validate_page_name(&nombre)?;
+    get_page(pool, &page_id, &user_id).await?;
+
+    sqlx::query("UPDATE board_pages SET nombre = ?, updated_at = ? WHERE id = ?")
+        .bind(&nombre)
+        .bind(Utc::now().to_rfc3339())
+        .bind(&page_id)
+        .execute(&**pool)
+        .await?;
+
+    get_page(pool, &page_id, &user_id).await
+}
+
+// Cada página toma la posición que ocupa en la lista
+#[tauri::command]
+pub async fn reorder_board_pages(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    board_id: String,
+    page_ids: Vec<String>,
+) -> DbResult<Vec<BoardPage>> {
+    let pool = &state.pool;
+    ensure_board_owner(pool, &board_id, &user_id).await?;
+
+    let mut tx = pool.begin().await?;
+
+    for (orden, page_id) in page_ids.iter().enumerate() {
+        let result = sqlx::query("UPDATE board_pages SET orden = ? WHERE id = ? AND board_id = ?")
+            .bind(orden as i64)
+            .bind(page_id)
+            .bind(&board_id)
+            .execute(&mut *tx)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(DbError::NotFound);
+        }
+    }
+
+    tx.commit().await?;
+
+    load_board_pages(pool, &board_id).await
+}
+
+// Borra la página; sus paneles pasan a `destino_id` o se borran con ella
+#[tauri::command]
+pub async fn delete_board_page(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    page_id: String,
+    destino_id: Option<String>,
+) -> DbResult<()> {
+    let pool = &state.pool;
+    let page = get_page(pool, &page_id, &user_id).await?;
+
+    let (paginas,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM board_pages WHERE board_id = ?")
+        .bind(&page.board_id)
+        .fetch_one(&**pool)
+        .await?;
+    if paginas <= 1 {
+        return Err(DbError::InvalidData(
+            "Un board necesita al menos una página".to_string(),
+        ));
+    }
+
+    if let Some(destino_id) = &destino_id {
+        let destino = get_page(pool, destino_id, &user_id).await?;
+        if destino.board_id != page.board_id || destino.id == page.id {
+            return Err(DbError::InvalidData(
+                "La página de destino debe ser otra página del mismo board".to_string(),
+            ));
+        }
+    }
+
+    let mut tx = pool.begin().await?;
+
+    if let Some(destino_id) = &destino_id {
+        sqlx::query("UPDATE panels SET page_id = ? WHERE page_id = ?")
+            .bind(destino_id)
+            .bind(&page_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    // Sin destino, los paneles caen por el ON DELETE CASCADE
+    sqlx::query("DELETE FROM board_pages WHERE id = ?")
+        .bind(&page_id)
+        .execute(&mut *tx)
+        .await?;
+    delete_empty_groups(&mut tx, &page.board_id).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_page_viewport(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    page_id: String,
+    viewport: PageViewport,
+) -> DbResult<BoardPage> {
+    let pool = &state.pool;
+    get_page(pool, &page_id, &user_id).await?;
+
+    if viewport.zoom <= 0.0 {
+        return Err(DbError::InvalidData(
+            "El zoom debe ser positivo".to_string(),
+        ));
+    }
+
+    sqlx::query(
+        "UPDATE board_pages SET viewport_x = ?, viewport_y = ?, zoom = ?, updated_at = ? WHERE id = ?",
+    )
+    .bind(viewport.x)
+    .bind(viewport.y)
+    .bind(viewport.zoom)
+    .bind(Utc::now().to_rfc3339())
+    .bind(&page_id)
+    .execute(&**pool)
+    .await?;
+
+    get_page(pool, &page_id, &user_id).await
+}
+
+// Mueve paneles a otra página del mismo board; los grupos viajan completos
+#[tauri::command]
+pub async fn move_panels_to_page(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    panel_ids: Vec<String>,
+    page_id: String,
+) -> DbResult<Vec<Panel>> {
+    let pool = &state.pool;
+    let page = get_page(pool, &page_id, &user_id).await?;
+
+    let mut movidos: Vec<String> = Vec::new();
+    for panel_id in &panel_ids {
+        let fila: Option<(String, Option<String>)> =
+            sqlx::query_as("SELECT board_id, grupo_id FROM panels WHERE id = ?")
+                .bind(panel_id)
+                .fetch_optional(&**pool)
+                .await?;
+        let (board_id, grupo_id) = fila.ok_or(DbError::NotFound)?;
+        if board_id != page.board_id {
+            return Err(DbError::InvalidData(
+                "Los paneles solo pueden moverse entre páginas de su board".to_string(),
+            ));
+        }
+
+        let miembros: Vec<(String,)> = match &grupo_id {
+            Some(grupo_id) => {
+                sqlx::query_as("SELECT id FROM panels WHERE grupo_id = ?")
+                    .bind(grupo_id)
+                    .fetch_all(&**pool)
+                    .await?
+            }
+            None => vec![(panel_id.clone(),)],
+        };
+        for (id,) in miembros {
+            if !movidos.contains(&id) {
+                movidos.push(id);
+            }
+        }
+    }
+
+    let (cuadricula, evitar_solapes) = board_layout_rules(pool, &page.board_id).await?;
+    let now = Utc::now().to_rfc3339();
+    let mut tx = pool.begin().await?;
+
+    for panel_id in &movidos {
+        sqlx::query("UPDATE panels SET page_id = ?, updated_at = ? WHERE id = ?")
+            .bind(&page_id)
+            .bind(&now)
+            .bind(panel_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    if evitar_solapes {
+        for panel_id in &movidos {
+            settle_board_layout(&mut tx, &page.board_id, panel_id, cuadricula).await?;
+        }
+    }
+
+    tx.commit().await?;
+
+    let mut panels = Vec::new();
+    for panel_id in &movidos {
+        panels.push(get_panel_with_data(state.clone(), panel_id, &user_id).await?);
+    }
+    Ok(panels)
+}
+
+pub(crate) async fn load_board_pages(
+    pool: &SqlitePool,
+    board_id: &str,
+) -> DbResult<Vec<BoardPage>> {
+    let pages: Vec<BoardPage> = sqlx::query_as(
+        r#"
+        SELECT id, board_id, nombre, orden, viewport_x, viewport_y, zoom, created_at, updated_at
+        FROM board_pages
+        WHERE board_id = ?
+        ORDER BY orden, created_at
+        "#,
+    )
+    .bind(board_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(pages)
+}
+
+// Añade una página al final del board y devuelve su id
+pub(crate) async fn insert_board_page(
+    conn: &mut SqliteConnection,
+    board_id: &str,
+    nombre: &str,
+) -> DbResult<String> {
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+
+    sqlx::query(
+        r#"
+        INSERT INTO board_pages (id, board_id, nombre, orden, created_at, updated_at)
+        VALUES (?, ?, ?, (
+            SELECT COALESCE(MAX(orden) + 1, 0) FROM board_pages WHERE board_id = ?
+        ), ?, ?)
+        "#,
+    )
+    .bind(&id)
+    .bind(board_id)
+    .bind(nombre)
+    .bind(board_id)
+    .bind(&now)
+    .bind(&now)
+    .execute(conn)
+    .await?;
+
+    Ok(id)
+}
+
+// Página donde crear un panel: la indicada, si es del board, o la primera
+pub(crate) async fn resolve_panel_page(
+    pool: &SqlitePool,
+    board_id: &str,
+    page_id: Option<&str>,
+) -> DbResult<String> {
+    let page: Option<(String,)> = sqlx::query_as(
+        r#"
+        SELECT id FROM board_pages
+        WHERE board_id = ? AND (? IS NULL OR id = ?)
+        ORDER BY orden, created_at
+        LIMIT 1
+        "#,
+    )
+    .bind(board_id)
+    .bind(page_id)
+    .bind(page_id)
+    .fetch_optional(pool)
+    .await?;
+
+    match page {
+        Some((id,)) => Ok(id),
+        None if page_id.is_some() => Err(DbError::InvalidData(
+            "La página no pertenece al board".to_string(),
+        )),
+        None => insert_board_page(&mut *pool.acquire().await?, board_id, "Página 1").await,
+    }
+}
+
+fn validate_page_name(nombre: &str) -> DbResult<String> {
+    let nombre = nombre.trim();
+    if nombre.is_empty() {
+        return Err(DbError::InvalidData(
+            "El nombre de la página no puede estar vacío".to_string(),
+        ));
+    }
+    Ok(nombre.to_string())
+}
+
+async fn get_page(pool: &SqlitePool, page_id: &str, user_id: &str) -> DbResult<BoardPage> {
+    sqlx::query_as(
+        r#"
+        SELECT p.id, p.board_id, p.nombre, p.orden, p.viewport_x, p.viewport_y, p.zoom,
+               p.created_at, p.updated_at
+        FROM board_pages p
+        JOIN boards b ON b.id = p.board_id
+        WHERE p.id = ? AND b.user_id = ?
+        "#,
+    )
+    .bind(page_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(DbError::NotFound)
+}
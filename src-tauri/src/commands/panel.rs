@@ -0,0 +1,878 @@
+use crate::commands::board::get_panel_with_data;
+use crate::commands::layout::{board_layout_rules, settle_board_layout, snap_position, snap_size};
+use crate::commands::list::CONTEOS_LISTA;
+use crate::models::{
+    board::{Panel, PanelGroup, PanelLayer, Position, Size},
+    panel::{NumberFormat, PanelConfig, PanelKind},
+    database::{AppStateHandle, DbError, DbResult},
+};
+use chrono::Utc;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use sqlx::{Sqlite, SqlitePool, Transaction};
+use uuid::Uuid;
+
+// Versión actual de la forma de la config; las anteriores se actualizan al leerlas
+pub(crate) const CONFIG_VERSION: u64 = 2;
+const TIPOS_GRAFICO: [&str; 4] = ["lineas", "barras", "area", "circular"];
+const AGREGACIONES: [&str; 5] = ["sum", "avg", "min", "max", "count"];
+const ESTILOS_NUMERO: [&str; 4] = ["numero", "moneda", "porcentaje", "compacto"];
+// Claves de la config comunes a todos los tipos; cada una se valida donde se interpreta.
+// 'datasetId' lo repite el frontend, pero el vínculo que cuenta es el del panel.
+const SECCIONES_COMPARTIDAS: [&str; 7] = [
+    "version",
+    "datasetId",
+    "filtro",
+    "pivote",
+    "lineaTendencia",
+    "pronostico",
+    "anomalias",
+];
+
+#[tauri::command]
+pub async fn get_panel_groups(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    board_id: String,
+) -> DbResult<Vec<PanelGroup>> {
+    let pool = &state.pool;
+
+    let mut groups: Vec<PanelGroup> = sqlx::query_as(
+        r#"
+        SELECT g.id, g.board_id, g.nombre, g.created_at
+        FROM panel_groups g
+        JOIN boards b ON b.id = g.board_id
+        WHERE g.board_id = ? AND b.user_id = ?
+        ORDER BY g.created_at
+        "#,
+    )
+    .bind(&board_id)
+    .bind(&user_id)
+    .fetch_all(&**pool)
+    .await?;
+
+    for group in &mut groups {
+        group.panel_ids = group_panel_ids(pool, &group.id).await?;
+    }
+
+    Ok(groups)
+}
+
+// Agrupa paneles del mismo board; un panel solo pertenece a un grupo a la vez
+#[tauri::command]
+pub async fn group_panels(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    panel_ids: Vec<String>,
+    nombre: Option<String>,
+) -> DbResult<PanelGroup> {
+    let pool = &state.pool;
+
+    if panel_ids.len() < 2 {
+        return Err(DbError::InvalidData(
+            "Un grupo necesita al menos dos paneles".to_string(),
+        ));
+    }
+
+    let mut primero: Option<OwnedPanel> = None;
+    for panel_id in &panel_ids {
+        let panel = get_owned_panel(pool, panel_id, &user_id).await?;
+        if panel.bloqueado {
+            return Err(DbError::InvalidData(
+                "Los paneles bloqueados no se pueden agrupar".to_string(),
+            ));
+        }
+        if primero
+            .as_ref()
+            .is_some_and(|p| p.board_id != panel.board_id || p.page_id != panel.page_id)
+        {
+            return Err(DbError::InvalidData(
+                "Los paneles de un grupo deben estar en la misma página".to_string(),
+            ));
+        }
+        primero.get_or_insert(panel);
+    }
+    let board_id = primero.map(|p| p.board_id).unwrap_or_default();
+
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("INSERT INTO panel_groups (id, board_id, nombre, created_at) VALUES (?, ?, ?, ?)")
+        .bind(&id)
+        .bind(&board_id)
+        .bind(nombre.unwrap_or_else(|| "Grupo".to_string()))
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+
+    for panel_id in &panel_ids {
+        sqlx::query("UPDATE panels SET grupo_id = ?, updated_at = ? WHERE id = ?")
+            .bind(&id)
+            .bind(&now)
+            .bind(panel_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    delete_empty_groups(&mut tx, &board_id).await?;
+    tx.commit().await?;
+
+    get_group(pool, &id, &user_id).await
+}
+
+#[tauri::command]
+pub async fn ungroup_panels(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    grupo_id: String,
+) -> DbResult<()> {
+    let pool = &state.pool;
+    get_group(pool, &grupo_id, &user_id).await?;
+
+    // Los paneles quedan sueltos por el ON DELETE SET NULL
+    let resultado = sqlx::query(
+        r#"
+        DELETE FROM panel_groups
+        WHERE id = ?
+          AND NOT EXISTS (SELECT 1 FROM panels WHERE grupo_id = ? AND bloqueado = 1)
+        "#,
+    )
+    .bind(&grupo_id)
+    .bind(&grupo_id)
+    .execute(&**pool)
+    .await?;
+
+    if resultado.rows_affected() == 0 {
+        return Err(DbError::InvalidData(
+            "El grupo tiene paneles bloqueados".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn move_panel_group(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    grupo_id: String,
+    dx: i32,
+    dy: i32,
+) -> DbResult<Vec<Panel>> {
+    let pool = &state.pool;
+    let group = get_group(pool, &grupo_id, &user_id).await?;
+    let (cuadricula, evitar_solapes) = board_layout_rules(pool, &group.board_id).await?;
+
+    let mut tx = pool.begin().await?;
+
+    // Con cuadrícula, la esquina del grupo queda sobre una celda y el resto la sigue
+    let (min_x, min_y): (Option<i32>, Option<i32>) =
+        sqlx::query_as("SELECT MIN(posicion_x), MIN(posicion_y) FROM panels WHERE grupo_id = ?")
+            .bind(&grupo_id)
+            .fetch_one(&mut *tx)
+            .await?;
+    let esquina = Position {
+        x: min_x.unwrap_or(0),
+        y: min_y.unwrap_or(0),
+    };
+    let destino = snap_position(
+        Position {
+            x: esquina.x + dx,
+            y: esquina.y + dy,
+        },
+        cuadricula,
+    );
+    shift_group_panels(
+        &mut tx,
+        &grupo_id,
+        "",
+        destino.x - esquina.x,
+        destino.y - esquina.y,
+    )
+    .await?;
+
+    if let Some(panel_id) = group.panel_ids.first().filter(|_| evitar_solapes) {
+        settle_board_layout(&mut tx, &group.board_id, panel_id, cuadricula).await?;
+    }
+    tx.commit().await?;
+
+    let mut panels = Vec::new();
+    for panel_id in &group.panel_ids {
+        panels.push(get_panel_with_data(state.clone(), panel_id, &user_id).await?);
+    }
+    Ok(panels)
+}
+
+// Escala posiciones y tamaños del grupo para que su contorno mida `tamano`
+#[tauri::command]
+pub async fn resize_panel_group(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    grupo_id: String,
+    tamano: Size,
+) -> DbResult<Vec<Panel>> {
+    let pool = &state.pool;
+    let group = get_group(pool, &grupo_id, &user_id).await?;
+
+    if tamano.width <= 0 || tamano.height <= 0 {
+        return Err(DbError::InvalidData(
+            "El tamaño del grupo debe ser positivo".to_string(),
+        ));
+    }
+
+    let (cuadricula, evitar_solapes) = board_layout_rules(pool, &group.board_id).await?;
+    let tamano = snap_size(tamano, cuadricula);
+
+    let mut tx = pool.begin().await?;
+    let rects: Vec<(String, i32, i32, i32, i32, bool)> = sqlx::query_as(
+        "SELECT id, posicion_x, posicion_y, ancho, alto, bloqueado FROM panels WHERE grupo_id = ?",
+    )
+    .bind(&grupo_id)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    if rects.iter().any(|r| r.5) {
+        return Err(DbError::InvalidData(
+            "El grupo tiene paneles bloqueados".to_string(),
+        ));
+    }
+
+    let min_x = rects.iter().map(|r| r.1).min().unwrap_or(0);
+    let min_y = rects.iter().map(|r| r.2).min().unwrap_or(0);
+    let max_x = rects.iter().map(|r| r.1 + r.3).max().unwrap_or(0);
+    let max_y = rects.iter().map(|r| r.2 + r.4).max().unwrap_or(0);
+    let escala_x = tamano.width as f64 / (max_x - min_x).max(1) as f64;
+    let escala_y = tamano.height as f64 / (max_y - min_y).max(1) as f64;
+
+    let now = Utc::now().to_rfc3339();
+    for (id, x, y, ancho, alto, _) in &rects {
+        let posicion = snap_position(
+            Position {
+                x: min_x + ((x - min_x) as f64 * escala_x).round() as i32,
+                y: min_y + ((y - min_y) as f64 * escala_y).round() as i32,
+            },
+            cuadricula,
+        );
+        let tamano = snap_size(
+            Size {
+                width: ((*ancho as f64 * escala_x).round() as i32).max(1),
+                height: ((*alto as f64 * escala_y).round() as i32).max(1),
+            },
+            cuadricula,
+        );
+        sqlx::query(
+            "UPDATE panels SET posicion_x = ?, posicion_y = ?, ancho = ?, alto = ?, updated_at = ? WHERE id = ?",
+        )
+        .bind(posicion.x)
+        .bind(posicion.y)
+        .bind(tamano.width)
+        .bind(tamano.height)
+        .bind(&now)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    if let Some(panel_id) = group.panel_ids.first().filter(|_| evitar_solapes) {
+        settle_board_layout(&mut tx, &group.board_id, panel_id, cuadricula).await?;
+    }
+    tx.commit().await?;
+
+    let mut panels = Vec::new();
+    for panel_id in &group.panel_ids {
+        panels.push(get_panel_with_data(state.clone(), panel_id, &user_id).await?);
+    }
+    Ok(panels)
+}
+
+#[tauri::command]
+pub async fn set_panel_locked(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    panel_id: String,
+    bloqueado: bool,
+) -> DbResult<Panel> {
+    let pool = &state.pool;
+    get_owned_panel(pool, &panel_id, &user_id).await?;
+
+    sqlx::query("UPDATE panels SET bloqueado = ?, updated_at = ? WHERE id = ?")
+        .bind(bloqueado)
+        .bind(Utc::now().to_rfc3339())
+        .bind(&panel_id)
+        .execute(&**pool)
+        .await?;
+
+    get_panel_with_data(state, &panel_id, &user_id).await
+}
+
+#[tauri::command]
+pub async fn set_panel_hidden(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    panel_id: String,
+    oculto: bool,
+) -> DbResult<Panel> {
+    let pool = &state.pool;
+    get_owned_panel(pool, &panel_id, &user_id).await?;
+
+    let resultado =
+        sqlx::query("UPDATE panels SET oculto = ?, updated_at = ? WHERE id = ? AND bloqueado = 0")
+            .bind(oculto)
+            .bind(Utc::now().to_rfc3339())
+            .bind(&panel_id)
+            .execute(&**pool)
+            .await?;
+
+    if resultado.rows_affected() == 0 {
+        return Err(DbError::InvalidData("El panel está bloqueado".to_string()));
+    }
+
+    get_panel_with_data(state, &panel_id, &user_id).await
+}
+
+// Lleva el panel (y su grupo) encima de todos los demás
+#[tauri::command]
+pub async fn bring_panel_to_front(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    panel_id: String,
+) -> DbResult<Vec<PanelLayer>> {
+    let pool = &state.pool;
+    let panel = get_owned_panel(pool, &panel_id, &user_id).await?;
+
+    let mut tx = pool.begin().await?;
+    let (movidos, resto) =
+        split_stack(&mut tx, &panel.board_id, &panel_id, &panel.grupo_id).await?;
+    let capas =
+        apply_stack_order(&mut tx, &panel.board_id, resto.into_iter().chain(movidos)).await?;
+    tx.commit().await?;
+
+    Ok(capas)
+}
+
+// Lleva el panel (y su grupo) debajo de todos los demás
+#[tauri::command]
+pub async fn send_panel_to_back(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    panel_id: String,
+) -> DbResult<Vec<PanelLayer>> {
+    let pool = &state.pool;
+    let panel = get_owned_panel(pool, &panel_id, &user_id).await?;
+
+    let mut tx = pool.begin().await?;
+    let (movidos, resto) =
+        split_stack(&mut tx, &panel.board_id, &panel_id, &panel.grupo_id).await?;
+    let capas =
+        apply_stack_order(&mut tx, &panel.board_id, movidos.into_iter().chain(resto)).await?;
+    tx.commit().await?;
+
+    Ok(capas)
+}
+
+// Reordena todo el board; `panel_ids` va del fondo al frente e incluye todos sus paneles
+#[tauri::command]
+pub async fn reorder_panels(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    board_id: String,
+    panel_ids: Vec<String>,
+) -> DbResult<Vec<PanelLayer>> {
+    let pool = &state.pool;
+    let mut tx = pool.begin().await?;
+
+    let actuales: Vec<(String,)> = sqlx::query_as(
+        r#"
+        SELECT p.id FROM panels p
+        JOIN boards b ON b.id = p.board_id
+        WHERE p.board_id = ? AND b.user_id = ?
+        "#,
+    )
+    .bind(&board_id)
+    .bind(&user_id)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let mut esperados: Vec<&str> = actuales.iter().map(|(id,)| id.as_str()).collect();
+    let mut recibidos: Vec<&str> = panel_ids.iter().map(String::as_str).collect();
+    esperados.sort_unstable();
+    recibidos.sort_unstable();
+    if esperados != recibidos {
+        return Err(DbError::InvalidData(
+            "El nuevo orden debe incluir exactamente los paneles del board".to_string(),
+        ));
+    }
+
+    let capas = apply_stack_order(&mut tx, &board_id, panel_ids).await?;
+    tx.commit().await?;
+
+    Ok(capas)
+}
+
+// Desplaza los demás paneles del grupo; falla si alguno está bloqueado
+pub(crate) async fn shift_group_panels(
+    tx: &mut Transaction<'_, Sqlite>,
+    grupo_id: &str,
+    excluido: &str,
+    dx: i32,
+    dy: i32,
+) -> DbResult<()> {
+    let (bloqueados,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM panels WHERE grupo_id = ? AND id != ? AND bloqueado = 1",
+    )
+    .bind(grupo_id)
+    .bind(excluido)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    if bloqueados > 0 {
+        return Err(DbError::InvalidData(
+            "El grupo tiene paneles bloqueados".to_string(),
+        ));
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE panels
+        SET posicion_x = posicion_x + ?, posicion_y = posicion_y + ?, updated_at = ?
+        WHERE grupo_id = ? AND id != ?
+        "#,
+    )
+    .bind(dx)
+    .bind(dy)
+    .bind(Utc::now().to_rfc3339())
+    .bind(grupo_id)
+    .bind(excluido)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+struct OwnedPanel {
+    board_id: String,
+    page_id: Option<String>,
+    grupo_id: Option<String>,
+    bloqueado: bool,
+}
+
+async fn get_owned_panel(pool: &SqlitePool, panel_id: &str, user_id: &str) -> DbResult<OwnedPanel> {
+    let (board_id, page_id, grupo_id, bloqueado): (String, Option<String>, Option<String>, bool) =
+        sqlx::query_as(
+            r#"
+            SELECT p.board_id, p.page_id, p.grupo_id, p.bloqueado
+            FROM panels p
+            JOIN boards b ON b.id = p.board_id
+            WHERE p.id = ? AND b.user_id = ?
+            "#,
+        )
+        .bind(panel_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(DbError::Unauthorized)?;
+
+    Ok(OwnedPanel {
+        board_id,
+        page_id,
+        grupo_id,
+        bloqueado,
+    })
+}
+
+async fn get_group(pool: &SqlitePool, grupo_id: &str, user_id: &str) -> DbResult<PanelGroup> {
+    let mut group: PanelGroup = sqlx::query_as(
+        r#"
+        SELECT g.id, g.board_id, g.nombre, g.created_at
+        FROM panel_groups g
+        JOIN boards b ON b.id = g.board_id
+        WHERE g.id = ? AND b.user_id = ?
+        "#,
+    )
+    .bind(grupo_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(DbError::NotFound)?;
+
+    group.panel_ids = group_panel_ids(pool, grupo_id).await?;
+    Ok(group)
+}
+
+async fn group_panel_ids(pool: &SqlitePool, grupo_id: &str) -> DbResult<Vec<String>> {
+    let ids: Vec<(String,)> =
+        sqlx::query_as("SELECT id FROM panels WHERE grupo_id = ? ORDER BY z_index")
+            .bind(grupo_id)
+            .fetch_all(pool)
+            .await?;
+
+    Ok(ids.into_iter().map(|(id,)| id).collect())
+}
+
+pub(crate) async fn delete_empty_groups(
+    tx: &mut Transaction<'_, Sqlite>,
+    board_id: &str,
+) -> DbResult<()> {
+    sqlx::query(
+        r#"
+        DELETE FROM panel_groups
+        WHERE board_id = ?
+          AND id NOT IN (SELECT grupo_id FROM panels WHERE grupo_id IS NOT NULL)
+        "#,
+    )
+    .bind(board_id)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+// Separa la pila actual del board en (panel y su grupo, resto), ambos del fondo al frente
+async fn split_stack(
+    tx: &mut Transaction<'_, Sqlite>,
+    board_id: &str,
+    panel_id: &str,
+    grupo_id: &Option<String>,
+) -> DbResult<(Vec<String>, Vec<String>)> {
+    let pila: Vec<(String, Option<String>)> = sqlx::query_as(
+        "SELECT id, grupo_id FROM panels WHERE board_id = ? ORDER BY z_index, created_at",
+    )
+    .bind(board_id)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    let (movidos, resto): (Vec<_>, Vec<_>) = pila
+        .into_iter()
+        .partition(|(id, grupo)| id == panel_id || (grupo_id.is_some() && grupo == grupo_id));
+
+    Ok((
+        movidos.into_iter().map(|(id, _)| id).collect(),
+        resto.into_iter().map(|(id, _)| id).collect(),
+    ))
+}
+
+// Renumera z_index de 0 en adelante según el orden dado, dentro de la transacción
+// en la que se leyó la pila
+async fn apply_stack_order(
+    tx: &mut Transaction<'_, Sqlite>,
+    board_id: &str,
+    orden: impl IntoIterator<Item = String>,
+) -> DbResult<Vec<PanelLayer>> {
+    let mut capas = Vec::new();
+
+    for (z_index, panel_id) in orden.into_iter().enumerate() {
+        sqlx::query("UPDATE panels SET z_index = ? WHERE id = ? AND board_id = ?")
+            .bind(z_index as i32)
+            .bind(&panel_id)
+            .bind(board_id)
+            .execute(&mut **tx)
+            .await?;
+        capas.push(PanelLayer {
+            panel_id,
+            z_index: z_index as i32,
+        });
+    }
+
+    Ok(capas)
+}
+
+// Lleva una config antigua a la forma actual:
+// - grafico: 'chartType' pasa a 'tipoGrafico' con nombres en español, y las
+//   series dadas como claves pasan a objetos, tomando el color de 'colores';
+//   'xAxisKey' pasa a 'columnaX'
+// - estadistica: 'formato' dado como texto pasa a {estilo}
+// - lista: items de texto pasan a {id, titulo, estado}; 'completado' pasa a 'estado'
+// - notas: 'texto' pasa a 'contenido'
+pub(crate) fn upgrade_panel_config(tipo: PanelKind, config: Value) -> Value {
+    let Value::Object(mut config) = config else {
+        return json!({ "version": CONFIG_VERSION });
+    };
+    let version = config.get("version").and_then(|v| v.as_u64()).unwrap_or(1);
+    if version >= CONFIG_VERSION {
+        return Value::Object(config);
+    }
+
+    match tipo {
+        PanelKind::Grafico => {
+            if !config.contains_key("tipoGrafico") {
+                if let Some(tipo_grafico) = config.remove("chartType") {
+                    config.insert("tipoGrafico".to_string(), tipo_grafico);
+                }
+            }
+            if let Some(Value::String(tipo_grafico)) = config.get_mut("tipoGrafico") {
+                let nombre = match tipo_grafico.as_str() {
+                    "line" | "linea" => "lineas",
+                    "bar" | "barra" => "barras",
+                    "pie" | "donut" => "circular",
+                    otro => otro,
+                };
+                *tipo_grafico = nombre.to_string();
+            }
+            if let Some(columna_x) = config.remove("xAxisKey") {
+                config.entry("columnaX").or_insert(columna_x);
+            }
+
+            let colores: Vec<Value> = config
+                .get("colores")
+                .and_then(|c| c.as_array())
+                .cloned()
+                .unwrap_or_default();
+            if let Some(Value::Array(series)) = config.get_mut("series") {
+                for (i, serie) in series.iter_mut().enumerate() {
+                    if let Value::String(clave) = serie {
+                        *serie = json!({ "key": clave, "name": clave });
+                    }
+                    if let (Value::Object(serie), Some(color)) = (serie, colores.get(i)) {
+                        serie
+                            .entry("color")
+                            .or_insert_with(|| color.clone());
+                    }
+                }
+            }
+        }
+        PanelKind::Estadistica => {
+            if let Some(Value::String(estilo)) = config.get("formato") {
+                let formato = json!({ "estilo": estilo });
+                config.insert("formato".to_string(), formato);
+            }
+        }
+        PanelKind::Lista => {
+            if let Some(Value::Array(items)) = config.get_mut("items") {
+                for (i, item) in items.iter_mut().enumerate() {
+                    if let Value::String(titulo) = item {
+                        *item = json!({ "id": i + 1, "titulo": titulo, "estado": "pendiente" });
+                    }
+                    if let Value::Object(item) = item {
+                        if let Some(completado) = item.remove("completado") {
+                            let estado = if completado.as_bool() == Some(true) {
+                                "completado"
+                            } else {
+                                "pendiente"
+                            };
+                            item.entry("estado").or_insert_with(|| json!(estado));
+                        }
+                    }
+                }
+            }
+        }
+        PanelKind::Notas => {
+            if !config.contains_key("contenido") {
+                if let Some(texto) = config.remove("texto") {
+                    config.insert("contenido".to_string(), texto);
+                }
+            }
+        }
+        PanelKind::Tabla | PanelKind::Mapa | PanelKind::Calendario => {}
+        // Sin conocer el tipo no se sabe cómo actualizarla; se devuelve tal cual
+        PanelKind::Desconocido => return Value::Object(config),
+    }
+
+    config.insert("version".to_string(), json!(CONFIG_VERSION));
+    Value::Object(config)
+}
+
+// Interpreta la config según el tipo del panel y rechaza valores que el backend no entiende
+pub(crate) fn parse_panel_config(tipo: PanelKind, config: &Value) -> DbResult<PanelConfig> {
+    let typed = match tipo {
+        PanelKind::Estadistica => PanelConfig::Estadistica(typed_config(config)?),
+        PanelKind::Grafico => PanelConfig::Grafico(typed_config(config)?),
+        PanelKind::Lista => PanelConfig::Lista(typed_config(config)?),
+        PanelKind::Tabla => PanelConfig::Tabla(typed_config(config)?),
+        PanelKind::Mapa => PanelConfig::Mapa(typed_config(config)?),
+        PanelKind::Calendario => PanelConfig::Calendario(typed_config(config)?),
+        PanelKind::Notas => PanelConfig::Notas(typed_config(config)?),
+        PanelKind::Desconocido => {
+            return Err(DbError::InvalidData(
+                "Los paneles de tipo desconocido no se pueden editar".to_string(),
+            ))
+        }
+    };
+
+    match &typed {
+        PanelConfig::Estadistica(estadistica) => {
+            if let Some(agregacion) = &estadistica.agregacion {
+                if !AGREGACIONES.contains(&agregacion.as_str()) {
+                    return Err(DbError::InvalidData(format!(
+                        "Agregación desconocida: {}",
+                        agregacion
+                    )));
+                }
+            }
+            if let Some(conteo) = &estadistica.conteo_lista {
+                if !CONTEOS_LISTA.contains(&conteo.as_str()) {
+                    return Err(DbError::InvalidData(format!(
+                        "Conteo de lista desconocido: {}",
+                        conteo
+                    )));
+                }
+            }
+            validate_number_format(estadistica.formato.as_ref())?;
+        }
+        PanelConfig::Grafico(grafico) => {
+            if let Some(tipo_grafico) = &grafico.tipo_grafico {
+                if !TIPOS_GRAFICO.contains(&tipo_grafico.as_str()) {
+                    return Err(DbError::InvalidData(format!(
+                        "Tipo de gráfico desconocido: {}",
+                        tipo_grafico
+                    )));
+                }
+            }
+            if grafico.series.iter().any(|s| s.key.trim().is_empty()) {
+                return Err(DbError::InvalidData(
+                    "Todas las series necesitan una columna".to_string(),
+                ));
+            }
+            validate_number_format(grafico.formato.as_ref())?;
+        }
+        PanelConfig::Tabla(tabla) => {
+            if !tabla.columnas.is_empty()
+                && tabla.filas.iter().any(|f| f.len() > tabla.columnas.len())
+            {
+                return Err(DbError::InvalidData(
+                    "Hay filas con más celdas que columnas".to_string(),
+                ));
+            }
+            for formato in tabla.formatos.values() {
+                validate_number_format(Some(formato))?;
+            }
+        }
+        PanelConfig::Calendario(calendario) => {
+            let otras = [
+                &calendario.columna_fin,
+                &calendario.columna_titulo,
+                &calendario.columna_color,
+            ];
+            if calendario.columna_inicio.is_none() && otras.iter().any(|c| c.is_some()) {
+                return Err(DbError::InvalidData(
+                    "El calendario necesita la columna de inicio de los eventos".to_string(),
+                ));
+            }
+        }
+        PanelConfig::Mapa(mapa) => {
+            if mapa.radio_cluster.is_some_and(|r| r <= 0.0) {
+                return Err(DbError::InvalidData(
+                    "El radio de agrupación debe ser positivo".to_string(),
+                ));
+            }
+            if let Some(agregacion) = &mapa.agregacion {
+                if !AGREGACIONES.contains(&agregacion.as_str()) {
+                    return Err(DbError::InvalidData(format!(
+                        "Agregación desconocida: {}",
+                        agregacion
+                    )));
+                }
+            }
+        }
+        PanelConfig::Lista(_) | PanelConfig::Notas(_) => {}
+    }
+
+    Ok(typed)
+}
+
+fn typed_config<T: DeserializeOwned>(config: &Value) -> DbResult<T> {
+    let mut config = config.clone();
+    if let Some(config) = config.as_object_mut() {
+        for seccion in SECCIONES_COMPARTIDAS {
+            config.remove(seccion);
+        }
+    }
+    serde_json::from_value(config)
+        .map_err(|e| DbError::InvalidData(format!("Config de panel inválida: {}", e)))
+}
+
+fn validate_number_format(formato: Option<&NumberFormat>) -> DbResult<()> {
+    let Some(formato) = formato else {
+        return Ok(());
+    };
+
+    if let Some(estilo) = &formato.estilo {
+        if !ESTILOS_NUMERO.contains(&estilo.as_str()) {
+            return Err(DbError::InvalidData(format!(
+                "Estilo de número desconocido: {}",
+                estilo
+            )));
+        }
+    }
+    if formato.decimales.is_some_and(|d| d > 10) {
+        return Err(DbError::InvalidData(
+            "Un formato admite como mucho 10 decimales".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Configs iniciales de cada tipo tal como las crea el frontend (usePanelManager.ts)
+    fn frontend_default(tipo: PanelKind) -> Value {
+        match tipo {
+            PanelKind::Estadistica => json!({ "valor": 1234, "subtitulo": "Total de usuarios" }),
+            PanelKind::Grafico => json!({
+                "titulo": "Gráfico de Rendimiento",
+                "xAxisKey": "date",
+                "datos": [
+                    { "date": "2024-07-01", "ValorA": 50, "ValorB": 30 },
+                    { "date": "2024-08-01", "ValorA": 75, "ValorB": 45 }
+                ],
+                "series": [{ "key": "ValorA", "name": "Valor A", "color": "#3b82f6" }],
+                "curveType": "monotoneX",
+                "legendPosition": "top"
+            }),
+            PanelKind::Lista => json!({
+                "items": [
+                    { "id": 1, "titulo": "Implementar autenticación", "estado": "Completado" },
+                    { "id": 2, "titulo": "Diseñar dashboard", "estado": "En progreso" }
+                ]
+            }),
+            PanelKind::Tabla => json!({
+                "columnas": ["Nombre", "Cargo", "Estado"],
+                "filas": [["Juan Pérez", "Desarrollador", "Activo"]]
+            }),
+            PanelKind::Calendario => json!({ "eventos": [] }),
+            PanelKind::Mapa => json!({ "ubicaciones": [] }),
+            PanelKind::Notas => json!({ "contenido": "" }),
+            PanelKind::Desconocido => json!({}),
+        }
+    }
+
+    #[test]
+    fn las_configs_iniciales_del_frontend_son_validas() {
+        for tipo in PanelKind::ALL {
+            let config = upgrade_panel_config(tipo, frontend_default(tipo));
+            assert_eq!(config["version"], json!(CONFIG_VERSION));
+            if let Err(e) = parse_panel_config(tipo, &config) {
+                panic!("{}: {:?}", tipo.as_str(), e);
+            }
+        }
+    }
+
+    #[test]
+    fn grafico_del_frontend_conserva_titulo_datos_y_eje_x() {
+        let config = upgrade_panel_config(PanelKind::Grafico, frontend_default(PanelKind::Grafico));
+        assert!(config.get("xAxisKey").is_none());
+
+        let PanelConfig::Grafico(grafico) =
+            parse_panel_config(PanelKind::Grafico, &config).unwrap()
+        else {
+            panic!("config de gráfico esperada");
+        };
+        assert_eq!(grafico.columna_x.as_deref(), Some("date"));
+        assert_eq!(grafico.titulo.as_deref(), Some("Gráfico de Rendimiento"));
+        assert_eq!(grafico.datos.len(), 2);
+
+        // El dataset que el frontend repite en la config no es un campo desconocido
+        let mut vinculada = config.clone();
+        vinculada["datasetId"] = json!("d1");
+        assert!(parse_panel_config(PanelKind::Grafico, &vinculada).is_ok());
+    }
+
+    #[test]
+    fn las_claves_desconocidas_se_siguen_rechazando() {
+        let config = json!({ "version": CONFIG_VERSION, "contenido": "", "colr": "rojo" });
+        assert!(parse_panel_config(PanelKind::Notas, &config).is_err());
+        assert!(parse_panel_config(PanelKind::Desconocido, &json!({})).is_err());
+    }
+}
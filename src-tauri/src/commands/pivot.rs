@@ -0,0 +1,401 @@
+use crate::commands::stats::{load_dataset_rows, valor_numerico, valor_texto};
+use crate::models::{
+    database::{AppStateHandle, DbError, DbResult},
+    pivot::{PivotConfig, PivotGrid, PivotHeader, PivotRow},
+};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+pub(crate) const AGREGACIONES: [&str; 5] = ["sum", "avg", "count", "min", "max"];
+const PORCENTAJES: [&str; 3] = ["fila", "columna", "total"];
+const VACIO: &str = "(vacío)";
+
+#[tauri::command]
+pub async fn pivot_dataset(
+    state: AppStateHandle<'_>,
+    dataset_id: String,
+    config: PivotConfig,
+) -> DbResult<PivotGrid> {
+    let pool = &state.pool;
+
+    validate_pivot_config(&config)?;
+    let (_, _, filas) = load_dataset_rows(pool, &dataset_id).await?;
+
+    Ok(build_pivot(&filas, &config))
+}
+
+pub(crate) fn validate_pivot_config(config: &PivotConfig) -> DbResult<()> {
+    let agregacion = config.agregacion.as_deref().unwrap_or("sum");
+    if !AGREGACIONES.contains(&agregacion) {
+        return Err(DbError::InvalidData(format!(
+            "Agregación desconocida: {}",
+            agregacion
+        )));
+    }
+    if agregacion != "count" && config.medida.is_none() {
+        return Err(DbError::InvalidData(
+            "La agregación requiere una medida".to_string(),
+        ));
+    }
+    if let Some(porcentaje) = config.porcentaje.as_deref() {
+        if !PORCENTAJES.contains(&porcentaje) {
+            return Err(DbError::InvalidData(format!(
+                "Porcentaje desconocido: {}",
+                porcentaje
+            )));
+        }
+    }
+    Ok(())
+}
+
+// Añade a los datos de un panel tabla la tabla dinámica de `config.pivote` como `data.pivote`
+pub(crate) fn apply_pivot(config: &serde_json::Value, data: &mut serde_json::Value) {
+    let Some(pivote) = config.get("pivote").filter(|p| !p.is_null()) else {
+        return;
+    };
+
+    let config = match serde_json::from_value::<PivotConfig>(pivote.clone()) {
+        Ok(config) => config,
+        Err(e) => {
+            log::warn!("Configuración de tabla dinámica inválida: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = validate_pivot_config(&config) {
+        log::warn!("Configuración de tabla dinámica inválida: {}", e);
+        return;
+    }
+
+    let Some(datos) = data.get("datos").and_then(|d| d.as_array()) else {
+        return;
+    };
+    let grid = build_pivot(datos, &config);
+
+    if let Some(obj) = data.as_object_mut() {
+        obj.insert(
+            "pivote".to_string(),
+            serde_json::to_value(grid).unwrap_or_default(),
+        );
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Accumulator {
+    pub(crate) registros: usize,
+    conteo: usize,
+    suma: f64,
+    min: Option<f64>,
+    max: Option<f64>,
+}
+
+impl Accumulator {
+    pub(crate) fn add(&mut self, valor: Option<f64>) {
+        self.registros += 1;
+        if let Some(v) = valor {
+            self.conteo += 1;
+            self.suma += v;
+            self.min = Some(self.min.map_or(v, |m| m.min(v)));
+            self.max = Some(self.max.map_or(v, |m| m.max(v)));
+        }
+    }
+
+    pub(crate) fn value(&self, agregacion: &str) -> Option<f64> {
+        match agregacion {
+            "count" => Some(self.registros as f64),
+            "sum" if self.conteo > 0 => Some(self.suma),
+            "avg" if self.conteo > 0 => Some(self.suma / self.conteo as f64),
+            "min" => self.min,
+            "max" => self.max,
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn build_pivot(filas: &[serde_json::Value], config: &PivotConfig) -> PivotGrid {
+    let agregacion = config.agregacion.as_deref().unwrap_or("sum");
+    let claves_de = |fila: &serde_json::Value, dimensiones: &[String]| -> Vec<String> {
+        dimensiones
+            .iter()
+            .map(|d| match fila.get(d) {
+                None | Some(serde_json::Value::Null) => VACIO.to_string(),
+                Some(v) => valor_texto(v),
+            })
+            .collect()
+    };
+
+    // Cada registro acumula en todas las combinaciones de prefijos de fila y columna,
+    // lo que da a la vez celdas, subtotales y totales
+    let mut celdas: HashMap<(Vec<String>, Vec<String>), Accumulator> = HashMap::new();
+    let mut claves_filas = HashSet::new();
+    let mut claves_columnas = HashSet::new();
+
+    for fila in filas {
+        let clave_fila = claves_de(fila, &config.filas);
+        let clave_columna = claves_de(fila, &config.columnas);
+        let valor = config
+            .medida
+            .as_ref()
+            .and_then(|m| fila.get(m))
+            .and_then(valor_numerico);
+
+        for i in 0..=clave_fila.len() {
+            for j in 0..=clave_columna.len() {
+                celdas
+                    .entry((clave_fila[..i].to_vec(), clave_columna[..j].to_vec()))
+                    .or_default()
+                    .add(valor);
+            }
+        }
+
+        claves_filas.insert(clave_fila);
+        claves_columnas.insert(clave_columna);
+    }
+
+    let encabezados_filas = ordered_headers(sorted_keys(claves_filas), config.filas.len(), config);
+    let encabezados_columnas =
+        ordered_headers(sorted_keys(claves_columnas), config.columnas.len(), config);
+
+    let valor_de = |fila: &[String], columna: &[String]| {
+        celdas
+            .get(&(fila.to_vec(), columna.to_vec()))
+            .and_then(|a| a.value(agregacion))
+    };
+
+    let filas_grid = encabezados_filas
+        .into_iter()
+        .map(|(clave_fila, tipo)| {
+            let valores = encabezados_columnas
+                .iter()
+                .map(|(clave_columna, _)| {
+                    let valor = valor_de(&clave_fila, clave_columna)?;
+                    let base = match config.porcentaje.as_deref() {
+                        Some("fila") => valor_de(&clave_fila, &[]),
+                        Some("columna") => valor_de(&[], clave_columna),
+                        Some("total") => valor_de(&[], &[]),
+                        _ => return Some(valor),
+                    };
+                    base.filter(|b| *b != 0.0).map(|b| valor / b * 100.0)
+                })
+                .collect();
+            PivotRow {
+                claves: clave_fila,
+                tipo: tipo.to_string(),
+                valores,
+            }
+        })
+        .collect();
+
+    PivotGrid {
+        dimensiones_filas: config.filas.clone(),
+        dimensiones_columnas: config.columnas.clone(),
+        medida: config.medida.clone(),
+        agregacion: agregacion.to_string(),
+        porcentaje: config.porcentaje.clone(),
+        columnas: encabezados_columnas
+            .into_iter()
+            .map(|(claves, tipo)| PivotHeader {
+                claves,
+                tipo: tipo.to_string(),
+            })
+            .collect(),
+        filas: filas_grid,
+    }
+}
+
+// Claves ordenadas dimensión a dimensión; las numéricas por valor y antes que el texto
+fn sorted_keys(claves: HashSet<Vec<String>>) -> Vec<Vec<String>> {
+    let mut claves: Vec<Vec<String>> = claves.into_iter().collect();
+    claves.sort_by(|a, b| {
+        a.iter()
+            .zip(b)
+            .map(|(x, y)| compare_key(x, y))
+            .find(|orden| orden.is_ne())
+            .unwrap_or_else(|| a.len().cmp(&b.len()))
+    });
+    claves
+}
+
+fn compare_key(a: &str, b: &str) -> Ordering {
+    let numero = |clave: &str| clave.trim().parse::<f64>().ok().filter(|n| n.is_finite());
+    match (numero(a), numero(b)) {
+        (Some(x), Some(y)) => x.total_cmp(&y).then_with(|| a.cmp(b)),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => a.cmp(b),
+    }
+}
+
+// Ordena las claves en profundidad, cerrando cada grupo con su subtotal
+// y terminando con el total general si se piden
+fn ordered_headers(
+    claves: Vec<Vec<String>>,
+    profundidad: usize,
+    config: &PivotConfig,
+) -> Vec<(Vec<String>, &'static str)> {
+    // Sin dimensiones hay una sola fila/columna que ya es el total
+    if profundidad == 0 {
+        return vec![(Vec::new(), "valor")];
+    }
+
+    let mut encabezados = Vec::new();
+    let mut anterior: Option<&Vec<String>> = None;
+
+    for clave in &claves {
+        if let (true, Some(previa)) = (config.subtotales, anterior) {
+            let comun = previa.iter().zip(clave).take_while(|(a, b)| a == b).count();
+            for nivel in (comun + 1..profundidad).rev() {
+                encabezados.push((previa[..nivel].to_vec(), "subtotal"));
+            }
+        }
+        encabezados.push((clave.clone(), "valor"));
+        anterior = Some(clave);
+    }
+
+    if let (true, Some(previa)) = (config.subtotales, anterior) {
+        for nivel in (1..profundidad).rev() {
+            encabezados.push((previa[..nivel].to_vec(), "subtotal"));
+        }
+    }
+    if config.totales {
+        encabezados.push((Vec::new(), "total"));
+    }
+
+    encabezados
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn config(valor: serde_json::Value) -> PivotConfig {
+        serde_json::from_value(valor).unwrap()
+    }
+
+    fn encabezados(filas: &[PivotRow]) -> Vec<(String, &str)> {
+        filas
+            .iter()
+            .map(|f| (f.claves.join("/"), f.tipo.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn claves_numericas_por_valor_y_antes_que_el_texto() {
+        let filas = [
+            json!({ "mes": "10", "ventas": 1 }),
+            json!({ "mes": 9, "ventas": 2 }),
+            json!({ "mes": "2", "ventas": 3 }),
+            json!({ "mes": "otro", "ventas": 4 }),
+            json!({ "mes": null, "ventas": 5 }),
+            json!({ "mes": "2.5", "ventas": 6 }),
+        ];
+        let grid = build_pivot(
+            &filas,
+            &config(json!({ "filas": ["mes"], "medida": "ventas" })),
+        );
+
+        let claves: Vec<String> = grid.filas.iter().map(|f| f.claves.join("/")).collect();
+        assert_eq!(claves, ["2", "2.5", "9", "10", VACIO, "otro"]);
+        assert_eq!(grid.filas[3].valores, [Some(1.0)]);
+    }
+
+    #[test]
+    fn subtotales_y_totales_en_filas_y_columnas() {
+        let filas = [
+            json!({ "region": "Norte", "anio": 2024, "tipo": "a", "ventas": 10 }),
+            json!({ "region": "Norte", "anio": 2023, "tipo": "b", "ventas": 5 }),
+            json!({ "region": "Sur", "anio": 2024, "tipo": "a", "ventas": 7 }),
+            json!({ "region": "Norte", "anio": 2024, "tipo": "b", "ventas": 1 }),
+        ];
+        let grid = build_pivot(
+            &filas,
+            &config(json!({
+                "filas": ["region", "anio"],
+                "columnas": ["tipo"],
+                "medida": "ventas",
+                "subtotales": true,
+                "totales": true
+            })),
+        );
+
+        assert_eq!(
+            encabezados(&grid.filas),
+            [
+                ("Norte/2023".to_string(), "valor"),
+                ("Norte/2024".to_string(), "valor"),
+                ("Norte".to_string(), "subtotal"),
+                ("Sur/2024".to_string(), "valor"),
+                ("Sur".to_string(), "subtotal"),
+                (String::new(), "total"),
+            ]
+        );
+        let columnas: Vec<(String, &str)> = grid
+            .columnas
+            .iter()
+            .map(|c| (c.claves.join("/"), c.tipo.as_str()))
+            .collect();
+        assert_eq!(
+            columnas,
+            [
+                ("a".to_string(), "valor"),
+                ("b".to_string(), "valor"),
+                (String::new(), "total"),
+            ]
+        );
+
+        assert_eq!(grid.filas[0].valores, [None, Some(5.0), Some(5.0)]);
+        assert_eq!(grid.filas[1].valores, [Some(10.0), Some(1.0), Some(11.0)]);
+        assert_eq!(grid.filas[2].valores, [Some(10.0), Some(6.0), Some(16.0)]);
+        assert_eq!(grid.filas[5].valores, [Some(17.0), Some(6.0), Some(23.0)]);
+    }
+
+    #[test]
+    fn agregaciones_y_porcentajes() {
+        let filas = [
+            json!({ "grupo": "x", "valor": 2 }),
+            json!({ "grupo": "x", "valor": "n/d" }),
+            json!({ "grupo": "x", "valor": 4 }),
+            json!({ "grupo": "y", "valor": 6 }),
+        ];
+        let valores = |agregacion: &str| -> Vec<Option<f64>> {
+            build_pivot(
+                &filas,
+                &config(json!({ "filas": ["grupo"], "medida": "valor", "agregacion": agregacion })),
+            )
+            .filas
+            .iter()
+            .map(|f| f.valores[0])
+            .collect()
+        };
+
+        // 'count' cuenta registros; el resto ignora los valores no numéricos
+        assert_eq!(valores("count"), [Some(3.0), Some(1.0)]);
+        assert_eq!(valores("avg"), [Some(3.0), Some(6.0)]);
+        assert_eq!(valores("min"), [Some(2.0), Some(6.0)]);
+        assert_eq!(valores("max"), [Some(4.0), Some(6.0)]);
+
+        let grid = build_pivot(
+            &filas,
+            &config(json!({ "filas": ["grupo"], "medida": "valor", "porcentaje": "total" })),
+        );
+        assert_eq!(grid.filas[0].valores, [Some(50.0)]);
+        assert_eq!(grid.filas[1].valores, [Some(50.0)]);
+    }
+
+    #[test]
+    fn validate_pivot_config_rechaza_configuraciones_incompletas() {
+        assert!(validate_pivot_config(&config(json!({ "filas": ["a"] }))).is_err());
+        assert!(
+            validate_pivot_config(&config(json!({ "filas": ["a"], "agregacion": "count" })))
+                .is_ok()
+        );
+        assert!(validate_pivot_config(&config(
+            json!({ "filas": ["a"], "medida": "m", "agregacion": "median" })
+        ))
+        .is_err());
+        assert!(validate_pivot_config(&config(
+            json!({ "filas": ["a"], "medida": "m", "porcentaje": "global" })
+        ))
+        .is_err());
+    }
+}
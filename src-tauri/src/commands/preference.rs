@@ -0,0 +1,166 @@
+use crate::commands::board::ensure_board_owner;
+use crate::commands::layout::{board_layout_rules, save_layout_rules};
+use crate::models::{
+    board::{BoardPreferences, UpdateBoardPreferencesDto},
+    database::{AppStateHandle, DbError, DbResult},
+    layout::BoardLayoutOptions,
+};
+use chrono::Utc;
+use sqlx::SqlitePool;
+
+// Preferencias guardadas o, si el usuario nunca abrió el board, las de por defecto
+#[tauri::command]
+pub async fn get_board_preferences(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    board_id: String,
+) -> DbResult<BoardPreferences> {
+    let pool = &state.pool;
+    ensure_board_owner(pool, &board_id, &user_id).await?;
+
+    load_board_preferences(pool, &board_id, &user_id).await
+}
+
+#[tauri::command]
+pub async fn set_board_preferences(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    board_id: String,
+    dto: UpdateBoardPreferencesDto,
+) -> DbResult<BoardPreferences> {
+    let pool = &state.pool;
+    ensure_board_owner(pool, &board_id, &user_id).await?;
+
+    if dto.escala.is_some_and(|e| e <= 0.0) {
+        return Err(DbError::InvalidData(
+            "La escala debe ser positiva".to_string(),
+        ));
+    }
+    if let Some(page_id) = &dto.page_id {
+        ensure_board_child(pool, "board_pages", page_id, &board_id).await?;
+    }
+    if let Some(panel_id) = &dto.panel_seleccionado_id {
+        ensure_board_child(pool, "panels", panel_id, &board_id).await?;
+    }
+
+    let actual = load_board_preferences(pool, &board_id, &user_id).await?;
+    let panel_seleccionado_id = if dto.limpiar_seleccion {
+        None
+    } else {
+        dto.panel_seleccionado_id.or(actual.panel_seleccionado_id)
+    };
+    let page_id = dto.page_id.or(actual.page_id);
+    let (_, evitar_solapes) = board_layout_rules(pool, &board_id).await?;
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO board_preferences
+        (board_id, user_id, page_id, panel_seleccionado_id, mostrar_minimapa, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?)
+        ON CONFLICT (board_id, user_id) DO UPDATE SET
+            page_id = excluded.page_id,
+            panel_seleccionado_id = excluded.panel_seleccionado_id,
+            mostrar_minimapa = excluded.mostrar_minimapa,
+            updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(&board_id)
+    .bind(&user_id)
+    .bind(&page_id)
+    .bind(panel_seleccionado_id)
+    .bind(dto.mostrar_minimapa.unwrap_or(actual.mostrar_minimapa))
+    .bind(Utc::now().to_rfc3339())
+    .execute(&mut *tx)
+    .await?;
+
+    // El viewport se guarda en la página que queda abierta
+    let cambia_viewport =
+        dto.viewport_x.is_some() || dto.viewport_y.is_some() || dto.escala.is_some();
+    if let Some(page_id) = page_id.as_deref().filter(|_| cambia_viewport) {
+        sqlx::query(
+            r#"
+            UPDATE board_pages SET
+                viewport_x = COALESCE(?, viewport_x),
+                viewport_y = COALESCE(?, viewport_y),
+                zoom = COALESCE(?, zoom),
+                updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(dto.viewport_x)
+        .bind(dto.viewport_y)
+        .bind(dto.escala)
+        .bind(Utc::now().to_rfc3339())
+        .bind(page_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    if let Some(cuadricula) = dto.cuadricula {
+        let opciones = BoardLayoutOptions {
+            cuadricula,
+            evitar_solapes,
+        };
+        save_layout_rules(&mut tx, &board_id, &opciones).await?;
+    }
+
+    tx.commit().await?;
+
+    load_board_preferences(pool, &board_id, &user_id).await
+}
+
+async fn load_board_preferences(
+    pool: &SqlitePool,
+    board_id: &str,
+    user_id: &str,
+) -> DbResult<BoardPreferences> {
+    // Sin preferencias guardadas se abre la primera página con los valores por defecto
+    let preferencias: BoardPreferences = sqlx::query_as(
+        r#"
+        SELECT b.id AS board_id, ? AS user_id, pg.id AS page_id,
+               COALESCE(pg.viewport_x, 0) AS viewport_x,
+               COALESCE(pg.viewport_y, 0) AS viewport_y,
+               COALESCE(pg.zoom, 1) AS escala,
+               pr.panel_seleccionado_id,
+               COALESCE(pr.mostrar_minimapa, 1) AS mostrar_minimapa,
+               b.cuadricula,
+               pr.updated_at
+        FROM boards b
+        LEFT JOIN board_preferences pr ON pr.board_id = b.id AND pr.user_id = ?
+        LEFT JOIN board_pages pg ON pg.id = COALESCE(pr.page_id, (
+            SELECT id FROM board_pages WHERE board_id = b.id ORDER BY orden LIMIT 1
+        ))
+        WHERE b.id = ?
+        "#,
+    )
+    .bind(user_id)
+    .bind(user_id)
+    .bind(board_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(DbError::NotFound)?;
+
+    Ok(preferencias)
+}
+
+// Comprueba que la página o el panel referenciados son de este board
+async fn ensure_board_child(
+    pool: &SqlitePool,
+    tabla: &str,
+    id: &str,
+    board_id: &str,
+) -> DbResult<()> {
+    let existe: Option<(String,)> = sqlx::query_as(&format!(
+        "SELECT id FROM {} WHERE id = ? AND board_id = ?",
+        tabla
+    ))
+    .bind(id)
+    .bind(board_id)
+    .fetch_optional(pool)
+    .await?;
+
+    existe
+        .map(|_| ())
+        .ok_or_else(|| DbError::InvalidData(format!("{} no pertenece al board", id)))
+}
@@ -0,0 +1,989 @@
+use crate::commands::board::get_board_by_id;
+use crate::commands::stats::{compare_values, valor_numerico, valor_texto};
+use crate::models::{
+    board::{Board, Panel},
+    database::{AppStateHandle, DbError, DbResult},
+    panel::PanelKind,
+    report::BoardReport,
+};
+use chrono::Utc;
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref};
+use resvg::{tiny_skia, usvg};
+use std::path::Path;
+use std::sync::OnceLock;
+use usvg::{fontdb, PostProcessingSteps, TreeParsing, TreePostProc};
+
+pub(crate) const FORMATOS: [&str; 3] = ["svg", "png", "pdf"];
+
+const MARGEN: i32 = 24;
+const ENCABEZADO: i32 = 56;
+const ANCHO_MINIMO_PDF: i32 = 960;
+// Página A4 apaisada, en puntos
+const PAGINA_ANCHO: f32 = 842.0;
+const PAGINA_ALTO: f32 = 595.0;
+// Familias preferidas para 'sans-serif', en orden; la primera instalada gana
+const FUENTES_SANS: [&str; 7] = [
+    "Inter",
+    "Segoe UI",
+    "Helvetica",
+    "Arial",
+    "Noto Sans",
+    "Liberation Sans",
+    "DejaVu Sans",
+];
+const PALETA: [&str; 8] = [
+    "#3b82f6", "#10b981", "#f59e0b", "#ef4444", "#8b5cf6", "#06b6d4", "#ec4899", "#84cc16",
+];
+
+#[tauri::command]
+pub async fn export_board_report(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    board_id: String,
+    formato: String,
+    ruta: String,
+) -> DbResult<BoardReport> {
+    if !FORMATOS.contains(&formato.as_str()) {
+        return Err(DbError::InvalidData(format!(
+            "Formato de reporte desconocido: {}",
+            formato
+        )));
+    }
+
+    // El board se renderiza con los mismos datos que vería el usuario
+    let board = get_board_by_id(state, board_id, user_id, None).await?;
+
+    // El renderizado es costoso; se hace fuera del hilo del runtime
+    tokio::task::spawn_blocking(move || write_board_report(&board, &formato, Path::new(&ruta)))
+        .await
+        .map_err(|e| DbError::InvalidData(format!("El renderizado se interrumpió: {}", e)))?
+}
+
+// Renderiza el board en el formato pedido y lo escribe en `ruta`
+pub(crate) fn write_board_report(
+    board: &Board,
+    formato: &str,
+    ruta: &Path,
+) -> DbResult<BoardReport> {
+    let apiladas = stack_pages(board);
+    let board = apiladas.as_ref().unwrap_or(board);
+
+    let (bytes, paginas, ancho, alto) = match formato {
+        "svg" => {
+            let (svg, ancho, alto) = render_board_svg(board);
+            (svg.into_bytes(), 1, ancho, alto)
+        }
+        "png" => {
+            let (svg, ancho, alto) = render_board_svg(board);
+            (render_png(&svg)?, 1, ancho, alto)
+        }
+        "pdf" => {
+            let (pdf, paginas) = render_pdf(board)?;
+            (pdf, paginas, PAGINA_ANCHO as u32, PAGINA_ALTO as u32)
+        }
+        otro => {
+            return Err(DbError::InvalidData(format!(
+                "Formato de reporte desconocido: {}",
+                otro
+            )))
+        }
+    };
+
+    if let Some(directorio) = ruta.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(directorio).map_err(|e| {
+            DbError::InvalidData(format!("No se pudo crear {}: {}", directorio.display(), e))
+        })?;
+    }
+    std::fs::write(ruta, bytes).map_err(|e| {
+        DbError::InvalidData(format!("No se pudo escribir {}: {}", ruta.display(), e))
+    })?;
+
+    Ok(BoardReport {
+        board_id: board.id.clone(),
+        ruta: ruta.to_string_lossy().to_string(),
+        formato: formato.to_string(),
+        paginas,
+        ancho,
+        alto,
+        generado_at: Utc::now().to_rfc3339(),
+    })
+}
+
+// Renderiza el board completo como un único SVG; devuelve (svg, ancho, alto)
+pub(crate) fn render_board_svg(board: &Board) -> (String, u32, u32) {
+    let paneles = ordered_panels(board);
+    let (min_x, min_y, max_x, max_y) = panel_bounds(&paneles);
+
+    let ancho = max_x
+        .saturating_sub(min_x)
+        .saturating_add(2 * MARGEN)
+        .max(480);
+    let alto = max_y
+        .saturating_sub(min_y)
+        .saturating_add(ENCABEZADO + 2 * MARGEN);
+    let svg = board_svg(board, &paneles, (min_x, min_y), ancho, alto, true);
+
+    (svg, ancho as u32, alto as u32)
+}
+
+// Reparte los paneles en páginas A4 sin cortar los que caben en una página
+fn render_pdf(board: &Board) -> DbResult<(Vec<u8>, usize)> {
+    let paneles = ordered_panels(board);
+    let (min_x, min_y, max_x, _) = panel_bounds(&paneles);
+
+    let ancho = max_x
+        .saturating_sub(min_x)
+        .saturating_add(2 * MARGEN)
+        .max(ANCHO_MINIMO_PDF);
+    let alto = (ancho as f32 * PAGINA_ALTO / PAGINA_ANCHO) as i32;
+
+    let mut paginas: Vec<(i32, Vec<&Panel>)> = Vec::new();
+    let mut inicio = min_y;
+    let mut disponible = alto - ENCABEZADO - 2 * MARGEN;
+    let mut actual: Vec<&Panel> = Vec::new();
+
+    let mut por_posicion = paneles.clone();
+    por_posicion.sort_by_key(|p| p.posicion.y);
+    for panel in por_posicion {
+        let fin = panel.posicion.y.saturating_add(panel.tamano.height);
+        if fin > inicio.saturating_add(disponible)
+            && panel.posicion.y > inicio
+            && !actual.is_empty()
+        {
+            paginas.push((inicio, std::mem::take(&mut actual)));
+            inicio = panel.posicion.y;
+            disponible = alto - 2 * MARGEN;
+        }
+        actual.push(panel);
+    }
+    paginas.push((inicio, actual));
+
+    let mut pdf = Pdf::new();
+    let catalogo_id = Ref::new(1);
+    let paginas_id = Ref::new(2);
+    let mut siguiente = Ref::new(3);
+    let mut ids = Vec::new();
+
+    for (i, (inicio, mut contenido)) in paginas.into_iter().enumerate() {
+        // Dentro de la página se respeta el orden de apilamiento
+        contenido.sort_by_key(|p| p.z_index);
+        let svg = board_svg(board, &contenido, (min_x, inicio), ancho, alto, i == 0);
+        let arbol = parse_svg(&svg)?;
+
+        let pagina_id = siguiente;
+        let contenido_id = Ref::new(pagina_id.get() + 1);
+        let svg_id = Ref::new(pagina_id.get() + 2);
+        siguiente =
+            svg2pdf::convert_tree_into(&arbol, svg2pdf::Options::default(), &mut pdf, svg_id);
+
+        let mut pagina = pdf.page(pagina_id);
+        pagina
+            .media_box(Rect::new(0.0, 0.0, PAGINA_ANCHO, PAGINA_ALTO))
+            .parent(paginas_id)
+            .contents(contenido_id);
+        pagina.resources().x_objects().pair(Name(b"Board"), svg_id);
+        pagina.finish();
+
+        let mut stream = Content::new();
+        stream
+            .transform([PAGINA_ANCHO, 0.0, 0.0, PAGINA_ALTO, 0.0, 0.0])
+            .x_object(Name(b"Board"));
+        pdf.stream(contenido_id, &stream.finish());
+
+        ids.push(pagina_id);
+    }
+
+    let total = ids.len();
+    pdf.catalog(catalogo_id).pages(paginas_id);
+    pdf.pages(paginas_id).kids(ids).count(total as i32);
+
+    Ok((pdf.finish(), total))
+}
+
+fn render_png(svg: &str) -> DbResult<Vec<u8>> {
+    let arbol = parse_svg(svg)?;
+    let tamano = arbol.size.to_int_size();
+
+    let mut pixmap = tiny_skia::Pixmap::new(tamano.width(), tamano.height())
+        .ok_or_else(|| DbError::InvalidData("El board es demasiado grande para PNG".to_string()))?;
+    resvg::render(
+        &arbol,
+        tiny_skia::Transform::default(),
+        &mut pixmap.as_mut(),
+    );
+
+    pixmap
+        .encode_png()
+        .map_err(|e| DbError::InvalidData(format!("No se pudo codificar el PNG: {}", e)))
+}
+
+fn parse_svg(svg: &str) -> DbResult<usvg::Tree> {
+    let mut arbol = usvg::Tree::from_str(svg, &usvg::Options::default())
+        .map_err(|e| DbError::InvalidData(format!("SVG de reporte inválido: {}", e)))?;
+    arbol.postprocess(PostProcessingSteps::default(), system_fonts());
+    Ok(arbol)
+}
+
+// Las fuentes del sistema se cargan una sola vez; sin ellas el texto no se dibuja
+fn system_fonts() -> &'static fontdb::Database {
+    static FUENTES: OnceLock<fontdb::Database> = OnceLock::new();
+    FUENTES.get_or_init(|| {
+        let mut db = fontdb::Database::new();
+        db.load_system_fonts();
+
+        let instalada = FUENTES_SANS.iter().find(|familia| {
+            db.faces()
+                .any(|cara| cara.families.iter().any(|(nombre, _)| nombre == *familia))
+        });
+        if let Some(familia) = instalada {
+            db.set_sans_serif_family(*familia);
+        } else if db.is_empty() {
+            log::warn!("No se encontraron fuentes del sistema; los reportes no tendrán texto");
+        }
+        db
+    })
+}
+
+// En boards con varias páginas, coloca cada página debajo de la anterior
+fn stack_pages(board: &Board) -> Option<Board> {
+    if board.pages.len() < 2 {
+        return None;
+    }
+
+    let mut apiladas = board.clone();
+    let mut cursor: i32 = 0;
+    for page in &board.pages {
+        let (min_y, max_y) = apiladas
+            .panels
+            .iter()
+            .filter(|p| p.page_id.as_deref() == Some(page.id.as_str()) && !p.oculto)
+            .fold((i32::MAX, i32::MIN), |(min_y, max_y), p| {
+                (
+                    min_y.min(p.posicion.y),
+                    max_y.max(p.posicion.y.saturating_add(p.tamano.height)),
+                )
+            });
+        if min_y > max_y {
+            continue;
+        }
+
+        let desplazamiento = cursor.saturating_sub(min_y);
+        for panel in apiladas
+            .panels
+            .iter_mut()
+            .filter(|p| p.page_id.as_deref() == Some(page.id.as_str()))
+        {
+            panel.posicion.y = panel.posicion.y.saturating_add(desplazamiento);
+        }
+        cursor = max_y
+            .saturating_add(desplazamiento)
+            .saturating_add(2 * MARGEN);
+    }
+
+    Some(apiladas)
+}
+
+fn ordered_panels(board: &Board) -> Vec<&Panel> {
+    // Los paneles ocultos no aparecen en el reporte
+    let mut paneles: Vec<&Panel> = board.panels.iter().filter(|p| !p.oculto).collect();
+    paneles.sort_by_key(|p| p.z_index);
+    paneles
+}
+
+// Rectángulo (min_x, min_y, max_x, max_y) que contiene a todos los paneles
+fn panel_bounds(paneles: &[&Panel]) -> (i32, i32, i32, i32) {
+    if paneles.is_empty() {
+        return (0, 0, 0, 0);
+    }
+    paneles.iter().fold(
+        (i32::MAX, i32::MAX, i32::MIN, i32::MIN),
+        |(min_x, min_y, max_x, max_y), p| {
+            (
+                min_x.min(p.posicion.x),
+                min_y.min(p.posicion.y),
+                max_x.max(p.posicion.x.saturating_add(p.tamano.width)),
+                max_y.max(p.posicion.y.saturating_add(p.tamano.height)),
+            )
+        },
+    )
+}
+
+// Dibuja los paneles dados desplazando el lienzo para que `origen` quede en el margen
+fn board_svg(
+    board: &Board,
+    paneles: &[&Panel],
+    origen: (i32, i32),
+    ancho: i32,
+    alto: i32,
+    encabezado: bool,
+) -> String {
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{ancho}" height="{alto}" viewBox="0 0 {ancho} {alto}" font-family="sans-serif">"#
+    );
+    svg.push_str(&format!(
+        r##"<rect width="{ancho}" height="{alto}" fill="#f8fafc"/>"##
+    ));
+
+    let mut superior = MARGEN;
+    if encabezado {
+        let acento = if board.color.starts_with('#') {
+            board.color.as_str()
+        } else {
+            PALETA[0]
+        };
+        svg.push_str(&format!(
+            r##"<rect x="{MARGEN}" y="{MARGEN}" width="4" height="32" fill="{}"/><text x="{}" y="{}" font-size="20" font-weight="bold" fill="#0f172a">{}</text><text x="{}" y="{}" font-size="11" fill="#64748b" text-anchor="end">{}</text>"##,
+            escape(acento),
+            MARGEN + 14,
+            MARGEN + 23,
+            escape(&board.title),
+            ancho - MARGEN,
+            MARGEN + 23,
+            Utc::now().format("%Y-%m-%d %H:%M UTC"),
+        ));
+        superior += ENCABEZADO;
+    }
+
+    for (i, panel) in paneles.iter().enumerate() {
+        let x = MARGEN.saturating_add(panel.posicion.x.saturating_sub(origen.0));
+        let y = superior.saturating_add(panel.posicion.y.saturating_sub(origen.1));
+        render_panel(&mut svg, panel, x, y, i);
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+fn render_panel(svg: &mut String, panel: &Panel, x: i32, y: i32, indice: usize) {
+    let ancho = panel.tamano.width.max(1);
+    let alto = panel.tamano.height.max(1);
+
+    svg.push_str(&format!(
+        r##"<g transform="translate({x},{y})"><defs><clipPath id="panel-{indice}"><rect width="{ancho}" height="{alto}" rx="8"/></clipPath></defs><g clip-path="url(#panel-{indice})"><rect width="{ancho}" height="{alto}" rx="8" fill="#ffffff" stroke="#e2e8f0" stroke-width="2"/>"##
+    ));
+    svg.push_str(&format!(
+        r##"<text x="12" y="22" font-size="13" font-weight="bold" fill="#0f172a">{}</text>"##,
+        escape(&truncate(
+            &panel.titulo,
+            ((ancho - 24) as f64 / 7.5) as usize
+        ))
+    ));
+
+    // Área de contenido bajo el título
+    let ancho_cuerpo = (ancho - 24) as f64;
+    let alto_cuerpo = (alto - 48) as f64;
+    svg.push_str(r#"<g transform="translate(12,36)">"#);
+    if ancho_cuerpo > 0.0 && alto_cuerpo > 0.0 {
+        match panel.tipo {
+            PanelKind::Estadistica => render_kpi(svg, panel, ancho_cuerpo, alto_cuerpo),
+            PanelKind::Grafico => render_chart(svg, panel, ancho_cuerpo, alto_cuerpo),
+            PanelKind::Tabla => render_table(svg, panel, ancho_cuerpo, alto_cuerpo),
+            PanelKind::Lista => render_list(svg, panel, ancho_cuerpo, alto_cuerpo),
+            PanelKind::Notas => render_notes(svg, panel, ancho_cuerpo, alto_cuerpo),
+            PanelKind::Mapa | PanelKind::Calendario | PanelKind::Desconocido => render_message(
+                svg,
+                &format!("Vista previa no disponible para paneles {}", panel.tipo.as_str()),
+                ancho_cuerpo,
+                alto_cuerpo,
+            ),
+        }
+    }
+    svg.push_str("</g></g></g>");
+}
+
+// Tarjeta KPI: agrega `config.columna` sobre los datos del dataset o muestra `config.valor`
+fn render_kpi(svg: &mut String, panel: &Panel, ancho: f64, alto: f64) {
+    let valor = kpi_value(panel);
+    let subtitulo = panel.config.get("subtitulo").and_then(|s| s.as_str());
+
+    let caracteres = valor.chars().count().max(1) as f64;
+    let tamano = (alto * 0.4)
+        .min(ancho / (caracteres * 0.62))
+        .clamp(14.0, 64.0);
+    let centro = if subtitulo.is_some() {
+        alto / 2.0
+    } else {
+        alto / 2.0 + tamano * 0.35
+    };
+
+    svg.push_str(&format!(
+        r##"<text x="{:.1}" y="{:.1}" font-size="{:.1}" font-weight="bold" fill="#0f172a" text-anchor="middle">{}</text>"##,
+        ancho / 2.0,
+        centro,
+        tamano,
+        escape(&valor)
+    ));
+    if let Some(subtitulo) = subtitulo {
+        svg.push_str(&format!(
+            r##"<text x="{:.1}" y="{:.1}" font-size="12" fill="#64748b" text-anchor="middle">{}</text>"##,
+            ancho / 2.0,
+            centro + 22.0,
+            escape(&truncate(subtitulo, (ancho / 6.5) as usize))
+        ));
+    }
+}
+
+fn kpi_value(panel: &Panel) -> String {
+    let columna = panel.config.get("columna").and_then(|c| c.as_str());
+    let agregacion = panel.config.get("agregacion").and_then(|a| a.as_str());
+    let datos = panel
+        .data
+        .as_ref()
+        .and_then(|d| d.get("datos"))
+        .and_then(|d| d.as_array());
+
+    if let Some(datos) = datos.filter(|_| columna.is_some() || agregacion == Some("count")) {
+        let valores: Vec<f64> = columna
+            .map(|c| {
+                datos
+                    .iter()
+                    .filter_map(|fila| fila.get(c).and_then(valor_numerico))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let valor = match agregacion.unwrap_or("sum") {
+            "count" => Some(datos.len() as f64),
+            "sum" => Some(valores.iter().sum()),
+            "avg" if !valores.is_empty() => {
+                Some(valores.iter().sum::<f64>() / valores.len() as f64)
+            }
+            "min" => valores.iter().cloned().reduce(f64::min),
+            "max" => valores.iter().cloned().reduce(f64::max),
+            _ => None,
+        };
+        return valor.map(format_number).unwrap_or_else(|| "-".to_string());
+    }
+
+    match panel.config.get("valor") {
+        Some(serde_json::Value::Number(n)) => n.as_f64().map(format_number).unwrap_or_default(),
+        Some(serde_json::Value::String(s)) if !s.is_empty() => s.clone(),
+        _ => "-".to_string(),
+    }
+}
+
+// Gráfico de líneas o barras (`config.tipoGrafico`) con las series de `config.series`
+fn render_chart(svg: &mut String, panel: &Panel, ancho: f64, alto: f64) {
+    let config = &panel.config;
+
+    // Los datos del dataset vienen del más reciente al más antiguo
+    let mut filas: Vec<&serde_json::Value> = match panel
+        .data
+        .as_ref()
+        .and_then(|d| d.get("datos"))
+        .and_then(|d| d.as_array())
+    {
+        Some(datos) => datos.iter().rev().collect(),
+        None => config
+            .get("datos")
+            .and_then(|d| d.as_array())
+            .map(|d| d.iter().collect())
+            .unwrap_or_default(),
+    };
+
+    let columna_x = config
+        .get("columnaX")
+        .and_then(|c| c.as_str())
+        .map(String::from)
+        .or_else(|| {
+            filas
+                .first()
+                .filter(|f| f.get("date").is_some())
+                .map(|_| "date".to_string())
+        });
+    if let Some(columna) = &columna_x {
+        filas.sort_by(|a, b| compare_values(a.get(columna), b.get(columna)));
+    }
+
+    let series = chart_series(config, filas.first().copied(), columna_x.as_deref());
+    if filas.is_empty() || series.is_empty() {
+        render_message(svg, "Sin datos", ancho, alto);
+        return;
+    }
+
+    let barras = matches!(
+        config.get("tipoGrafico").and_then(|t| t.as_str()),
+        Some("barras" | "barra" | "bar")
+    );
+
+    // Leyenda
+    let mut x_leyenda = 0.0;
+    for (_, nombre, color) in &series {
+        let texto = truncate(nombre, 18);
+        svg.push_str(&format!(
+            r##"<rect x="{:.1}" y="0" width="10" height="10" rx="2" fill="{}"/><text x="{:.1}" y="9" font-size="10" fill="#475569">{}</text>"##,
+            x_leyenda,
+            escape(color),
+            x_leyenda + 14.0,
+            escape(&texto)
+        ));
+        x_leyenda += 24.0 + texto.chars().count() as f64 * 6.0;
+    }
+
+    let valores: Vec<f64> = filas
+        .iter()
+        .flat_map(|f| {
+            series
+                .iter()
+                .filter_map(|(clave, _, _)| f.get(clave).and_then(valor_numerico))
+        })
+        .collect();
+    let mut minimo = valores.iter().cloned().fold(f64::INFINITY, f64::min);
+    let mut maximo = valores.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    if !minimo.is_finite() {
+        render_message(svg, "Sin datos numéricos", ancho, alto);
+        return;
+    }
+    if barras {
+        minimo = minimo.min(0.0);
+        maximo = maximo.max(0.0);
+    }
+    if (maximo - minimo).abs() < f64::EPSILON {
+        minimo -= 1.0;
+        maximo += 1.0;
+    }
+
+    let (izquierda, superior, derecha, inferior) = (48.0, 20.0, ancho - 4.0, alto - 16.0);
+    if derecha <= izquierda || inferior <= superior {
+        return;
+    }
+    let escala_y = |v: f64| inferior - (v - minimo) / (maximo - minimo) * (inferior - superior);
+
+    // Rejilla y etiquetas del eje y
+    for i in 0..=4 {
+        let v = minimo + (maximo - minimo) * i as f64 / 4.0;
+        let y = escala_y(v);
+        svg.push_str(&format!(
+            r##"<line x1="{izquierda}" y1="{y:.1}" x2="{derecha:.1}" y2="{y:.1}" stroke="#e2e8f0" stroke-width="1"/><text x="{:.1}" y="{:.1}" font-size="9" fill="#64748b" text-anchor="end">{}</text>"##,
+            izquierda - 4.0,
+            y + 3.0,
+            escape(&format_compact(v))
+        ));
+    }
+
+    let n = filas.len();
+    let paso = (derecha - izquierda) / n as f64;
+    let centro_x = |i: usize| izquierda + paso * (i as f64 + 0.5);
+
+    // Etiquetas del eje x: primera, central y última
+    if let Some(columna) = &columna_x {
+        let mut indices = vec![0, n / 2, n - 1];
+        indices.dedup();
+        for i in indices {
+            let etiqueta = filas[i].get(columna).map(valor_texto).unwrap_or_default();
+            svg.push_str(&format!(
+                r##"<text x="{:.1}" y="{:.1}" font-size="9" fill="#64748b" text-anchor="middle">{}</text>"##,
+                centro_x(i),
+                alto - 2.0,
+                escape(&truncate(&etiqueta, 12))
+            ));
+        }
+    }
+
+    if barras {
+        let ancho_grupo = paso * 0.8;
+        let ancho_barra = ancho_grupo / series.len() as f64;
+        let base = escala_y(0.0);
+        for (i, fila) in filas.iter().enumerate() {
+            for (j, (clave, _, color)) in series.iter().enumerate() {
+                let Some(v) = fila.get(clave).and_then(valor_numerico) else {
+                    continue;
+                };
+                let y = escala_y(v);
+                svg.push_str(&format!(
+                    r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{}"/>"#,
+                    centro_x(i) - ancho_grupo / 2.0 + ancho_barra * j as f64,
+                    y.min(base),
+                    (ancho_barra - 1.0).max(0.5),
+                    (base - y).abs(),
+                    escape(color)
+                ));
+            }
+        }
+    } else {
+        for (clave, _, color) in &series {
+            let puntos: Vec<String> = filas
+                .iter()
+                .enumerate()
+                .filter_map(|(i, fila)| {
+                    let v = fila.get(clave).and_then(valor_numerico)?;
+                    Some(format!("{:.1},{:.1}", centro_x(i), escala_y(v)))
+                })
+                .collect();
+            svg.push_str(&format!(
+                r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="2" stroke-linejoin="round"/>"#,
+                puntos.join(" "),
+                escape(color)
+            ));
+        }
+    }
+}
+
+// Series (clave, nombre, color) del gráfico; sin `config.series` se usan las columnas numéricas
+fn chart_series(
+    config: &serde_json::Value,
+    primera: Option<&serde_json::Value>,
+    columna_x: Option<&str>,
+) -> Vec<(String, String, String)> {
+    let configuradas: Vec<(String, Option<String>, Option<String>)> = config
+        .get("series")
+        .and_then(|s| s.as_array())
+        .map(|series| {
+            series
+                .iter()
+                .filter_map(|s| match s.as_str() {
+                    Some(clave) => Some((clave.to_string(), None, None)),
+                    None => Some((
+                        s.get("key")?.as_str()?.to_string(),
+                        s.get("name").and_then(|n| n.as_str()).map(String::from),
+                        s.get("color").and_then(|c| c.as_str()).map(String::from),
+                    )),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let configuradas = if configuradas.is_empty() {
+        primera
+            .and_then(|f| f.as_object())
+            .map(|fila| {
+                fila.iter()
+                    .filter(|(clave, valor)| {
+                        Some(clave.as_str()) != columna_x && valor_numerico(valor).is_some()
+                    })
+                    .map(|(clave, _)| (clave.clone(), None, None))
+                    .collect()
+            })
+            .unwrap_or_default()
+    } else {
+        configuradas
+    };
+
+    configuradas
+        .into_iter()
+        .enumerate()
+        .map(|(i, (clave, nombre, color))| {
+            let nombre = nombre.unwrap_or_else(|| clave.clone());
+            let color = color.unwrap_or_else(|| PALETA[i % PALETA.len()].to_string());
+            (clave, nombre, color)
+        })
+        .collect()
+}
+
+// Tabla con formato: tabla dinámica si existe, si no las filas del dataset o de la config
+fn render_table(svg: &mut String, panel: &Panel, ancho: f64, alto: f64) {
+    let (encabezados, filas) = table_contents(panel);
+    if encabezados.is_empty() {
+        render_message(svg, "Sin datos", ancho, alto);
+        return;
+    }
+
+    const ALTO_FILA: f64 = 22.0;
+    let max_columnas = ((ancho / 60.0) as usize).max(1);
+    let columnas = encabezados.len().min(max_columnas);
+    let ancho_columna = ancho / columnas as f64;
+    let caracteres = ((ancho_columna - 8.0) / 6.0).max(1.0) as usize;
+
+    svg.push_str(&format!(
+        r##"<rect width="{ancho:.1}" height="{ALTO_FILA}" fill="#f1f5f9"/>"##
+    ));
+    for (j, encabezado) in encabezados.iter().take(columnas).enumerate() {
+        svg.push_str(&format!(
+            r##"<text x="{:.1}" y="15" font-size="11" font-weight="bold" fill="#334155">{}</text>"##,
+            ancho_columna * j as f64 + 4.0,
+            escape(&truncate(encabezado, caracteres))
+        ));
+    }
+
+    let max_filas = ((alto - ALTO_FILA) / ALTO_FILA).max(0.0) as usize;
+    let (visibles, restantes) = if filas.len() > max_filas {
+        let visibles = max_filas.saturating_sub(1);
+        (visibles, filas.len() - visibles)
+    } else {
+        (filas.len(), 0)
+    };
+
+    for (i, fila) in filas.iter().take(visibles).enumerate() {
+        let y = ALTO_FILA * (i + 1) as f64;
+        if i % 2 == 1 {
+            svg.push_str(&format!(
+                r##"<rect y="{y:.1}" width="{ancho:.1}" height="{ALTO_FILA}" fill="#f8fafc"/>"##
+            ));
+        }
+        for (j, celda) in fila.iter().take(columnas).enumerate() {
+            let texto = escape(&truncate(celda, caracteres));
+            if is_numeric_cell(celda) {
+                svg.push_str(&format!(
+                    r##"<text x="{:.1}" y="{:.1}" font-size="11" fill="#0f172a" text-anchor="end">{}</text>"##,
+                    ancho_columna * (j + 1) as f64 - 4.0,
+                    y + 15.0,
+                    texto
+                ));
+            } else {
+                svg.push_str(&format!(
+                    r##"<text x="{:.1}" y="{:.1}" font-size="11" fill="#0f172a">{}</text>"##,
+                    ancho_columna * j as f64 + 4.0,
+                    y + 15.0,
+                    texto
+                ));
+            }
+        }
+    }
+
+    if restantes > 0 {
+        svg.push_str(&format!(
+            r##"<text x="4" y="{:.1}" font-size="11" fill="#64748b">… {} filas más</text>"##,
+            ALTO_FILA * (visibles + 1) as f64 + 15.0,
+            restantes
+        ));
+    }
+}
+
+fn table_contents(panel: &Panel) -> (Vec<String>, Vec<Vec<String>>) {
+    let data = panel.data.as_ref();
+
+    if let Some(pivote) = data.and_then(|d| d.get("pivote")) {
+        let texto = |v: &serde_json::Value| v.as_str().unwrap_or_default().to_string();
+        let lista = |v: Option<&serde_json::Value>| -> Vec<String> {
+            v.and_then(|l| l.as_array())
+                .map(|l| l.iter().map(texto).collect())
+                .unwrap_or_default()
+        };
+
+        let dimensiones = lista(pivote.get("dimensionesFilas"));
+        let medida = pivote
+            .get("medida")
+            .and_then(|m| m.as_str())
+            .or_else(|| pivote.get("agregacion").and_then(|a| a.as_str()))
+            .unwrap_or("valor");
+
+        let mut encabezados = dimensiones.clone();
+        for columna in pivote
+            .get("columnas")
+            .and_then(|c| c.as_array())
+            .into_iter()
+            .flatten()
+        {
+            let claves = lista(columna.get("claves"));
+            let etiqueta = match columna.get("tipo").and_then(|t| t.as_str()) {
+                Some("total") => "Total".to_string(),
+                Some("subtotal") => format!("{} (subtotal)", claves.join(" / ")),
+                _ if claves.is_empty() => medida.to_string(),
+                _ => claves.join(" / "),
+            };
+            encabezados.push(etiqueta);
+        }
+
+        let filas = pivote
+            .get("filas")
+            .and_then(|f| f.as_array())
+            .into_iter()
+            .flatten()
+            .map(|fila| {
+                let mut celdas = lista(fila.get("claves"));
+                if fila.get("tipo").and_then(|t| t.as_str()) == Some("total") && celdas.is_empty() {
+                    celdas.push("Total".to_string());
+                }
+                celdas.resize(dimensiones.len(), String::new());
+                for valor in fila
+                    .get("valores")
+                    .and_then(|v| v.as_array())
+                    .into_iter()
+                    .flatten()
+                {
+                    celdas.push(valor.as_f64().map(format_number).unwrap_or_default());
+                }
+                celdas
+            })
+            .collect();
+
+        return (encabezados, filas);
+    }
+
+    if let Some(datos) = data.and_then(|d| d.get("datos")).and_then(|d| d.as_array()) {
+        let mut columnas: Vec<String> = data
+            .and_then(|d| d.get("columnas"))
+            .and_then(|c| c.as_array())
+            .map(|c| {
+                c.iter()
+                    .filter_map(|c| c.as_str())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+        if columnas.is_empty() {
+            columnas = datos
+                .first()
+                .and_then(|f| f.as_object())
+                .map(|f| f.keys().cloned().collect())
+                .unwrap_or_default();
+        }
+
+        let filas = datos
+            .iter()
+            .map(|fila| columnas.iter().map(|c| format_cell(fila.get(c))).collect())
+            .collect();
+        return (columnas, filas);
+    }
+
+    // Tabla con datos locales en la config
+    let columnas: Vec<String> = panel
+        .config
+        .get("columnas")
+        .and_then(|c| c.as_array())
+        .map(|c| c.iter().map(valor_texto).collect())
+        .unwrap_or_default();
+    let filas = panel
+        .config
+        .get("filas")
+        .and_then(|f| f.as_array())
+        .map(|filas| {
+            filas
+                .iter()
+                .filter_map(|f| f.as_array())
+                .map(|f| f.iter().map(|c| format_cell(Some(c))).collect())
+                .collect()
+        })
+        .unwrap_or_default();
+    (columnas, filas)
+}
+
+fn render_list(svg: &mut String, panel: &Panel, ancho: f64, alto: f64) {
+    let items: Vec<&serde_json::Value> = panel
+        .config
+        .get("items")
+        .and_then(|i| i.as_array())
+        .map(|i| i.iter().collect())
+        .unwrap_or_default();
+    if items.is_empty() {
+        render_message(svg, "Lista vacía", ancho, alto);
+        return;
+    }
+
+    let caracteres = (ancho / 6.5) as usize;
+    let max_items = (alto / 20.0) as usize;
+    for (i, item) in items.iter().take(max_items).enumerate() {
+        let titulo = item.get("titulo").map(valor_texto).unwrap_or_default();
+        let estado = item
+            .get("estado")
+            .and_then(|e| e.as_str())
+            .unwrap_or_default();
+        svg.push_str(&format!(
+            r##"<text x="0" y="{:.1}" font-size="12" fill="#0f172a">• {}<tspan fill="#94a3b8">  {}</tspan></text>"##,
+            20.0 * i as f64 + 14.0,
+            escape(&truncate(&titulo, caracteres)),
+            escape(estado)
+        ));
+    }
+}
+
+fn render_notes(svg: &mut String, panel: &Panel, ancho: f64, alto: f64) {
+    let contenido = panel
+        .config
+        .get("contenido")
+        .and_then(|c| c.as_str())
+        .unwrap_or_default();
+
+    let caracteres = ((ancho / 6.5) as usize).max(1);
+    let max_lineas = (alto / 16.0) as usize;
+    let lineas = contenido.lines().flat_map(|linea| {
+        let letras: Vec<char> = linea.chars().collect();
+        if letras.is_empty() {
+            vec![String::new()]
+        } else {
+            letras
+                .chunks(caracteres)
+                .map(|c| c.iter().collect())
+                .collect()
+        }
+    });
+
+    for (i, linea) in lineas.take(max_lineas).enumerate() {
+        svg.push_str(&format!(
+            r##"<text x="0" y="{:.1}" font-size="12" fill="#334155">{}</text>"##,
+            16.0 * i as f64 + 12.0,
+            escape(&linea)
+        ));
+    }
+}
+
+fn render_message(svg: &mut String, mensaje: &str, ancho: f64, alto: f64) {
+    svg.push_str(&format!(
+        r##"<text x="{:.1}" y="{:.1}" font-size="12" fill="#94a3b8" text-anchor="middle">{}</text>"##,
+        ancho / 2.0,
+        alto / 2.0,
+        escape(&truncate(mensaje, (ancho / 6.0) as usize))
+    ));
+}
+
+fn format_cell(valor: Option<&serde_json::Value>) -> String {
+    match valor {
+        None | Some(serde_json::Value::Null) => String::new(),
+        Some(serde_json::Value::Number(n)) => n.as_f64().map(format_number).unwrap_or_default(),
+        Some(otro) => valor_texto(otro),
+    }
+}
+
+fn is_numeric_cell(celda: &str) -> bool {
+    let cifras = celda.strip_prefix('-').unwrap_or(celda);
+    cifras.chars().any(|c| c.is_ascii_digit())
+        && cifras
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, ',' | '.' | '%'))
+}
+
+// Número con separador de miles y hasta dos decimales
+fn format_number(valor: f64) -> String {
+    let decimales = if valor.fract().abs() < 1e-9 { 0 } else { 2 };
+    let texto = format!("{:.*}", decimales, valor.abs());
+    let (entero, fraccion) = texto.split_once('.').unwrap_or((&texto, ""));
+
+    let mut agrupado = String::new();
+    for (i, c) in entero.chars().enumerate() {
+        if i > 0 && (entero.len() - i) % 3 == 0 {
+            agrupado.push(',');
+        }
+        agrupado.push(c);
+    }
+
+    let signo = if valor < 0.0 { "-" } else { "" };
+    if fraccion.is_empty() {
+        format!("{}{}", signo, agrupado)
+    } else {
+        format!("{}{}.{}", signo, agrupado, fraccion)
+    }
+}
+
+// Número abreviado para los ejes (1.2K, 3.4M)
+fn format_compact(valor: f64) -> String {
+    let absoluto = valor.abs();
+    if absoluto >= 1e9 {
+        format!("{:.1}B", valor / 1e9)
+    } else if absoluto >= 1e6 {
+        format!("{:.1}M", valor / 1e6)
+    } else if absoluto >= 1e4 {
+        format!("{:.1}K", valor / 1e3)
+    } else if valor.fract().abs() < 1e-9 {
+        format!("{:.0}", valor)
+    } else {
+        format!("{:.2}", valor)
+    }
+}
+
+fn truncate(texto: &str, max: usize) -> String {
+    if texto.chars().count() <= max {
+        return texto.to_string();
+    }
+    let mut recortado: String = texto.chars().take(max.saturating_sub(1)).collect();
+    recortado.push('…');
+    recortado
+}
+
+fn escape(texto: &str) -> String {
+    texto
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
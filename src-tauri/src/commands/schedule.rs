@@ -0,0 +1,606 @@
+use crate::commands::board::{ensure_board_owner, get_board_by_id};
+use crate::commands::report::{write_board_report, FORMATOS};
+use crate::models::{
+    database::{AppState, AppStateHandle, DbError, DbResult},
+    report::BoardReport,
+    schedule::{CreateReportScheduleDto, ReportRun, ReportSchedule},
+};
+use chrono::{
+    DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Timelike, Utc,
+};
+use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+use uuid::Uuid;
+
+// Cada cuánto revisa el runtime si hay reportes pendientes
+const INTERVALO_REVISION: std::time::Duration = std::time::Duration::from_secs(30);
+
+#[tauri::command]
+pub async fn create_report_schedule(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    dto: CreateReportScheduleDto,
+) -> DbResult<ReportSchedule> {
+    let pool = &state.pool;
+
+    ensure_board_owner(pool, &dto.board_id, &user_id).await?;
+
+    if !FORMATOS.contains(&dto.formato.as_str()) {
+        return Err(DbError::InvalidData(format!(
+            "Formato de reporte desconocido: {}",
+            dto.formato
+        )));
+    }
+    if !Path::new(&dto.directorio).is_absolute() {
+        return Err(DbError::InvalidData(
+            "El directorio de salida debe ser una ruta absoluta".to_string(),
+        ));
+    }
+    let programacion = CronSchedule::parse(&dto.programacion)?;
+
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now();
+    let proxima = programacion.next_after(now).map(|f| f.to_rfc3339());
+
+    sqlx::query(
+        r#"
+        INSERT INTO report_schedules
+        (id, user_id, board_id, nombre, formato, programacion, directorio, activo,
+         ultima_ejecucion, proxima_ejecucion, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, 1, NULL, ?, ?, ?)
+        "#,
+    )
+    .bind(&id)
+    .bind(&user_id)
+    .bind(&dto.board_id)
+    .bind(&dto.nombre)
+    .bind(&dto.formato)
+    .bind(dto.programacion.trim())
+    .bind(&dto.directorio)
+    .bind(&proxima)
+    .bind(now.to_rfc3339())
+    .bind(now.to_rfc3339())
+    .execute(&**pool)
+    .await?;
+
+    get_report_schedule(pool, &id, &user_id).await
+}
+
+#[tauri::command]
+pub async fn get_report_schedules(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    board_id: Option<String>,
+) -> DbResult<Vec<ReportSchedule>> {
+    let pool = &state.pool;
+
+    let schedules: Vec<ReportSchedule> = sqlx::query_as(
+        r#"
+        SELECT id, user_id, board_id, nombre, formato, programacion, directorio, activo,
+               ultima_ejecucion, proxima_ejecucion, created_at, updated_at
+        FROM report_schedules
+        WHERE user_id = ? AND (? IS NULL OR board_id = ?)
+        ORDER BY created_at DESC
+        "#,
+    )
+    .bind(&user_id)
+    .bind(&board_id)
+    .bind(&board_id)
+    .fetch_all(&**pool)
+    .await?;
+
+    Ok(schedules)
+}
+
+#[tauri::command]
+pub async fn set_report_schedule_active(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    schedule_id: String,
+    activo: bool,
+) -> DbResult<ReportSchedule> {
+    let pool = &state.pool;
+    let schedule = get_report_schedule(pool, &schedule_id, &user_id).await?;
+
+    // Al reactivar se recalcula la próxima ejecución para no disparar las perdidas
+    let proxima = if activo {
+        CronSchedule::parse(&schedule.programacion)?
+            .next_after(Utc::now())
+            .map(|f| f.to_rfc3339())
+    } else {
+        schedule.proxima_ejecucion
+    };
+
+    sqlx::query(
+        "UPDATE report_schedules SET activo = ?, proxima_ejecucion = ?, updated_at = ? WHERE id = ?",
+    )
+    .bind(activo)
+    .bind(&proxima)
+    .bind(Utc::now().to_rfc3339())
+    .bind(&schedule_id)
+    .execute(&**pool)
+    .await?;
+
+    get_report_schedule(pool, &schedule_id, &user_id).await
+}
+
+#[tauri::command]
+pub async fn delete_report_schedule(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    schedule_id: String,
+) -> DbResult<()> {
+    let pool = &state.pool;
+
+    let result = sqlx::query("DELETE FROM report_schedules WHERE id = ? AND user_id = ?")
+        .bind(&schedule_id)
+        .bind(&user_id)
+        .execute(&**pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(DbError::NotFound);
+    }
+
+    Ok(())
+}
+
+// Genera el reporte en el momento, sin alterar la próxima ejecución programada
+#[tauri::command]
+pub async fn run_report_schedule(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    schedule_id: String,
+) -> DbResult<ReportRun> {
+    let schedule = get_report_schedule(&state.pool, &schedule_id, &user_id).await?;
+    execute_schedule(state, &schedule, true).await
+}
+
+#[tauri::command]
+pub async fn get_report_runs(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    schedule_id: String,
+    limite: Option<i64>,
+) -> DbResult<Vec<ReportRun>> {
+    let pool = &state.pool;
+    get_report_schedule(pool, &schedule_id, &user_id).await?;
+
+    let runs: Vec<ReportRun> = sqlx::query_as(
+        r#"
+        SELECT id, schedule_id, estado, ruta, mensaje, manual, iniciada_at, finalizada_at
+        FROM report_runs
+        WHERE schedule_id = ?
+        ORDER BY iniciada_at DESC
+        LIMIT ?
+        "#,
+    )
+    .bind(&schedule_id)
+    .bind(limite.unwrap_or(50))
+    .fetch_all(&**pool)
+    .await?;
+
+    Ok(runs)
+}
+
+// Bucle del runtime de Tauri que genera los reportes cuya hora ya llegó
+pub async fn run_report_scheduler(app: AppHandle) {
+    loop {
+        tokio::time::sleep(INTERVALO_REVISION).await;
+
+        let state = app.state::<AppState>();
+        if let Err(e) = run_due_schedules(state).await {
+            log::error!("Error al revisar reportes programados: {}", e);
+        }
+    }
+}
+
+async fn run_due_schedules(state: AppStateHandle<'_>) -> DbResult<()> {
+    let pendientes: Vec<ReportSchedule> = sqlx::query_as(
+        r#"
+        SELECT id, user_id, board_id, nombre, formato, programacion, directorio, activo,
+               ultima_ejecucion, proxima_ejecucion, created_at, updated_at
+        FROM report_schedules
+        WHERE activo = 1 AND proxima_ejecucion IS NOT NULL AND proxima_ejecucion <= ?
+        "#,
+    )
+    .bind(Utc::now().to_rfc3339())
+    .fetch_all(&*state.pool)
+    .await?;
+
+    for schedule in pendientes {
+        // Se programa la siguiente antes de generar, para no repetir si el reporte falla
+        let proxima = CronSchedule::parse(&schedule.programacion)
+            .map(|p| p.next_after(Utc::now()).map(|f| f.to_rfc3339()))
+            .unwrap_or_else(|e| {
+                log::warn!("Programación inválida en {}: {}", schedule.id, e);
+                None
+            });
+        sqlx::query("UPDATE report_schedules SET proxima_ejecucion = ? WHERE id = ?")
+            .bind(&proxima)
+            .bind(&schedule.id)
+            .execute(&*state.pool)
+            .await?;
+
+        let run = execute_schedule(state.clone(), &schedule, false).await?;
+        if run.estado == "error" {
+            log::warn!(
+                "Falló el reporte programado {}: {}",
+                schedule.nombre,
+                run.mensaje.unwrap_or_default()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+// Genera el archivo del reporte y registra la ejecución, haya salido bien o no
+async fn execute_schedule(
+    state: AppStateHandle<'_>,
+    schedule: &ReportSchedule,
+    manual: bool,
+) -> DbResult<ReportRun> {
+    let iniciada = Local::now();
+
+    let resultado: DbResult<BoardReport> = async {
+        let board = get_board_by_id(
+            state.clone(),
+            schedule.board_id.clone(),
+            schedule.user_id.clone(),
+            None,
+        )
+        .await?;
+
+        let ruta = PathBuf::from(&schedule.directorio).join(format!(
+            "{}_{}.{}",
+            file_stem(&board.title),
+            iniciada.format("%Y%m%d_%H%M%S"),
+            schedule.formato
+        ));
+        let formato = schedule.formato.clone();
+
+        // El renderizado es costoso; se hace fuera del hilo del runtime
+        tokio::task::spawn_blocking(move || write_board_report(&board, &formato, &ruta))
+            .await
+            .map_err(|e| DbError::InvalidData(format!("El renderizado se interrumpió: {}", e)))?
+    }
+    .await;
+
+    let (estado, ruta, mensaje) = match resultado {
+        Ok(reporte) => ("exito", Some(reporte.ruta), None),
+        Err(e) => ("error", None, Some(e.to_string())),
+    };
+
+    let run = ReportRun {
+        id: Uuid::new_v4().to_string(),
+        schedule_id: schedule.id.clone(),
+        estado: estado.to_string(),
+        ruta,
+        mensaje,
+        manual,
+        iniciada_at: iniciada.with_timezone(&Utc).to_rfc3339(),
+        finalizada_at: Utc::now().to_rfc3339(),
+    };
+
+    sqlx::query(
+        r#"
+        INSERT INTO report_runs (id, schedule_id, estado, ruta, mensaje, manual, iniciada_at, finalizada_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&run.id)
+    .bind(&run.schedule_id)
+    .bind(&run.estado)
+    .bind(&run.ruta)
+    .bind(&run.mensaje)
+    .bind(run.manual)
+    .bind(&run.iniciada_at)
+    .bind(&run.finalizada_at)
+    .execute(&*state.pool)
+    .await?;
+
+    sqlx::query("UPDATE report_schedules SET ultima_ejecucion = ? WHERE id = ?")
+        .bind(&run.iniciada_at)
+        .bind(&schedule.id)
+        .execute(&*state.pool)
+        .await?;
+
+    Ok(run)
+}
+
+async fn get_report_schedule(
+    pool: &SqlitePool,
+    schedule_id: &str,
+    user_id: &str,
+) -> DbResult<ReportSchedule> {
+    let schedule: ReportSchedule = sqlx::query_as(
+        r#"
+        SELECT id, user_id, board_id, nombre, formato, programacion, directorio, activo,
+               ultima_ejecucion, proxima_ejecucion, created_at, updated_at
+        FROM report_schedules
+        WHERE id = ?
+        "#,
+    )
+    .bind(schedule_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(DbError::NotFound)?;
+
+    if schedule.user_id != user_id {
+        return Err(DbError::Unauthorized);
+    }
+
+    Ok(schedule)
+}
+
+// Nombre de archivo seguro a partir del título del board
+fn file_stem(titulo: &str) -> String {
+    let limpio: String = titulo
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let limpio = limpio.trim_matches('_');
+    if limpio.is_empty() {
+        "board".to_string()
+    } else {
+        limpio.to_string()
+    }
+}
+
+// Expresión cron de cinco campos evaluada en la hora local:
+// minuto (0-59) hora (0-23) día-del-mes (1-31) mes (1-12) día-de-la-semana (0-7, 0 y 7 = domingo).
+// Cada campo acepta '*', listas 'a,b', rangos 'a-b' y pasos '*/n' o 'a-b/n'.
+pub(crate) struct CronSchedule {
+    minutos: u64,
+    horas: u64,
+    dias_mes: u64,
+    meses: u64,
+    dias_semana: u64,
+    dia_mes_libre: bool,
+    dia_semana_libre: bool,
+}
+
+impl CronSchedule {
+    pub(crate) fn parse(expresion: &str) -> DbResult<Self> {
+        let expresion = match expresion.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" => "0 0 1 1 *",
+            otra => otra,
+        };
+
+        let campos: Vec<&str> = expresion.split_whitespace().collect();
+        if campos.len() != 5 {
+            return Err(DbError::InvalidData(format!(
+                "La programación debe tener 5 campos (minuto hora día mes día-semana): {}",
+                expresion
+            )));
+        }
+
+        let mut dias_semana = parse_cron_field(campos[4], 0, 7)?;
+        // El 7 también es domingo
+        if dias_semana & (1 << 7) != 0 {
+            dias_semana = (dias_semana | 1) & !(1 << 7);
+        }
+
+        Ok(Self {
+            minutos: parse_cron_field(campos[0], 0, 59)?,
+            horas: parse_cron_field(campos[1], 0, 23)?,
+            dias_mes: parse_cron_field(campos[2], 1, 31)?,
+            meses: parse_cron_field(campos[3], 1, 12)?,
+            dias_semana,
+            dia_mes_libre: campos[2] == "*",
+            dia_semana_libre: campos[4] == "*",
+        })
+    }
+
+    // Primer minuto posterior a `desde` que cumple la expresión
+    pub(crate) fn next_after(&self, desde: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let local = desde.with_timezone(&Local).naive_local();
+        let mut t = local.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        // Cubre programaciones que solo se cumplen en años bisiestos
+        let limite = t + Duration::days(366 * 8);
+
+        while t < limite {
+            if !bit(self.meses, t.month()) {
+                t = first_of_next_month(t.date())?;
+                continue;
+            }
+            if !self.day_matches(t.date()) {
+                t = (t.date() + Duration::days(1)).and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !bit(self.horas, t.hour()) {
+                t = t.date().and_hms_opt(t.hour(), 0, 0)? + Duration::hours(1);
+                continue;
+            }
+            if !bit(self.minutos, t.minute()) {
+                t += Duration::minutes(1);
+                continue;
+            }
+
+            // Las horas que no existen por cambio de horario se saltan
+            if let Some(fecha) = Local.from_local_datetime(&t).earliest() {
+                return Some(fecha.with_timezone(&Utc));
+            }
+            t += Duration::minutes(1);
+        }
+
+        None
+    }
+
+    // Como en cron, si ambos días están restringidos basta con que se cumpla uno
+    fn day_matches(&self, fecha: NaiveDate) -> bool {
+        let dia_mes = bit(self.dias_mes, fecha.day());
+        let dia_semana = bit(self.dias_semana, fecha.weekday().num_days_from_sunday());
+        match (self.dia_mes_libre, self.dia_semana_libre) {
+            (true, true) => true,
+            (true, false) => dia_semana,
+            (false, true) => dia_mes,
+            (false, false) => dia_mes || dia_semana,
+        }
+    }
+}
+
+fn parse_cron_field(campo: &str, minimo: u32, maximo: u32) -> DbResult<u64> {
+    let invalido = || DbError::InvalidData(format!("Campo de programación inválido: {}", campo));
+    let mut bits = 0u64;
+
+    for parte in campo.split(',') {
+        let (rango, paso) = match parte.split_once('/') {
+            Some((rango, paso)) => (rango, Some(paso.parse::<u32>().map_err(|_| invalido())?)),
+            None => (parte, None),
+        };
+        if paso == Some(0) {
+            return Err(invalido());
+        }
+
+        let (inicio, fin) = if rango == "*" {
+            (minimo, maximo)
+        } else if let Some((a, b)) = rango.split_once('-') {
+            (
+                a.parse::<u32>().map_err(|_| invalido())?,
+                b.parse::<u32>().map_err(|_| invalido())?,
+            )
+        } else {
+            let a = rango.parse::<u32>().map_err(|_| invalido())?;
+            // 'a/n' equivale a 'a-max/n'
+            (a, if paso.is_some() { maximo } else { a })
+        };
+
+        if inicio < minimo || fin > maximo || inicio > fin {
+            return Err(invalido());
+        }
+        for valor in (inicio..=fin).step_by(paso.unwrap_or(1) as usize) {
+            bits |= 1 << valor;
+        }
+    }
+
+    Ok(bits)
+}
+
+fn bit(bits: u64, valor: u32) -> bool {
+    bits & (1 << valor) != 0
+}
+
+fn first_of_next_month(fecha: NaiveDate) -> Option<NaiveDateTime> {
+    let (anio, mes) = if fecha.month() == 12 {
+        (fecha.year() + 1, 1)
+    } else {
+        (fecha.year(), fecha.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(anio, mes, 1)?.and_hms_opt(0, 0, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(anio: i32, mes: u32, dia: u32, hora: u32, minuto: u32) -> DateTime<Utc> {
+        Local
+            .with_ymd_and_hms(anio, mes, dia, hora, minuto, 0)
+            .earliest()
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn siguiente(expresion: &str, desde: DateTime<Utc>) -> NaiveDateTime {
+        CronSchedule::parse(expresion)
+            .unwrap()
+            .next_after(desde)
+            .unwrap()
+            .with_timezone(&Local)
+            .naive_local()
+    }
+
+    fn fecha(anio: i32, mes: u32, dia: u32, hora: u32, minuto: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(anio, mes, dia)
+            .unwrap()
+            .and_hms_opt(hora, minuto, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn parse_cron_field_listas_rangos_y_pasos() {
+        let valores = |campo: &str, minimo: u32, maximo: u32| -> Vec<u32> {
+            let bits = parse_cron_field(campo, minimo, maximo).unwrap();
+            (minimo..=maximo).filter(|v| bit(bits, *v)).collect()
+        };
+
+        assert_eq!(valores("*/15", 0, 59), vec![0, 15, 30, 45]);
+        assert_eq!(valores("1-5/2", 0, 59), vec![1, 3, 5]);
+        assert_eq!(valores("5/20", 0, 59), vec![5, 25, 45]);
+        assert_eq!(valores("1,3,10-11", 1, 12), vec![1, 3, 10, 11]);
+
+        for invalido in ["*/0", "60", "5-3", "a", "", "1-"] {
+            assert!(parse_cron_field(invalido, 0, 59).is_err(), "{}", invalido);
+        }
+        assert!(parse_cron_field("0", 1, 31).is_err());
+    }
+
+    #[test]
+    fn parse_valida_campos_y_alias() {
+        assert!(CronSchedule::parse("0 9 * *").is_err());
+        assert!(CronSchedule::parse("0 9 * * * *").is_err());
+        assert!(CronSchedule::parse("@daily").is_ok());
+
+        // El 7 se trata como domingo
+        let cron = CronSchedule::parse("0 0 * * 7").unwrap();
+        assert!(bit(cron.dias_semana, 0));
+        assert!(!bit(cron.dias_semana, 7));
+    }
+
+    #[test]
+    fn next_after_busca_el_siguiente_minuto() {
+        assert_eq!(
+            siguiente("*/15 * * * *", local(2024, 1, 10, 10, 7)),
+            fecha(2024, 1, 10, 10, 15)
+        );
+        // Nunca devuelve el mismo minuto de partida
+        assert_eq!(
+            siguiente("*/15 * * * *", local(2024, 1, 10, 10, 15)),
+            fecha(2024, 1, 10, 10, 30)
+        );
+        // De un viernes por la tarde al lunes por la mañana
+        assert_eq!(
+            siguiente("0 9 * * 1-5", local(2024, 1, 5, 10, 0)),
+            fecha(2024, 1, 8, 9, 0)
+        );
+        assert_eq!(
+            siguiente("@monthly", local(2024, 12, 15, 0, 0)),
+            fecha(2025, 1, 1, 0, 0)
+        );
+    }
+
+    #[test]
+    fn next_after_dia_del_mes_o_de_la_semana() {
+        // Con ambos restringidos basta uno: el viernes 5 llega antes que el día 13
+        assert_eq!(
+            siguiente("0 0 13 * 5", local(2024, 1, 1, 0, 0)),
+            fecha(2024, 1, 5, 0, 0)
+        );
+        assert_eq!(
+            siguiente("0 0 13 * 5", local(2024, 1, 12, 0, 0)),
+            fecha(2024, 1, 13, 0, 0)
+        );
+        // Solo en años bisiestos
+        assert_eq!(
+            siguiente("0 0 29 2 *", local(2024, 3, 1, 0, 0)),
+            fecha(2028, 2, 29, 0, 0)
+        );
+        assert!(CronSchedule::parse("0 0 31 2 *")
+            .unwrap()
+            .next_after(local(2024, 1, 1, 0, 0))
+            .is_none());
+    }
+}
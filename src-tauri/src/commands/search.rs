@@ -0,0 +1,82 @@
+use crate::models::{
+    database::{AppStateHandle, DbError, DbResult},
+    search::SearchHit,
+};
+
+const TIPOS: [&str; 3] = ["board", "panel", "dataset"];
+
+#[tauri::command]
+pub async fn search(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    consulta: String,
+    tipos: Option<Vec<String>>,
+    limite: Option<i64>,
+) -> DbResult<Vec<SearchHit>> {
+    let pool = &state.pool;
+
+    if let Some(tipo) = tipos
+        .iter()
+        .flatten()
+        .find(|t| !TIPOS.contains(&t.as_str()))
+    {
+        return Err(DbError::InvalidData(format!(
+            "Tipo de búsqueda desconocido: {}",
+            tipo
+        )));
+    }
+
+    let Some(expresion) = fts_query(&consulta) else {
+        return Ok(Vec::new());
+    };
+    let tipos = tipos.map(|t| serde_json::json!(t).to_string());
+
+    // bm25 pondera más el título que el contenido; las columnas sin índice no cuentan
+    let hits: Vec<SearchHit> = sqlx::query_as(
+        r#"
+        SELECT tipo,
+               entidad_id AS id,
+               COALESCE(board_id, (
+                   SELECT p.board_id FROM panels p
+                   JOIN boards b ON b.id = p.board_id
+                   WHERE p.dataset_id = search_index.entidad_id AND b.user_id = ?
+                   ORDER BY b.updated_at DESC
+                   LIMIT 1
+               )) AS board_id,
+               titulo,
+               snippet(search_index, 5, '<mark>', '</mark>', '…', 12) AS fragmento,
+               -bm25(search_index, 0.0, 0.0, 0.0, 0.0, 10.0, 1.0) AS puntuacion
+        FROM search_index
+        WHERE search_index MATCH ?
+          AND (user_id IS NULL OR user_id = ?)
+          AND (? IS NULL OR tipo IN (SELECT value FROM json_each(?)))
+        ORDER BY puntuacion DESC
+        LIMIT ?
+        "#,
+    )
+    .bind(&user_id)
+    .bind(&expresion)
+    .bind(&user_id)
+    .bind(&tipos)
+    .bind(&tipos)
+    .bind(limite.unwrap_or(30))
+    .fetch_all(&**pool)
+    .await?;
+
+    Ok(hits)
+}
+
+// Convierte el texto del usuario en una consulta FTS5 segura: cada palabra
+// se busca como prefijo y todas deben aparecer
+pub(crate) fn fts_query(consulta: &str) -> Option<String> {
+    let terminos: Vec<String> = consulta
+        .split_whitespace()
+        .map(|t| format!("\"{}\"*", t.replace('"', "\"\"")))
+        .collect();
+
+    if terminos.is_empty() {
+        None
+    } else {
+        Some(terminos.join(" "))
+    }
+}
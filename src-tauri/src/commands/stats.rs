@@ -1,16 +1,31 @@
+use crate::commands::alert::evaluate_dataset_alerts;
 use crate::models::{
     database::{AppStateHandle, DbError, DbResult},
-    stats::{AddDatasetDataDto, CreateDatasetDto, DatasetData, GlobalDataset, GlobalDatasetDb},
+    stats::{
+        AddDatasetDataDto, Anomaly, AnomalyDto, AnomalyResult, ColumnDescription,
+        CorrelationMatrix, CreateDatasetDto, DatasetData, DatasetDescription, DateSummary,
+        ForecastDto, ForecastPoint, ForecastResult, FrequencyEntry, GlobalDataset, GlobalDatasetDb,
+        HistogramBin, LinearRegressionDto, LinearRegressionResult, NumericSummary,
+        RegressionCoefficient, RegressionPrediction, TextSummary,
+    },
 };
-use chrono::Utc;
+use chrono::{DateTime, Duration, Months, NaiveDate, NaiveDateTime, Utc};
+use sqlx::SqlitePool;
+use statrs::distribution::{ContinuousCDF, Normal, StudentsT};
+use std::collections::{HashMap, HashSet};
+use tauri::AppHandle;
 use uuid::Uuid;
 
+const TIPOS_COLUMNA: [&str; 6] = ["texto", "numero", "fecha", "booleano", "latlon", "geojson"];
+const MAX_INTERVALOS: usize = 1000; // Del histograma de describe_dataset
+const MAX_PERIODOS: usize = 1000; // El intervalo de Holt-Winters es cuadrático en los periodos
+
 #[tauri::command]
 pub async fn get_global_datasets(state: AppStateHandle<'_>) -> DbResult<Vec<GlobalDataset>> {
     let pool = &state.pool;
 
     let datasets_db: Vec<GlobalDatasetDb> = sqlx::query_as(
-        "SELECT id, nombre, tipo, columnas, created_at, updated_at FROM global_datasets ORDER BY created_at DESC"
+        "SELECT id, nombre, tipo, columnas, tipos_columna, created_at, updated_at FROM global_datasets ORDER BY created_at DESC"
     )
         .fetch_all(&**pool)
         .await?;
@@ -19,6 +34,7 @@ pub async fn get_global_datasets(state: AppStateHandle<'_>) -> DbResult<Vec<Glob
 
     for dataset_db in datasets_db {
         let columnas: Vec<String> = serde_json::from_str(&dataset_db.columnas).unwrap_or_default();
+        let tipos_columna = serde_json::from_str(&dataset_db.tipos_columna).unwrap_or_default();
 
         // Obtener datos del dataset
         let datos_raw: Vec<(String,)> = sqlx::query_as(
@@ -40,6 +56,7 @@ pub async fn get_global_datasets(state: AppStateHandle<'_>) -> DbResult<Vec<Glob
             nombre: dataset_db.nombre,
             tipo: dataset_db.tipo,
             columnas,
+            tipos_columna,
             created_at: dataset_db.created_at,
             updated_at: dataset_db.updated_at,
             datos,
@@ -57,7 +74,7 @@ pub async fn get_dataset_by_id(
     let pool = &state.pool;
 
     let dataset_db: GlobalDatasetDb = sqlx::query_as(
-        "SELECT id, nombre, tipo, columnas, created_at, updated_at FROM global_datasets WHERE id = ?"
+        "SELECT id, nombre, tipo, columnas, tipos_columna, created_at, updated_at FROM global_datasets WHERE id = ?"
     )
         .bind(&dataset_id)
         .fetch_optional(&**pool)
@@ -65,6 +82,7 @@ pub async fn get_dataset_by_id(
         .ok_or(DbError::NotFound)?;
 
     let columnas: Vec<String> = serde_json::from_str(&dataset_db.columnas).unwrap_or_default();
+    let tipos_columna = serde_json::from_str(&dataset_db.tipos_columna).unwrap_or_default();
 
     // Obtener datos
     let datos_raw: Vec<(String,)> = sqlx::query_as(
@@ -86,6 +104,7 @@ pub async fn get_dataset_by_id(
         nombre: dataset_db.nombre,
         tipo: dataset_db.tipo,
         columnas,
+        tipos_columna,
         created_at: dataset_db.created_at,
         updated_at: dataset_db.updated_at,
         datos,
@@ -99,21 +118,26 @@ pub async fn create_dataset(
 ) -> DbResult<GlobalDataset> {
     let pool = &state.pool;
 
+    validate_column_types(&dto.tipos_columna)?;
+
     let id = Uuid::new_v4().to_string();
     let now = Utc::now().to_rfc3339();
     let columnas_json =
         serde_json::to_string(&dto.columnas).map_err(|e| DbError::InvalidData(e.to_string()))?;
+    let tipos_json = serde_json::to_string(&dto.tipos_columna)
+        .map_err(|e| DbError::InvalidData(e.to_string()))?;
 
     sqlx::query(
         r#"
-        INSERT INTO global_datasets (id, nombre, tipo, columnas, created_at, updated_at)
-        VALUES (?, ?, ?, ?, ?, ?)
+        INSERT INTO global_datasets (id, nombre, tipo, columnas, tipos_columna, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
         "#,
     )
     .bind(&id)
     .bind(&dto.nombre)
     .bind(&dto.tipo)
     .bind(&columnas_json)
+    .bind(&tipos_json)
     .bind(&now)
     .bind(&now)
     .execute(&**pool)
@@ -124,6 +148,7 @@ pub async fn create_dataset(
         nombre: dto.nombre,
         tipo: dto.tipo,
         columnas: dto.columnas,
+        tipos_columna: dto.tipos_columna,
         created_at: now.clone(),
         updated_at: now,
         datos: vec![],
@@ -133,6 +158,7 @@ pub async fn create_dataset(
 #[tauri::command]
 pub async fn add_dataset_data(
     state: AppStateHandle<'_>,
+    app: AppHandle,
     dto: AddDatasetDataDto,
 ) -> DbResult<DatasetData> {
     let pool = &state.pool;
@@ -169,6 +195,15 @@ pub async fn add_dataset_data(
         .execute(&**pool)
         .await?;
 
+    // Re-evaluar las alertas del dataset con la nueva fila
+    if let Err(e) = evaluate_dataset_alerts(&app, pool, &dto.dataset_id).await {
+        log::error!(
+            "Error al evaluar alertas del dataset {}: {}",
+            dto.dataset_id,
+            e
+        );
+    }
+
     Ok(DatasetData {
         id,
         dataset_id: dto.dataset_id,
@@ -177,6 +212,45 @@ pub async fn add_dataset_data(
     })
 }
 
+// Declara el tipo de las columnas indicadas; un tipo vacío quita la declaración
+#[tauri::command]
+pub async fn set_dataset_column_types(
+    state: AppStateHandle<'_>,
+    dataset_id: String,
+    tipos: HashMap<String, String>,
+) -> DbResult<HashMap<String, String>> {
+    let pool = &state.pool;
+
+    let (tipos_json,): (String,) =
+        sqlx::query_as("SELECT tipos_columna FROM global_datasets WHERE id = ?")
+            .bind(&dataset_id)
+            .fetch_optional(&**pool)
+            .await?
+            .ok_or(DbError::NotFound)?;
+
+    let mut tipos_columna: HashMap<String, String> =
+        serde_json::from_str(&tipos_json).unwrap_or_default();
+    for (columna, tipo) in tipos {
+        if tipo.is_empty() {
+            tipos_columna.remove(&columna);
+        } else {
+            tipos_columna.insert(columna, tipo);
+        }
+    }
+    validate_column_types(&tipos_columna)?;
+
+    let tipos_json =
+        serde_json::to_string(&tipos_columna).map_err(|e| DbError::InvalidData(e.to_string()))?;
+    sqlx::query("UPDATE global_datasets SET tipos_columna = ?, updated_at = ? WHERE id = ?")
+        .bind(&tipos_json)
+        .bind(Utc::now().to_rfc3339())
+        .bind(&dataset_id)
+        .execute(&**pool)
+        .await?;
+
+    Ok(tipos_columna)
+}
+
 #[tauri::command]
 pub async fn delete_dataset(state: AppStateHandle<'_>, dataset_id: String) -> DbResult<()> {
     let pool = &state.pool;
@@ -192,3 +266,1587 @@ pub async fn delete_dataset(state: AppStateHandle<'_>, dataset_id: String) -> Db
 
     Ok(())
 }
+
+#[tauri::command]
+pub async fn describe_dataset(
+    state: AppStateHandle<'_>,
+    dataset_id: String,
+    top_n: Option<usize>,
+    bins: Option<usize>,
+) -> DbResult<DatasetDescription> {
+    let pool = &state.pool;
+
+    if bins.is_some_and(|b| b == 0 || b > MAX_INTERVALOS) {
+        return Err(DbError::InvalidData(format!(
+            "El histograma debe tener entre 1 y {} intervalos",
+            MAX_INTERVALOS
+        )));
+    }
+
+    let (nombre, columnas, filas) = load_dataset_rows(pool, &dataset_id).await?;
+    let top_n = top_n.unwrap_or(10);
+
+    let columnas = columnas
+        .into_iter()
+        .map(|columna| describe_column(&columna, &filas, top_n, bins))
+        .collect();
+
+    Ok(DatasetDescription {
+        dataset_id,
+        nombre,
+        filas: filas.len(),
+        columnas,
+    })
+}
+
+#[tauri::command]
+pub async fn correlation_matrix(
+    state: AppStateHandle<'_>,
+    dataset_id: String,
+    columnas: Vec<String>,
+) -> DbResult<CorrelationMatrix> {
+    let pool = &state.pool;
+
+    if columnas.len() < 2 {
+        return Err(DbError::InvalidData(
+            "Se necesitan al menos dos columnas".to_string(),
+        ));
+    }
+
+    let (_, _, filas) = load_dataset_rows(pool, &dataset_id).await?;
+
+    let k = columnas.len();
+    let mut pearson = vec![vec![None; k]; k];
+    let mut spearman = vec![vec![None; k]; k];
+    let mut observaciones = vec![vec![0; k]; k];
+
+    for i in 0..k {
+        for j in i..k {
+            // Solo pares donde ambas columnas tienen valor numérico
+            let (xs, ys): (Vec<f64>, Vec<f64>) = filas
+                .iter()
+                .filter_map(|fila| {
+                    let x = fila.get(&columnas[i]).and_then(valor_numerico)?;
+                    let y = fila.get(&columnas[j]).and_then(valor_numerico)?;
+                    Some((x, y))
+                })
+                .unzip();
+
+            let r = pearson_correlation(&xs, &ys);
+            let rho = pearson_correlation(&ranks(&xs), &ranks(&ys));
+
+            pearson[i][j] = r;
+            pearson[j][i] = r;
+            spearman[i][j] = rho;
+            spearman[j][i] = rho;
+            observaciones[i][j] = xs.len();
+            observaciones[j][i] = xs.len();
+        }
+    }
+
+    Ok(CorrelationMatrix {
+        columnas,
+        pearson,
+        spearman,
+        observaciones,
+    })
+}
+
+#[tauri::command]
+pub async fn linear_regression(
+    state: AppStateHandle<'_>,
+    dto: LinearRegressionDto,
+) -> DbResult<LinearRegressionResult> {
+    let pool = &state.pool;
+
+    if dto.columnas_x.is_empty() {
+        return Err(DbError::InvalidData(
+            "Se necesita al menos una columna explicativa".to_string(),
+        ));
+    }
+    let nivel = dto.nivel_confianza.unwrap_or(0.95);
+    if nivel <= 0.0 || nivel >= 1.0 {
+        return Err(DbError::InvalidData(
+            "El nivel de confianza debe estar entre 0 y 1".to_string(),
+        ));
+    }
+
+    let (_, _, filas) = load_dataset_rows(pool, &dto.dataset_id).await?;
+
+    // Filas con todos los valores numéricos presentes
+    let (xs, ys): (Vec<Vec<f64>>, Vec<f64>) = filas
+        .iter()
+        .filter_map(|fila| {
+            let y = fila.get(&dto.columna_y).and_then(valor_numerico)?;
+            let x = dto
+                .columnas_x
+                .iter()
+                .map(|c| fila.get(c).and_then(valor_numerico))
+                .collect::<Option<Vec<f64>>>()?;
+            Some((x, y))
+        })
+        .unzip();
+
+    let ajuste = least_squares(&xs, &ys).ok_or_else(|| {
+        DbError::InvalidData(
+            "No hay suficientes observaciones independientes para la regresión".to_string(),
+        )
+    })?;
+
+    let coeficientes = dto
+        .columnas_x
+        .iter()
+        .enumerate()
+        .map(|(i, columna)| {
+            let valor = ajuste.coeficientes[i + 1];
+            let error_estandar = ajuste.error_estandar * ajuste.xtx_inv[i + 1][i + 1].sqrt();
+            RegressionCoefficient {
+                columna: columna.clone(),
+                valor,
+                error_estandar,
+                t: (error_estandar > 0.0).then(|| valor / error_estandar),
+            }
+        })
+        .collect();
+
+    let mut predicciones = Vec::new();
+    for x in dto.predecir.unwrap_or_default() {
+        if x.len() != dto.columnas_x.len() {
+            return Err(DbError::InvalidData(format!(
+                "Cada punto a predecir necesita {} valores",
+                dto.columnas_x.len()
+            )));
+        }
+        let (valor, margen) = ajuste.predict(&x, nivel);
+        predicciones.push(RegressionPrediction {
+            x,
+            valor,
+            inferior: valor - margen,
+            superior: valor + margen,
+        });
+    }
+
+    Ok(LinearRegressionResult {
+        columna_y: dto.columna_y,
+        columnas_x: dto.columnas_x,
+        n: ys.len(),
+        intercepto: ajuste.coeficientes[0],
+        coeficientes,
+        r2: ajuste.r2,
+        r2_ajustado: ajuste.r2_ajustado,
+        error_estandar_residual: ajuste.error_estandar,
+        ajustados: ajuste.ajustados,
+        residuos: ajuste.residuos,
+        predicciones,
+    })
+}
+
+#[tauri::command]
+pub async fn forecast_series(
+    state: AppStateHandle<'_>,
+    dto: ForecastDto,
+) -> DbResult<ForecastResult> {
+    let pool = &state.pool;
+
+    let (_, _, filas) = load_dataset_rows(pool, &dto.dataset_id).await?;
+    let serie = time_series(&filas, &dto.columna_fecha, &dto.columna_valor);
+
+    forecast(
+        &serie,
+        dto.periodos,
+        dto.metodo.as_deref().unwrap_or("holt_winters"),
+        dto.estacionalidad,
+        dto.nivel_confianza.unwrap_or(0.95),
+    )
+}
+
+#[tauri::command]
+pub async fn detect_anomalies(
+    state: AppStateHandle<'_>,
+    dto: AnomalyDto,
+) -> DbResult<AnomalyResult> {
+    let pool = &state.pool;
+
+    let _: (String,) = sqlx::query_as("SELECT id FROM global_datasets WHERE id = ?")
+        .bind(&dto.dataset_id)
+        .fetch_optional(&**pool)
+        .await?
+        .ok_or(DbError::NotFound)?;
+
+    let datos_raw: Vec<(String, String)> = sqlx::query_as(
+        "SELECT id, data FROM dataset_data WHERE dataset_id = ? ORDER BY created_at ASC",
+    )
+    .bind(&dto.dataset_id)
+    .fetch_all(&**pool)
+    .await?;
+
+    let mut filas: Vec<(String, serde_json::Value)> = datos_raw
+        .into_iter()
+        .filter_map(|(id, data_str)| Some((id, serde_json::from_str(&data_str).ok()?)))
+        .collect();
+    if let Some(columna_orden) = &dto.columna_orden {
+        filas.sort_by(|a, b| compare_values(a.1.get(columna_orden), b.1.get(columna_orden)));
+    }
+
+    let valores: Vec<Option<f64>> = filas
+        .iter()
+        .map(|(_, fila)| fila.get(&dto.columna).and_then(valor_numerico))
+        .collect();
+    let umbral = dto
+        .umbral
+        .unwrap_or_else(|| default_anomaly_threshold(&dto.metodo));
+
+    let detectadas = find_anomalies(&valores, &dto.metodo, umbral, dto.ventana.unwrap_or(7))?;
+
+    let anomalias = detectadas
+        .into_iter()
+        .map(|(i, esperado, puntuacion)| Anomaly {
+            id: filas[i].0.clone(),
+            fila: filas[i].1.clone(),
+            valor: valores[i].unwrap_or_default(),
+            esperado,
+            puntuacion,
+        })
+        .collect();
+
+    Ok(AnomalyResult {
+        columna: dto.columna,
+        metodo: dto.metodo,
+        umbral,
+        evaluadas: valores.iter().flatten().count(),
+        anomalias,
+    })
+}
+
+fn validate_column_types(tipos: &HashMap<String, String>) -> DbResult<()> {
+    if let Some(tipo) = tipos
+        .values()
+        .find(|t| !TIPOS_COLUMNA.contains(&t.as_str()))
+    {
+        return Err(DbError::InvalidData(format!(
+            "Tipo de columna desconocido: {}",
+            tipo
+        )));
+    }
+    Ok(())
+}
+
+// Tipos de columna declarados en un dataset
+pub(crate) async fn load_column_types(
+    pool: &SqlitePool,
+    dataset_id: &str,
+) -> DbResult<HashMap<String, String>> {
+    let (tipos_json,): (String,) =
+        sqlx::query_as("SELECT tipos_columna FROM global_datasets WHERE id = ?")
+            .bind(dataset_id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or(DbError::NotFound)?;
+
+    Ok(serde_json::from_str(&tipos_json).unwrap_or_default())
+}
+
+// Helper para obtener nombre, columnas y filas de un dataset.
+// Las columnas incluyen las declaradas y cualquier clave extra presente en los datos.
+pub(crate) async fn load_dataset_rows(
+    pool: &SqlitePool,
+    dataset_id: &str,
+) -> DbResult<(String, Vec<String>, Vec<serde_json::Value>)> {
+    let dataset: (String, String) =
+        sqlx::query_as("SELECT nombre, columnas FROM global_datasets WHERE id = ?")
+            .bind(dataset_id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or(DbError::NotFound)?;
+
+    let mut columnas: Vec<String> = serde_json::from_str(&dataset.1).unwrap_or_default();
+
+    let datos_raw: Vec<(String,)> = sqlx::query_as(
+        "SELECT data FROM dataset_data WHERE dataset_id = ? ORDER BY created_at ASC",
+    )
+    .bind(dataset_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut filas = Vec::new();
+    for (data_str,) in datos_raw {
+        if let Ok(data) = serde_json::from_str::<serde_json::Value>(&data_str) {
+            if let Some(obj) = data.as_object() {
+                for key in obj.keys() {
+                    if !columnas.contains(key) {
+                        columnas.push(key.clone());
+                    }
+                }
+            }
+            filas.push(data);
+        }
+    }
+
+    Ok((dataset.0, columnas, filas))
+}
+
+fn describe_column(
+    columna: &str,
+    filas: &[serde_json::Value],
+    top_n: usize,
+    bins: Option<usize>,
+) -> ColumnDescription {
+    let valores: Vec<&serde_json::Value> = filas
+        .iter()
+        .filter_map(|fila| fila.get(columna))
+        .filter(|v| !es_nulo(v))
+        .collect();
+
+    let nulos = filas.len() - valores.len();
+    let distintos = valores
+        .iter()
+        .map(|v| valor_texto(v))
+        .collect::<HashSet<_>>()
+        .len();
+
+    let mut descripcion = ColumnDescription {
+        columna: columna.to_string(),
+        tipo: "vacio".to_string(),
+        count: valores.len(),
+        nulos,
+        distintos,
+        numerico: None,
+        fecha: None,
+        texto: None,
+    };
+
+    if valores.is_empty() {
+        return descripcion;
+    }
+
+    let numeros: Vec<f64> = valores.iter().filter_map(|v| valor_numerico(v)).collect();
+    if numeros.len() == valores.len() {
+        descripcion.tipo = "numerico".to_string();
+        descripcion.numerico = Some(numeric_summary(numeros, bins));
+        return descripcion;
+    }
+
+    let fechas: Vec<NaiveDateTime> = valores.iter().filter_map(|v| valor_fecha(v)).collect();
+    if fechas.len() == valores.len() {
+        let min = fechas.iter().min().copied().unwrap_or_default();
+        let max = fechas.iter().max().copied().unwrap_or_default();
+        descripcion.tipo = "fecha".to_string();
+        descripcion.fecha = Some(DateSummary {
+            min: min.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            max: max.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            rango_dias: (max - min).num_seconds() as f64 / 86_400.0,
+        });
+        return descripcion;
+    }
+
+    let mut conteos: HashMap<String, usize> = HashMap::new();
+    for valor in &valores {
+        *conteos.entry(valor_texto(valor)).or_insert(0) += 1;
+    }
+    let mut frecuencias: Vec<FrequencyEntry> = conteos
+        .into_iter()
+        .map(|(valor, conteo)| FrequencyEntry { valor, conteo })
+        .collect();
+    frecuencias.sort_by(|a, b| b.conteo.cmp(&a.conteo).then_with(|| a.valor.cmp(&b.valor)));
+    frecuencias.truncate(top_n);
+
+    descripcion.tipo = "texto".to_string();
+    descripcion.texto = Some(TextSummary { frecuencias });
+    descripcion
+}
+
+fn numeric_summary(mut numeros: Vec<f64>, bins: Option<usize>) -> NumericSummary {
+    numeros.sort_by(|a, b| a.total_cmp(b));
+
+    let n = numeros.len();
+    let min = numeros[0];
+    let max = numeros[n - 1];
+    let media = numeros.iter().sum::<f64>() / n as f64;
+    let desviacion_estandar = if n > 1 {
+        (numeros.iter().map(|x| (x - media).powi(2)).sum::<f64>() / (n - 1) as f64).sqrt()
+    } else {
+        0.0
+    };
+
+    // Regla de Sturges si no se indica el número de intervalos
+    let bins = bins
+        .unwrap_or_else(|| (n as f64).log2().ceil() as usize + 1)
+        .clamp(1, MAX_INTERVALOS);
+    let ancho = (max - min) / bins as f64;
+    let mut histograma: Vec<HistogramBin> = (0..bins)
+        .map(|i| HistogramBin {
+            desde: min + ancho * i as f64,
+            hasta: if i + 1 == bins {
+                max
+            } else {
+                min + ancho * (i + 1) as f64
+            },
+            conteo: 0,
+        })
+        .collect();
+    for x in &numeros {
+        let i = if ancho > 0.0 {
+            (((x - min) / ancho) as usize).min(bins - 1)
+        } else {
+            0
+        };
+        histograma[i].conteo += 1;
+    }
+
+    NumericSummary {
+        min,
+        max,
+        media,
+        mediana: quantile(&numeros, 0.5),
+        desviacion_estandar,
+        q1: quantile(&numeros, 0.25),
+        q3: quantile(&numeros, 0.75),
+        histograma,
+    }
+}
+
+// Cuantil con interpolación lineal sobre datos ya ordenados
+pub(crate) fn quantile(ordenados: &[f64], q: f64) -> f64 {
+    if ordenados.is_empty() {
+        return f64::NAN;
+    }
+    let pos = q * (ordenados.len() - 1) as f64;
+    let base = pos.floor() as usize;
+    let resto = pos - base as f64;
+    match ordenados.get(base + 1) {
+        Some(siguiente) => ordenados[base] + resto * (siguiente - ordenados[base]),
+        None => ordenados[base],
+    }
+}
+
+// Añade a los datos de un panel grafico las líneas de tendencia pedidas en
+// `config.lineaTendencia` ({ columnaX, series }) como `data.tendencias`
+pub(crate) fn apply_trend_lines(config: &serde_json::Value, data: &mut serde_json::Value) {
+    let Some(tendencia) = config.get("lineaTendencia").filter(|t| !t.is_null()) else {
+        return;
+    };
+    let columna_x = tendencia.get("columnaX").and_then(|c| c.as_str());
+
+    // Series explícitas, o las de la configuración del gráfico
+    let series: Vec<String> = tendencia
+        .get("series")
+        .or_else(|| config.get("series"))
+        .and_then(|s| s.as_array())
+        .map(|series| {
+            series
+                .iter()
+                .filter_map(|s| s.as_str().or_else(|| s.get("key").and_then(|k| k.as_str())))
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let Some(datos) = data.get("datos").and_then(|d| d.as_array()) else {
+        return;
+    };
+
+    let total = datos.len();
+    let mut tendencias = Vec::new();
+
+    for serie in series {
+        // Sin columna x se usa el orden de inserción (los datos vienen del más reciente al más antiguo)
+        let puntos: Vec<(serde_json::Value, f64, f64)> = datos
+            .iter()
+            .enumerate()
+            .filter_map(|(i, fila)| {
+                let y = fila.get(&serie).and_then(valor_numerico)?;
+                match columna_x {
+                    Some(columna) => {
+                        let original = fila.get(columna)?.clone();
+                        let x = valor_numerico(&original).or_else(|| {
+                            valor_fecha(&original).map(|f| f.and_utc().timestamp() as f64)
+                        })?;
+                        Some((original, x, y))
+                    }
+                    None => {
+                        let orden = (total - 1 - i) as f64;
+                        Some((serde_json::json!(orden), orden, y))
+                    }
+                }
+            })
+            .collect();
+
+        let xs: Vec<Vec<f64>> = puntos.iter().map(|(_, x, _)| vec![*x]).collect();
+        let ys: Vec<f64> = puntos.iter().map(|(_, _, y)| *y).collect();
+        let Some(ajuste) = least_squares(&xs, &ys) else {
+            continue;
+        };
+
+        let mut ajustados: Vec<(f64, serde_json::Value)> = puntos
+            .into_iter()
+            .zip(&ajuste.ajustados)
+            .map(|((original, x, _), valor)| {
+                (x, serde_json::json!({ "x": original, "valor": valor }))
+            })
+            .collect();
+        ajustados.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        tendencias.push(serde_json::json!({
+            "serie": serie,
+            "intercepto": ajuste.coeficientes[0],
+            "pendiente": ajuste.coeficientes[1],
+            "r2": ajuste.r2,
+            "puntos": ajustados.into_iter().map(|(_, p)| p).collect::<Vec<_>>(),
+        }));
+    }
+
+    if let Some(obj) = data.as_object_mut() {
+        obj.insert(
+            "tendencias".to_string(),
+            serde_json::Value::Array(tendencias),
+        );
+    }
+}
+
+// Añade a los datos de un panel grafico las proyecciones pedidas en
+// `config.pronostico` ({ columnaFecha, series, periodos, metodo }) como `data.pronosticos`
+pub(crate) fn apply_forecasts(config: &serde_json::Value, data: &mut serde_json::Value) {
+    let Some(pronostico) = config.get("pronostico").filter(|p| !p.is_null()) else {
+        return;
+    };
+    let Some(columna_fecha) = pronostico.get("columnaFecha").and_then(|c| c.as_str()) else {
+        return;
+    };
+    let periodos = pronostico
+        .get("periodos")
+        .and_then(|p| p.as_u64())
+        .unwrap_or(6) as usize;
+    let metodo = pronostico
+        .get("metodo")
+        .and_then(|m| m.as_str())
+        .unwrap_or("holt_winters");
+    let estacionalidad = pronostico
+        .get("estacionalidad")
+        .and_then(|e| e.as_u64())
+        .map(|e| e as usize);
+
+    let series: Vec<String> = pronostico
+        .get("series")
+        .or_else(|| config.get("series"))
+        .and_then(|s| s.as_array())
+        .map(|series| {
+            series
+                .iter()
+                .filter_map(|s| s.as_str().or_else(|| s.get("key").and_then(|k| k.as_str())))
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let Some(datos) = data.get("datos").and_then(|d| d.as_array()) else {
+        return;
+    };
+
+    let mut pronosticos = Vec::new();
+    for serie in series {
+        let puntos = time_series(datos, columna_fecha, &serie);
+        match forecast(&puntos, periodos, metodo, estacionalidad, 0.95) {
+            Ok(resultado) => pronosticos.push(serde_json::json!({
+                "serie": serie,
+                "metodo": resultado.metodo,
+                "puntos": resultado.pronostico,
+            })),
+            Err(e) => log::warn!("No se pudo proyectar la serie {}: {}", serie, e),
+        }
+    }
+
+    if let Some(obj) = data.as_object_mut() {
+        obj.insert(
+            "pronosticos".to_string(),
+            serde_json::Value::Array(pronosticos),
+        );
+    }
+}
+
+// Extrae pares (fecha, valor) ordenados por fecha, sumando los valores de fechas repetidas
+fn time_series(
+    filas: &[serde_json::Value],
+    columna_fecha: &str,
+    columna_valor: &str,
+) -> Vec<(NaiveDateTime, f64)> {
+    let mut por_fecha: Vec<(NaiveDateTime, f64)> = filas
+        .iter()
+        .filter_map(|fila| {
+            let fecha = fila.get(columna_fecha).and_then(valor_fecha)?;
+            let valor = fila.get(columna_valor).and_then(valor_numerico)?;
+            Some((fecha, valor))
+        })
+        .collect();
+    por_fecha.sort_by_key(|(fecha, _)| *fecha);
+
+    let mut serie: Vec<(NaiveDateTime, f64)> = Vec::with_capacity(por_fecha.len());
+    for (fecha, valor) in por_fecha {
+        match serie.last_mut() {
+            Some((ultima, acumulado)) if *ultima == fecha => *acumulado += valor,
+            _ => serie.push((fecha, valor)),
+        }
+    }
+    serie
+}
+
+// Separación entre periodos consecutivos de una serie temporal
+#[derive(Clone, Copy)]
+enum Step {
+    Months(u32),
+    Seconds(i64),
+}
+
+impl Step {
+    // Mediana de las diferencias entre fechas, redondeada a meses si corresponde
+    fn infer(fechas: &[NaiveDateTime]) -> Step {
+        let mut difs: Vec<i64> = fechas
+            .windows(2)
+            .map(|w| (w[1] - w[0]).num_seconds())
+            .collect();
+        difs.sort_unstable();
+        let mediana = difs.get(difs.len() / 2).copied().unwrap_or(86_400).max(1);
+
+        let dias = mediana as f64 / 86_400.0;
+        if (28.0..=31.0).contains(&dias) {
+            Step::Months(1)
+        } else if (89.0..=92.0).contains(&dias) {
+            Step::Months(3)
+        } else if (365.0..=366.0).contains(&dias) {
+            Step::Months(12)
+        } else {
+            Step::Seconds(mediana)
+        }
+    }
+
+    // None si la fecha resultante no es representable
+    fn advance(self, fecha: NaiveDateTime, pasos: u32) -> Option<NaiveDateTime> {
+        match self {
+            Step::Months(m) => fecha.checked_add_months(Months::new(m.checked_mul(pasos)?)),
+            Step::Seconds(s) => {
+                fecha.checked_add_signed(Duration::try_seconds(s.checked_mul(pasos as i64)?)?)
+            }
+        }
+    }
+
+    // Longitud de ciclo habitual para la frecuencia de la serie
+    fn default_season(self) -> Option<usize> {
+        match self {
+            Step::Months(1) => Some(12),
+            Step::Months(3) => Some(4),
+            Step::Seconds(3_600) => Some(24),
+            Step::Seconds(86_400) => Some(7),
+            Step::Seconds(604_800) => Some(52),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn forecast(
+    serie: &[(NaiveDateTime, f64)],
+    periodos: usize,
+    metodo: &str,
+    estacionalidad: Option<usize>,
+    nivel: f64,
+) -> DbResult<ForecastResult> {
+    if serie.len() < 3 {
+        return Err(DbError::InvalidData(
+            "Se necesitan al menos tres puntos para proyectar".to_string(),
+        ));
+    }
+    if nivel <= 0.0 || nivel >= 1.0 {
+        return Err(DbError::InvalidData(
+            "El nivel de confianza debe estar entre 0 y 1".to_string(),
+        ));
+    }
+    if periodos > MAX_PERIODOS {
+        return Err(DbError::InvalidData(format!(
+            "Como máximo se pueden proyectar {} periodos",
+            MAX_PERIODOS
+        )));
+    }
+
+    let fechas: Vec<NaiveDateTime> = serie.iter().map(|(f, _)| *f).collect();
+    let valores: Vec<f64> = serie.iter().map(|(_, v)| *v).collect();
+    let paso = Step::infer(&fechas);
+
+    // La estacionalidad solo se usa si hay al menos dos ciclos completos
+    let estacionalidad = estacionalidad
+        .or_else(|| paso.default_season())
+        .filter(|&m| m >= 2 && valores.len() >= 2 * m);
+
+    let (valores_futuros, parametros, rmse) = match metodo {
+        "holt_winters" => holt_winters(&valores, estacionalidad, periodos, nivel),
+        "tendencia_estacional" => seasonal_trend(&valores, estacionalidad, periodos, nivel)?,
+        otro => {
+            return Err(DbError::InvalidData(format!(
+                "Método de pronóstico desconocido: {}",
+                otro
+            )))
+        }
+    };
+
+    // Fechas sin hora se devuelven como 'YYYY-MM-DD'
+    let solo_fecha = fechas.iter().all(|f| f.time() == chrono::NaiveTime::MIN);
+    let formatear = |fecha: NaiveDateTime| {
+        if solo_fecha {
+            fecha.format("%Y-%m-%d").to_string()
+        } else {
+            fecha.format("%Y-%m-%dT%H:%M:%S").to_string()
+        }
+    };
+
+    let ultima = fechas[fechas.len() - 1];
+    let pronostico = valores_futuros
+        .into_iter()
+        .enumerate()
+        .map(|(h, (valor, margen))| {
+            let fecha = paso.advance(ultima, h as u32 + 1).ok_or_else(|| {
+                DbError::InvalidData("El pronóstico se sale del rango de fechas".to_string())
+            })?;
+            Ok(ForecastPoint {
+                fecha: formatear(fecha),
+                valor,
+                inferior: Some(valor - margen),
+                superior: Some(valor + margen),
+            })
+        })
+        .collect::<DbResult<_>>()?;
+
+    let historico = serie
+        .iter()
+        .map(|(fecha, valor)| ForecastPoint {
+            fecha: formatear(*fecha),
+            valor: *valor,
+            inferior: None,
+            superior: None,
+        })
+        .collect();
+
+    Ok(ForecastResult {
+        metodo: metodo.to_string(),
+        estacionalidad,
+        parametros,
+        rmse,
+        historico,
+        pronostico,
+    })
+}
+
+// Valores proyectados con su margen de error, parámetros del modelo y error típico
+type ModelForecast = (Vec<(f64, f64)>, serde_json::Value, f64);
+
+// Suavizado exponencial de Holt-Winters aditivo (Holt lineal si no hay estacionalidad).
+// Los parámetros se eligen por búsqueda en rejilla minimizando el error a un paso.
+fn holt_winters(
+    valores: &[f64],
+    estacionalidad: Option<usize>,
+    periodos: usize,
+    nivel: f64,
+) -> ModelForecast {
+    let rejilla: Vec<f64> = (1..10).map(|i| i as f64 / 10.0).collect();
+    let gammas: &[f64] = if estacionalidad.is_some() {
+        &rejilla
+    } else {
+        &[0.0]
+    };
+
+    let mut mejor: Option<(f64, f64, f64, HoltWintersState)> = None;
+    for &alpha in &rejilla {
+        for &beta in &rejilla {
+            for &gamma in gammas {
+                let estado = HoltWintersState::fit(valores, estacionalidad, alpha, beta, gamma);
+                if mejor.as_ref().map_or(true, |m| estado.sse < m.3.sse) {
+                    mejor = Some((alpha, beta, gamma, estado));
+                }
+            }
+        }
+    }
+    let Some((alpha, beta, gamma, estado)) = mejor else {
+        return (Vec::new(), serde_json::json!({}), f64::NAN);
+    };
+
+    let sigma = (estado.sse / estado.errores.max(1) as f64).sqrt();
+    let z = Normal::new(0.0, 1.0)
+        .map(|n| n.inverse_cdf(0.5 + nivel / 2.0))
+        .unwrap_or(1.96);
+    let m = estacionalidad.unwrap_or(1);
+
+    let futuros = (1..=periodos)
+        .map(|h| {
+            let valor = estado.nivel
+                + h as f64 * estado.tendencia
+                + estado.estacional[(valores.len() + h - 1) % m];
+            // Varianza aproximada a h pasos del modelo aditivo
+            let acumulado: f64 = (1..h)
+                .map(|j| {
+                    let estacional = if estacionalidad.is_some() && j % m == 0 {
+                        gamma
+                    } else {
+                        0.0
+                    };
+                    (alpha * (1.0 + j as f64 * beta) + estacional).powi(2)
+                })
+                .sum();
+            (valor, z * sigma * (1.0 + acumulado).sqrt())
+        })
+        .collect();
+
+    (
+        futuros,
+        serde_json::json!({ "alpha": alpha, "beta": beta, "gamma": gamma }),
+        sigma,
+    )
+}
+
+struct HoltWintersState {
+    nivel: f64,
+    tendencia: f64,
+    estacional: Vec<f64>,
+    sse: f64,
+    errores: usize,
+}
+
+impl HoltWintersState {
+    fn fit(
+        valores: &[f64],
+        estacionalidad: Option<usize>,
+        alpha: f64,
+        beta: f64,
+        gamma: f64,
+    ) -> Self {
+        let (mut nivel, mut tendencia, mut estacional, inicio) = match estacionalidad {
+            Some(m) => {
+                let media = |ciclo: &[f64]| ciclo.iter().sum::<f64>() / ciclo.len() as f64;
+                let primero = media(&valores[..m]);
+                let tendencia = (media(&valores[m..2 * m]) - primero) / m as f64;
+                // El primer ciclo se centra en su media descontando la tendencia,
+                // y el nivel inicial corresponde a su último periodo
+                let centro = (m - 1) as f64 / 2.0;
+                let estacional = valores[..m]
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| v - (primero + tendencia * (i as f64 - centro)))
+                    .collect();
+                (primero + tendencia * centro, tendencia, estacional, m)
+            }
+            None => (valores[0], valores[1] - valores[0], vec![0.0], 1),
+        };
+        let m = estacional.len();
+
+        let mut sse = 0.0;
+        let mut errores = 0;
+        for (t, &y) in valores.iter().enumerate().skip(inicio) {
+            let s = estacional[t % m];
+            let error = y - (nivel + tendencia + s);
+            sse += error * error;
+            errores += 1;
+
+            let nivel_anterior = nivel;
+            nivel = alpha * (y - s) + (1.0 - alpha) * (nivel + tendencia);
+            tendencia = beta * (nivel - nivel_anterior) + (1.0 - beta) * tendencia;
+            if estacionalidad.is_some() {
+                estacional[t % m] = gamma * (y - nivel) + (1.0 - gamma) * s;
+            }
+        }
+
+        Self {
+            nivel,
+            tendencia,
+            estacional,
+            sse,
+            errores,
+        }
+    }
+}
+
+// Regresión sobre tendencia lineal con variables indicadoras por posición en el ciclo
+fn seasonal_trend(
+    valores: &[f64],
+    estacionalidad: Option<usize>,
+    periodos: usize,
+    nivel: f64,
+) -> DbResult<ModelForecast> {
+    let m = estacionalidad.unwrap_or(1);
+    let regresores = |t: usize| -> Vec<f64> {
+        std::iter::once(t as f64)
+            .chain((1..m).map(|k| if t % m == k { 1.0 } else { 0.0 }))
+            .collect()
+    };
+
+    let xs: Vec<Vec<f64>> = (0..valores.len()).map(regresores).collect();
+    let ajuste = least_squares(&xs, valores).ok_or_else(|| {
+        DbError::InvalidData("No hay suficientes puntos para el ajuste estacional".to_string())
+    })?;
+
+    let futuros = (1..=periodos)
+        .map(|h| ajuste.predict(&regresores(valores.len() - 1 + h), nivel))
+        .collect();
+
+    let parametros = serde_json::json!({
+        "intercepto": ajuste.coeficientes[0],
+        "pendiente": ajuste.coeficientes[1],
+        "efectosEstacionales": &ajuste.coeficientes[2..],
+        "r2": ajuste.r2,
+    });
+
+    Ok((futuros, parametros, ajuste.error_estandar))
+}
+
+// Marca en los datos de un panel las filas anómalas pedidas en `config.anomalias`
+// ({ columna, metodo, umbral, ventana, columnaOrden }) como `data.anomalias`,
+// con índices relativos a `data.datos`
+pub(crate) fn apply_anomalies(config: &serde_json::Value, data: &mut serde_json::Value) {
+    let Some(opciones) = config.get("anomalias").filter(|a| !a.is_null()) else {
+        return;
+    };
+    let Some(columna) = opciones.get("columna").and_then(|c| c.as_str()) else {
+        return;
+    };
+    let metodo = opciones
+        .get("metodo")
+        .and_then(|m| m.as_str())
+        .unwrap_or("zscore");
+    let umbral = opciones
+        .get("umbral")
+        .and_then(|u| u.as_f64())
+        .unwrap_or_else(|| default_anomaly_threshold(metodo));
+    let ventana = opciones
+        .get("ventana")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(7) as usize;
+
+    let Some(datos) = data.get("datos").and_then(|d| d.as_array()) else {
+        return;
+    };
+
+    // Los datos del panel vienen del más reciente al más antiguo
+    let mut orden: Vec<usize> = (0..datos.len()).rev().collect();
+    if let Some(columna_orden) = opciones.get("columnaOrden").and_then(|c| c.as_str()) {
+        orden.sort_by(|&a, &b| {
+            compare_values(datos[a].get(columna_orden), datos[b].get(columna_orden))
+        });
+    }
+
+    let valores: Vec<Option<f64>> = orden
+        .iter()
+        .map(|&i| datos[i].get(columna).and_then(valor_numerico))
+        .collect();
+
+    let anomalias = match find_anomalies(&valores, metodo, umbral, ventana) {
+        Ok(detectadas) => detectadas
+            .into_iter()
+            .map(|(i, esperado, puntuacion)| {
+                serde_json::json!({
+                    "indice": orden[i],
+                    "columna": columna,
+                    "esperado": esperado,
+                    "puntuacion": puntuacion,
+                })
+            })
+            .collect(),
+        Err(e) => {
+            log::warn!("No se pudieron detectar anomalías en {}: {}", columna, e);
+            Vec::new()
+        }
+    };
+
+    if let Some(obj) = data.as_object_mut() {
+        obj.insert("anomalias".to_string(), serde_json::Value::Array(anomalias));
+    }
+}
+
+fn default_anomaly_threshold(metodo: &str) -> f64 {
+    match metodo {
+        "iqr" => 1.5,
+        _ => 3.0,
+    }
+}
+
+// Devuelve (índice, valor esperado, puntuación) de los valores anómalos.
+// Los valores None no se evalúan pero conservan su posición.
+pub(crate) fn find_anomalies(
+    valores: &[Option<f64>],
+    metodo: &str,
+    umbral: f64,
+    ventana: usize,
+) -> DbResult<Vec<(usize, f64, f64)>> {
+    let presentes: Vec<(usize, f64)> = valores
+        .iter()
+        .enumerate()
+        .filter_map(|(i, v)| v.map(|v| (i, v)))
+        .collect();
+    if presentes.len() < 3 {
+        return Ok(Vec::new());
+    }
+    let numeros: Vec<f64> = presentes.iter().map(|(_, v)| *v).collect();
+
+    let puntuaciones: Vec<(f64, f64)> = match metodo {
+        "zscore" => {
+            let n = numeros.len() as f64;
+            let media = numeros.iter().sum::<f64>() / n;
+            let desviacion =
+                (numeros.iter().map(|x| (x - media).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+            numeros
+                .iter()
+                .map(|x| (media, safe_ratio((x - media).abs(), desviacion)))
+                .collect()
+        }
+        "iqr" => {
+            let mut ordenados = numeros.clone();
+            ordenados.sort_by(|a, b| a.total_cmp(b));
+            let q1 = quantile(&ordenados, 0.25);
+            let q3 = quantile(&ordenados, 0.75);
+            let mediana = quantile(&ordenados, 0.5);
+            let iqr = q3 - q1;
+            // Puntuación en múltiplos del rango intercuartílico fuera de la caja
+            numeros
+                .iter()
+                .map(|&x| {
+                    let fuera = if x < q1 {
+                        q1 - x
+                    } else if x > q3 {
+                        x - q3
+                    } else {
+                        0.0
+                    };
+                    (mediana, safe_ratio(fuera, iqr))
+                })
+                .collect()
+        }
+        "mediana_movil" => {
+            let mitad = ventana.max(3) / 2;
+            let medianas: Vec<f64> = (0..numeros.len())
+                .map(|i| {
+                    let desde = i.saturating_sub(mitad);
+                    let hasta = (i + mitad + 1).min(numeros.len());
+                    let mut vecinos = numeros[desde..hasta].to_vec();
+                    vecinos.sort_by(|a, b| a.total_cmp(b));
+                    quantile(&vecinos, 0.5)
+                })
+                .collect();
+            // Escala robusta: desviación absoluta mediana de los residuos
+            let mut residuos: Vec<f64> = numeros
+                .iter()
+                .zip(&medianas)
+                .map(|(x, m)| (x - m).abs())
+                .collect();
+            residuos.sort_by(|a, b| a.total_cmp(b));
+            let escala = 1.4826 * quantile(&residuos, 0.5);
+            numeros
+                .iter()
+                .zip(&medianas)
+                .map(|(x, &m)| (m, safe_ratio((x - m).abs(), escala)))
+                .collect()
+        }
+        otro => {
+            return Err(DbError::InvalidData(format!(
+                "Método de detección desconocido: {}",
+                otro
+            )))
+        }
+    };
+
+    Ok(presentes
+        .iter()
+        .zip(puntuaciones)
+        .filter(|(_, (_, puntuacion))| *puntuacion > umbral)
+        .map(|((i, _), (esperado, puntuacion))| (*i, esperado, puntuacion))
+        .collect())
+}
+
+// Cociente que trata la escala nula: cualquier desviación es infinitamente anómala
+fn safe_ratio(desviacion: f64, escala: f64) -> f64 {
+    if escala > 0.0 {
+        desviacion / escala
+    } else if desviacion > 0.0 {
+        f64::INFINITY
+    } else {
+        0.0
+    }
+}
+
+// Orden total entre valores JSON: primero por tipo (número < fecha < texto < ausente),
+// después por valor dentro de cada tipo. Los nulos y vacíos quedan al final
+pub(crate) fn compare_values(
+    a: Option<&serde_json::Value>,
+    b: Option<&serde_json::Value>,
+) -> std::cmp::Ordering {
+    ClaveOrden::new(a).cmp(&ClaveOrden::new(b))
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum ClaveOrden {
+    Numero(OrdenNumerico),
+    Fecha(NaiveDateTime),
+    Texto(String),
+    Nulo,
+}
+
+impl ClaveOrden {
+    fn new(valor: Option<&serde_json::Value>) -> Self {
+        let Some(valor) = valor.filter(|v| !es_nulo(v)) else {
+            return ClaveOrden::Nulo;
+        };
+        if let Some(numero) = valor_numerico(valor) {
+            return ClaveOrden::Numero(OrdenNumerico(numero));
+        }
+        if let Some(fecha) = valor_fecha(valor) {
+            return ClaveOrden::Fecha(fecha);
+        }
+        ClaveOrden::Texto(valor_texto(valor))
+    }
+}
+
+// f64 con el orden total de `total_cmp`
+struct OrdenNumerico(f64);
+
+impl PartialEq for OrdenNumerico {
+    fn eq(&self, otro: &Self) -> bool {
+        self.cmp(otro).is_eq()
+    }
+}
+
+impl Eq for OrdenNumerico {}
+
+impl PartialOrd for OrdenNumerico {
+    fn partial_cmp(&self, otro: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(otro))
+    }
+}
+
+impl Ord for OrdenNumerico {
+    fn cmp(&self, otro: &Self) -> std::cmp::Ordering {
+        self.0.total_cmp(&otro.0)
+    }
+}
+
+// Ajuste por mínimos cuadrados ordinarios (el coeficiente 0 es el intercepto).
+// Se resuelve con los regresores centrados para no perder precisión con valores
+// grandes (p. ej. fechas); `xtx_inv` corresponde a ese diseño centrado.
+pub(crate) struct LeastSquaresFit {
+    pub coeficientes: Vec<f64>,
+    pub medias: Vec<f64>,
+    pub xtx_inv: Vec<Vec<f64>>,
+    pub ajustados: Vec<f64>,
+    pub residuos: Vec<f64>,
+    pub r2: f64,
+    pub r2_ajustado: f64,
+    pub error_estandar: f64,
+    pub grados_libertad: usize,
+}
+
+impl LeastSquaresFit {
+    // Valor predicho y semiancho del intervalo de predicción para un punto x
+    pub fn predict(&self, x: &[f64], nivel: f64) -> (f64, f64) {
+        let valor = self.coeficientes[0]
+            + x.iter()
+                .zip(&self.coeficientes[1..])
+                .map(|(a, b)| a * b)
+                .sum::<f64>();
+
+        let fila: Vec<f64> = std::iter::once(1.0)
+            .chain(x.iter().zip(&self.medias).map(|(a, m)| a - m))
+            .collect();
+
+        let mut apalancamiento = 0.0;
+        for (i, a) in fila.iter().enumerate() {
+            for (j, b) in fila.iter().enumerate() {
+                apalancamiento += a * self.xtx_inv[i][j] * b;
+            }
+        }
+
+        let t = t_critical(nivel, self.grados_libertad);
+        (
+            valor,
+            t * self.error_estandar * (1.0 + apalancamiento).sqrt(),
+        )
+    }
+}
+
+pub(crate) fn least_squares(xs: &[Vec<f64>], ys: &[f64]) -> Option<LeastSquaresFit> {
+    let n = ys.len();
+    let p = xs.first()?.len() + 1;
+    if n <= p {
+        return None;
+    }
+
+    let medias: Vec<f64> = (0..p - 1)
+        .map(|j| xs.iter().map(|x| x[j]).sum::<f64>() / n as f64)
+        .collect();
+    let diseno: Vec<Vec<f64>> = xs
+        .iter()
+        .map(|x| {
+            std::iter::once(1.0)
+                .chain(x.iter().zip(&medias).map(|(a, m)| a - m))
+                .collect()
+        })
+        .collect();
+
+    let mut xtx = vec![vec![0.0; p]; p];
+    let mut xty = vec![0.0; p];
+    for (fila, y) in diseno.iter().zip(ys) {
+        for i in 0..p {
+            xty[i] += fila[i] * y;
+            for j in 0..p {
+                xtx[i][j] += fila[i] * fila[j];
+            }
+        }
+    }
+
+    let xtx_inv = invert_matrix(xtx)?;
+    let mut coeficientes: Vec<f64> = (0..p)
+        .map(|i| (0..p).map(|j| xtx_inv[i][j] * xty[j]).sum())
+        .collect();
+
+    let ajustados: Vec<f64> = diseno
+        .iter()
+        .map(|fila| fila.iter().zip(&coeficientes).map(|(a, b)| a * b).sum())
+        .collect();
+
+    // Intercepto en la escala original de los regresores
+    coeficientes[0] -= coeficientes[1..]
+        .iter()
+        .zip(&medias)
+        .map(|(b, m)| b * m)
+        .sum::<f64>();
+    let residuos: Vec<f64> = ys.iter().zip(&ajustados).map(|(y, f)| y - f).collect();
+
+    let media = ys.iter().sum::<f64>() / n as f64;
+    let sst: f64 = ys.iter().map(|y| (y - media).powi(2)).sum();
+    let sse: f64 = residuos.iter().map(|r| r * r).sum();
+    let grados_libertad = n - p;
+    let r2 = if sst > 0.0 { 1.0 - sse / sst } else { 1.0 };
+
+    Some(LeastSquaresFit {
+        coeficientes,
+        medias,
+        xtx_inv,
+        ajustados,
+        residuos,
+        r2,
+        r2_ajustado: 1.0 - (1.0 - r2) * (n - 1) as f64 / grados_libertad as f64,
+        error_estandar: (sse / grados_libertad as f64).sqrt(),
+        grados_libertad,
+    })
+}
+
+// Inversa por Gauss-Jordan con pivoteo parcial; None si la matriz es singular
+fn invert_matrix(mut a: Vec<Vec<f64>>) -> Option<Vec<Vec<f64>>> {
+    let n = a.len();
+    let mut inv: Vec<Vec<f64>> = (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect();
+
+    for col in 0..n {
+        let pivote = (col..n).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivote][col].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(col, pivote);
+        inv.swap(col, pivote);
+
+        let factor = a[col][col];
+        for j in 0..n {
+            a[col][j] /= factor;
+            inv[col][j] /= factor;
+        }
+
+        for i in 0..n {
+            if i != col {
+                let f = a[i][col];
+                for j in 0..n {
+                    a[i][j] -= f * a[col][j];
+                    inv[i][j] -= f * inv[col][j];
+                }
+            }
+        }
+    }
+
+    Some(inv)
+}
+
+// Valor crítico bilateral de la t de Student para el nivel de confianza dado
+pub(crate) fn t_critical(nivel: f64, grados_libertad: usize) -> f64 {
+    StudentsT::new(0.0, 1.0, grados_libertad.max(1) as f64)
+        .map(|t| t.inverse_cdf(0.5 + nivel / 2.0))
+        .unwrap_or(f64::NAN)
+}
+
+pub(crate) fn pearson_correlation(xs: &[f64], ys: &[f64]) -> Option<f64> {
+    let n = xs.len();
+    if n < 2 {
+        return None;
+    }
+    let media_x = xs.iter().sum::<f64>() / n as f64;
+    let media_y = ys.iter().sum::<f64>() / n as f64;
+
+    let mut cov = 0.0;
+    let mut var_x = 0.0;
+    let mut var_y = 0.0;
+    for (x, y) in xs.iter().zip(ys) {
+        cov += (x - media_x) * (y - media_y);
+        var_x += (x - media_x).powi(2);
+        var_y += (y - media_y).powi(2);
+    }
+
+    if var_x == 0.0 || var_y == 0.0 {
+        return None;
+    }
+    Some(cov / (var_x * var_y).sqrt())
+}
+
+// Rangos (base 1) promediando los empates, para la correlación de Spearman
+fn ranks(valores: &[f64]) -> Vec<f64> {
+    let mut indices: Vec<usize> = (0..valores.len()).collect();
+    indices.sort_by(|&a, &b| valores[a].total_cmp(&valores[b]));
+
+    let mut rangos = vec![0.0; valores.len()];
+    let mut i = 0;
+    while i < indices.len() {
+        let mut j = i;
+        while j + 1 < indices.len() && valores[indices[j + 1]] == valores[indices[i]] {
+            j += 1;
+        }
+        let rango = (i + j) as f64 / 2.0 + 1.0;
+        for &idx in &indices[i..=j] {
+            rangos[idx] = rango;
+        }
+        i = j + 1;
+    }
+    rangos
+}
+
+fn es_nulo(valor: &serde_json::Value) -> bool {
+    match valor {
+        serde_json::Value::Null => true,
+        serde_json::Value::String(s) => s.trim().is_empty(),
+        _ => false,
+    }
+}
+
+// Representación textual de un valor JSON (sin comillas para cadenas)
+pub(crate) fn valor_texto(valor: &serde_json::Value) -> String {
+    match valor {
+        serde_json::Value::String(s) => s.clone(),
+        otro => otro.to_string(),
+    }
+}
+
+// Interpreta un valor JSON como fecha (RFC 3339, 'YYYY-MM-DD HH:MM:SS' o 'YYYY-MM-DD')
+pub(crate) fn valor_fecha(valor: &serde_json::Value) -> Option<NaiveDateTime> {
+    let texto = valor.as_str()?.trim();
+
+    if let Ok(fecha) = DateTime::parse_from_rfc3339(texto) {
+        return Some(fecha.naive_utc());
+    }
+    for formato in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"] {
+        if let Ok(fecha) = NaiveDateTime::parse_from_str(texto, formato) {
+            return Some(fecha);
+        }
+    }
+    NaiveDate::parse_from_str(texto, "%Y-%m-%d")
+        .ok()
+        .and_then(|fecha| fecha.and_hms_opt(0, 0, 0))
+}
+
+// Interpreta un valor JSON como número (acepta números y cadenas numéricas)
+pub(crate) fn valor_numerico(valor: &serde_json::Value) -> Option<f64> {
+    match valor {
+        serde_json::Value::Number(n) => n.as_f64(),
+        serde_json::Value::String(s) => s.trim().parse::<f64>().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn compare_values_es_un_orden_total_con_tipos_mezclados() {
+        let valores = [
+            json!("b"),
+            json!(10),
+            json!("2024-01-02"),
+            json!(null),
+            json!("9"),
+            json!("a"),
+            json!("2023-12-31"),
+            json!(""),
+        ];
+        let mut ordenados: Vec<&serde_json::Value> = valores.iter().collect();
+        ordenados.sort_by(|a, b| compare_values(Some(a), Some(b)));
+
+        let esperado = [
+            json!("9"),
+            json!(10),
+            json!("2023-12-31"),
+            json!("2024-01-02"),
+            json!("a"),
+            json!("b"),
+            json!(null),
+            json!(""),
+        ];
+        let ordenados: Vec<serde_json::Value> = ordenados.into_iter().cloned().collect();
+        assert_eq!(ordenados, esperado);
+
+        // Transitividad en cualquier terna
+        for a in &valores {
+            for b in &valores {
+                for c in &valores {
+                    if compare_values(Some(a), Some(b)).is_le()
+                        && compare_values(Some(b), Some(c)).is_le()
+                    {
+                        assert!(compare_values(Some(a), Some(c)).is_le());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn numeric_summary_limita_los_intervalos() {
+        let resumen = numeric_summary(vec![1.0, 2.0, 3.0, 4.0], Some(usize::MAX));
+        assert_eq!(resumen.histograma.len(), MAX_INTERVALOS);
+        assert_eq!(
+            resumen.histograma.iter().map(|b| b.conteo).sum::<usize>(),
+            4
+        );
+
+        let resumen = numeric_summary(vec![1.0, 2.0, 3.0, 4.0], Some(0));
+        assert_eq!(resumen.histograma.len(), 1);
+    }
+
+    #[test]
+    fn compare_values_deja_los_nulos_al_final() {
+        for valor in [json!(-1e300), json!("2024-01-01"), json!("zzz")] {
+            assert!(compare_values(None, Some(&valor)).is_gt());
+            assert!(compare_values(Some(&json!(null)), Some(&valor)).is_gt());
+            assert!(compare_values(Some(&json!("  ")), Some(&valor)).is_gt());
+        }
+        assert!(compare_values(None, Some(&json!(null))).is_eq());
+
+        let mut valores = vec![json!(null), json!(3), json!(1)];
+        valores.sort_by(|a, b| compare_values(Some(a), Some(b)));
+        assert_eq!(valores, [json!(1), json!(3), json!(null)]);
+    }
+
+    #[test]
+    fn least_squares_con_regresores_grandes() {
+        // Marcas de tiempo en milisegundos: sin centrar, X'X pierde toda la precisión
+        let inicio = 1.7e12;
+        let dia = 86_400_000.0;
+        let xs: Vec<Vec<f64>> = (0..30).map(|i| vec![inicio + i as f64 * dia]).collect();
+        let ys: Vec<f64> = (0..30)
+            .map(|i| 10.0 + 2.0 * i as f64 + if i % 2 == 0 { 0.5 } else { -0.5 })
+            .collect();
+
+        let ajuste = least_squares(&xs, &ys).expect("ajuste");
+        assert!((ajuste.coeficientes[1] * dia - 2.0).abs() < 1e-2);
+        assert!(ajuste.r2 > 0.99);
+
+        let (valor, margen) = ajuste.predict(&[inicio + 30.0 * dia], 0.95);
+        assert!((valor - 70.0).abs() < 0.5);
+        assert!(margen.is_finite() && margen > 0.0);
+    }
+
+    fn mensual(valores: &[f64]) -> Vec<(NaiveDateTime, f64)> {
+        let inicio = NaiveDate::from_ymd_opt(2023, 1, 1).unwrap();
+        valores
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                let fecha = inicio.checked_add_months(Months::new(i as u32)).unwrap();
+                (fecha.and_hms_opt(0, 0, 0).unwrap(), *v)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn holt_winters_sigue_una_tendencia_lineal() {
+        let valores: Vec<f64> = (0..20).map(|t| 10.0 + 2.0 * t as f64).collect();
+        let (futuros, _, rmse) = holt_winters(&valores, None, 3, 0.95);
+
+        assert!(rmse < 1e-9);
+        for (h, (valor, margen)) in futuros.iter().enumerate() {
+            assert!((valor - (10.0 + 2.0 * (20 + h) as f64)).abs() < 1e-6);
+            assert!(*margen < 1e-6);
+        }
+    }
+
+    #[test]
+    fn holt_winters_repite_el_ciclo_estacional() {
+        let ciclo = [5.0, -5.0, 3.0, -3.0];
+        let valor = |t: usize| 100.0 + t as f64 + ciclo[t % 4];
+        let valores: Vec<f64> = (0..16).map(valor).collect();
+        let (futuros, parametros, _) = holt_winters(&valores, Some(4), 8, 0.95);
+
+        assert_eq!(futuros.len(), 8);
+        for (h, (proyectado, margen)) in futuros.iter().enumerate() {
+            assert!(
+                (proyectado - valor(16 + h)).abs() < 0.5,
+                "h={} {} != {}",
+                h + 1,
+                proyectado,
+                valor(16 + h)
+            );
+            assert!(margen.is_finite());
+        }
+        assert!(parametros["gamma"].as_f64().is_some());
+    }
+
+    #[test]
+    fn holt_winters_margenes_crecen_con_el_horizonte() {
+        let valores: Vec<f64> = (0..30)
+            .map(|t| 50.0 + t as f64 * 0.5 + if t % 3 == 0 { 4.0 } else { -2.0 })
+            .collect();
+        let (futuros, _, rmse) = holt_winters(&valores, None, 6, 0.95);
+
+        assert!(rmse > 0.0);
+        for par in futuros.windows(2) {
+            assert!(par[1].1 >= par[0].1);
+        }
+    }
+
+    #[test]
+    fn forecast_mensual_con_estacionalidad_por_defecto() {
+        let valores: Vec<f64> = (0..24)
+            .map(|t| 20.0 + t as f64 + if t % 12 < 6 { 3.0 } else { -3.0 })
+            .collect();
+        let serie = mensual(&valores);
+
+        let resultado = forecast(&serie, 3, "holt_winters", None, 0.9).unwrap();
+        assert_eq!(resultado.estacionalidad, Some(12));
+        assert_eq!(resultado.historico.len(), 24);
+        let fechas: Vec<&str> = resultado
+            .pronostico
+            .iter()
+            .map(|p| p.fecha.as_str())
+            .collect();
+        assert_eq!(fechas, ["2025-01-01", "2025-02-01", "2025-03-01"]);
+        for punto in &resultado.pronostico {
+            assert!(
+                punto.inferior.unwrap() <= punto.valor && punto.valor <= punto.superior.unwrap()
+            );
+        }
+
+        // Sin dos ciclos completos no se usa la estacionalidad
+        let resultado = forecast(&serie[..20], 1, "holt_winters", None, 0.9).unwrap();
+        assert_eq!(resultado.estacionalidad, None);
+
+        assert!(forecast(&serie[..2], 1, "holt_winters", None, 0.9).is_err());
+        assert!(forecast(&serie, 1, "holt_winters", None, 1.0).is_err());
+        assert!(forecast(&serie, 1, "arima", None, 0.9).is_err());
+        assert!(forecast(&serie, MAX_PERIODOS + 1, "holt_winters", None, 0.9).is_err());
+
+        // Proyectar más allá de la última fecha representable es un error, no un pánico
+        let anual: Vec<_> = (0..6)
+            .map(|i| {
+                let fecha = NaiveDate::from_ymd_opt(262_000 + i, 1, 1).unwrap();
+                (fecha.and_hms_opt(0, 0, 0).unwrap(), i as f64)
+            })
+            .collect();
+        assert!(forecast(&anual, MAX_PERIODOS, "holt_winters", None, 0.9).is_err());
+    }
+}
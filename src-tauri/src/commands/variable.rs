@@ -0,0 +1,263 @@
+use crate::commands::board::{ensure_board_owner, get_board_by_id};
+use crate::commands::stats::{valor_fecha, valor_numerico, valor_texto};
+use crate::models::{
+    board::{Board, BoardVariable},
+    database::{AppStateHandle, DbError, DbResult},
+};
+use chrono::Utc;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+
+const TIPOS_VARIABLE: [&str; 4] = ["texto", "numero", "fecha", "booleano"];
+
+#[tauri::command]
+pub async fn set_board_variables(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    board_id: String,
+    variables: Vec<BoardVariable>,
+) -> DbResult<Vec<BoardVariable>> {
+    let pool = &state.pool;
+
+    ensure_board_owner(pool, &board_id, &user_id).await?;
+
+    for (i, variable) in variables.iter().enumerate() {
+        let nombre_valido = variable
+            .nombre
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && variable
+                .nombre
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !nombre_valido {
+            return Err(DbError::InvalidData(format!(
+                "Nombre de variable inválido: {}",
+                variable.nombre
+            )));
+        }
+        if !TIPOS_VARIABLE.contains(&variable.tipo.as_str()) {
+            return Err(DbError::InvalidData(format!(
+                "Tipo de variable desconocido: {}",
+                variable.tipo
+            )));
+        }
+        if variables[..i].iter().any(|v| v.nombre == variable.nombre) {
+            return Err(DbError::InvalidData(format!(
+                "Variable duplicada: {}",
+                variable.nombre
+            )));
+        }
+        validate_variable_value(variable, &variable.valor_defecto)?;
+        validate_variable_value(variable, &variable.valor)?;
+    }
+
+    save_board_variables(pool, &board_id, &variables).await?;
+
+    Ok(variables)
+}
+
+#[tauri::command]
+pub async fn set_board_variable_values(
+    state: AppStateHandle<'_>,
+    user_id: String,
+    board_id: String,
+    valores: HashMap<String, serde_json::Value>,
+) -> DbResult<Board> {
+    let pool = &state.pool;
+
+    ensure_board_owner(pool, &board_id, &user_id).await?;
+
+    let mut variables = load_board_variables(pool, &board_id).await?;
+    for (nombre, valor) in valores {
+        let variable = variables
+            .iter_mut()
+            .find(|v| v.nombre == nombre)
+            .ok_or_else(|| DbError::InvalidData(format!("Variable desconocida: {}", nombre)))?;
+        validate_variable_value(variable, &valor)?;
+        variable.valor = valor;
+    }
+
+    save_board_variables(pool, &board_id, &variables).await?;
+
+    // Devolver el board con los paneles recalculados
+    get_board_by_id(state, board_id, user_id, None).await
+}
+
+pub(crate) async fn load_board_variables(
+    pool: &SqlitePool,
+    board_id: &str,
+) -> DbResult<Vec<BoardVariable>> {
+    let variables: Option<(String,)> = sqlx::query_as("SELECT variables FROM boards WHERE id = ?")
+        .bind(board_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(variables
+        .and_then(|(json,)| serde_json::from_str(&json).ok())
+        .unwrap_or_default())
+}
+
+async fn save_board_variables(
+    pool: &SqlitePool,
+    board_id: &str,
+    variables: &[BoardVariable],
+) -> DbResult<()> {
+    let variables_json =
+        serde_json::to_string(variables).map_err(|e| DbError::InvalidData(e.to_string()))?;
+    let now = Utc::now().to_rfc3339();
+
+    sqlx::query("UPDATE boards SET variables = ?, updated_at = ? WHERE id = ?")
+        .bind(&variables_json)
+        .bind(&now)
+        .bind(board_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+fn validate_variable_value(variable: &BoardVariable, valor: &serde_json::Value) -> DbResult<()> {
+    if valor.is_null() {
+        return Ok(());
+    }
+    if coerce_variable(&variable.tipo, valor).is_none() {
+        return Err(DbError::InvalidData(format!(
+            "Valor inválido para la variable ${} ({})",
+            variable.nombre, variable.tipo
+        )));
+    }
+    if let Some(opciones) = &variable.opciones {
+        if !opciones
+            .iter()
+            .any(|o| valor_texto(o) == valor_texto(valor))
+        {
+            return Err(DbError::InvalidData(format!(
+                "Valor no permitido para la variable ${}",
+                variable.nombre
+            )));
+        }
+    }
+    Ok(())
+}
+
+// Convierte un valor al tipo de la variable; None si no es compatible
+fn coerce_variable(tipo: &str, valor: &serde_json::Value) -> Option<serde_json::Value> {
+    match tipo {
+        "numero" => valor_numerico(valor).map(|n| serde_json::json!(n)),
+        "fecha" => valor_fecha(valor).map(|_| valor.clone()),
+        "booleano" => match valor {
+            serde_json::Value::Bool(b) => Some(serde_json::json!(b)),
+            serde_json::Value::String(s) => s.parse::<bool>().ok().map(|b| serde_json::json!(b)),
+            _ => None,
+        },
+        _ => Some(serde_json::json!(valor_texto(valor))),
+    }
+}
+
+// Valor vigente de cada variable: el actual o, si no hay, el de por defecto
+pub(crate) fn resolve_variables(variables: &[BoardVariable]) -> HashMap<String, serde_json::Value> {
+    variables
+        .iter()
+        .map(|v| {
+            let valor = if v.valor.is_null() {
+                &v.valor_defecto
+            } else {
+                &v.valor
+            };
+            let valor = coerce_variable(&v.tipo, valor).unwrap_or(serde_json::Value::Null);
+            (v.nombre.clone(), valor)
+        })
+        .collect()
+}
+
+// Sustituye las referencias $nombre y ${nombre} dentro de un valor JSON.
+// Una cadena que es solo la referencia toma el valor con su tipo; dentro de
+// un texto más largo se sustituye su representación textual.
+pub(crate) fn substitute_variables(
+    valor: &serde_json::Value,
+    variables: &HashMap<String, serde_json::Value>,
+) -> serde_json::Value {
+    match valor {
+        serde_json::Value::String(texto) => substitute_in_text(texto, variables),
+        serde_json::Value::Array(items) => items
+            .iter()
+            .map(|v| substitute_variables(v, variables))
+            .collect(),
+        serde_json::Value::Object(obj) => obj
+            .iter()
+            .map(|(k, v)| (k.clone(), substitute_variables(v, variables)))
+            .collect::<serde_json::Map<_, _>>()
+            .into(),
+        otro => otro.clone(),
+    }
+}
+
+pub(crate) fn substitute_in_text(
+    texto: &str,
+    variables: &HashMap<String, serde_json::Value>,
+) -> serde_json::Value {
+    let referencias = find_references(texto);
+
+    if let [(inicio, fin, nombre)] = referencias.as_slice() {
+        if *inicio == 0 && *fin == texto.len() {
+            if let Some(valor) = variables.get(nombre) {
+                return valor.clone();
+            }
+        }
+    }
+
+    let mut resultado = String::with_capacity(texto.len());
+    let mut ultimo = 0;
+    for (inicio, fin, nombre) in referencias {
+        if let Some(valor) = variables.get(&nombre) {
+            resultado.push_str(&texto[ultimo..inicio]);
+            resultado.push_str(&valor_texto(valor));
+            ultimo = fin;
+        }
+    }
+    resultado.push_str(&texto[ultimo..]);
+    serde_json::Value::String(resultado)
+}
+
+// Posiciones (inicio, fin, nombre) de cada referencia a variable en el texto
+fn find_references(texto: &str) -> Vec<(usize, usize, String)> {
+    let bytes = texto.as_bytes();
+    let es_inicio = |b: u8| b.is_ascii_alphabetic() || b == b'_';
+    let es_parte = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+
+    let mut referencias = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'$' {
+            i += 1;
+            continue;
+        }
+
+        let (desde, llaves) = if bytes.get(i + 1) == Some(&b'{') {
+            (i + 2, true)
+        } else {
+            (i + 1, false)
+        };
+        if !bytes.get(desde).is_some_and(|&b| es_inicio(b)) {
+            i += 1;
+            continue;
+        }
+
+        let mut hasta = desde;
+        while hasta < bytes.len() && es_parte(bytes[hasta]) {
+            hasta += 1;
+        }
+        if llaves && bytes.get(hasta) != Some(&b'}') {
+            i += 1;
+            continue;
+        }
+
+        let fin = if llaves { hasta + 1 } else { hasta };
+        referencias.push((i, fin, texto[desde..hasta].to_string()));
+        i = fin;
+    }
+
+    referencias
+}
@@ -2,7 +2,9 @@ pub mod commands;
 pub mod models;
 
 use models::database::AppState;
+use models::geo::MapPackStore;
 use std::path::PathBuf;
+use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -40,8 +42,38 @@ pub fn run() {
                 app_handle.manage(state);
             });
 
+            // Paquetes de mapas sin conexión: los incluidos en la app y los que instale el usuario
+            let mut directorios_mapas = Vec::new();
+            if let Ok(recursos) = app.path().resource_dir() {
+                directorios_mapas.push((recursos.join("mapas"), "incluido".to_string()));
+            }
+            directorios_mapas.push((app_dir.join("mapas"), "usuario".to_string()));
+            app.manage(MapPackStore::new(directorios_mapas));
+
+            // Generar en segundo plano los reportes programados
+            tauri::async_runtime::spawn(commands::schedule::run_report_scheduler(
+                app.handle().clone(),
+            ));
+
+            // Borrar las imágenes de notas cuyos paneles ya no existen
+            tauri::async_runtime::spawn(commands::note::prune_note_attachments(
+                app.handle().clone(),
+            ));
+
             Ok(())
         })
+        .register_asynchronous_uri_scheme_protocol(
+            commands::map::PROTOCOLO_MAPAS,
+            |ctx, request, responder| {
+                let app = ctx.app_handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    let store = app.state::<MapPackStore>();
+                    responder.respond(
+                        commands::map::map_protocol_response(store.inner(), &request).await,
+                    );
+                });
+            },
+        )
         .invoke_handler(tauri::generate_handler![
             // Comandos de autenticación
             commands::auth::register_user,
@@ -52,12 +84,104 @@ pub fn run() {
             commands::board::get_board_by_id,
             commands::board::create_board,
             commands::board::create_panel,
+            commands::board::update_panel,
+            commands::organization::get_board_folders,
+            commands::organization::create_board_folder,
+            commands::organization::rename_board_folder,
+            commands::organization::move_board_folder,
+            commands::organization::delete_board_folder,
+            commands::organization::move_board_to_folder,
+            commands::organization::set_board_tags,
+            commands::organization::get_board_tags,
+            commands::organization::set_board_favorite,
+            commands::organization::reorder_boards,
+            commands::organization::mark_board_opened,
+            commands::panel::get_panel_groups,
+            commands::panel::group_panels,
+            commands::panel::ungroup_panels,
+            commands::panel::move_panel_group,
+            commands::panel::resize_panel_group,
+            commands::panel::set_panel_locked,
+            commands::panel::set_panel_hidden,
+            commands::panel::bring_panel_to_front,
+            commands::panel::send_panel_to_back,
+            commands::panel::reorder_panels,
+            commands::layout::set_board_layout,
+            commands::layout::auto_arrange_board,
+            commands::layout::get_panel_overlaps,
+            commands::layout::resolve_panel_collisions,
+            commands::layout::update_panel_layout,
+            commands::layout::reset_breakpoint_layout,
+            commands::page::get_board_pages,
+            commands::page::create_board_page,
+            commands::page::rename_board_page,
+            commands::page::reorder_board_pages,
+            commands::page::delete_board_page,
+            commands::page::set_page_viewport,
+            commands::page::move_panels_to_page,
+            commands::preference::get_board_preferences,
+            commands::preference::set_board_preferences,
+            commands::note::get_panel_note,
+            commands::note::update_panel_note,
+            commands::note::get_note_revisions,
+            commands::note::restore_note_revision,
+            commands::note::add_note_attachment,
+            commands::note::delete_note_attachment,
+            commands::calendar::get_calendar_events,
+            commands::calendar::create_calendar_event,
+            commands::calendar::update_calendar_event,
+            commands::calendar::delete_calendar_event,
+            commands::calendar::expand_calendar_events,
+            commands::calendar::get_calendar_panel_events,
+            commands::ics::import_calendar_ics,
+            commands::ics::export_calendar_ics,
+            commands::list::get_list_items,
+            commands::list::add_list_item,
+            commands::list::update_list_item,
+            commands::list::toggle_list_item,
+            commands::list::reorder_list_items,
+            commands::list::delete_list_item,
+            commands::list::get_list_counts,
+            commands::geo::import_geojson,
+            commands::geo::query_geo_points,
+            commands::geo::aggregate_geo_regions,
+            commands::map::list_map_packs,
+            commands::map::get_boundary_choropleth,
+            commands::filter::set_board_filters,
+            commands::filter::set_board_filter_values,
+            commands::filter::set_board_selection,
+            commands::filter::clear_board_selection,
+            commands::variable::set_board_variables,
+            commands::variable::set_board_variable_values,
+            commands::search::search,
 
             commands::stats::get_global_datasets,
             commands::stats::get_dataset_by_id,
             commands::stats::create_dataset,
             commands::stats::add_dataset_data,
+            commands::stats::set_dataset_column_types,
             commands::stats::delete_dataset,
+            commands::stats::describe_dataset,
+            commands::stats::correlation_matrix,
+            commands::stats::linear_regression,
+            commands::stats::forecast_series,
+            commands::stats::detect_anomalies,
+            commands::pivot::pivot_dataset,
+            // Comandos de reportes
+            commands::report::export_board_report,
+            commands::schedule::create_report_schedule,
+            commands::schedule::get_report_schedules,
+            commands::schedule::set_report_schedule_active,
+            commands::schedule::delete_report_schedule,
+            commands::schedule::run_report_schedule,
+            commands::schedule::get_report_runs,
+            // Comandos de alertas
+            commands::alert::create_alert_rule,
+            commands::alert::get_alert_rules,
+            commands::alert::delete_alert_rule,
+            commands::alert::mute_alert_rule,
+            commands::alert::acknowledge_alert,
+            commands::alert::get_alert_events,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
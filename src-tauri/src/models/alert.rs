@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertRule {
+    pub id: String,
+    pub dataset_id: String,
+    pub nombre: String,
+    pub columna: Option<String>, // None solo para 'count'
+    pub agregacion: String,      // 'avg', 'sum', 'min', 'max', 'count'
+    pub operador: String,        // '>', '>=', '<', '<=', '=', '!='
+    pub umbral: f64,
+    pub ventana_minutos: Option<i64>, // Filas insertadas en los últimos N minutos
+    pub filtro: serde_json::Value,    // Objeto {columna: valor} que deben cumplir las filas
+    pub silenciada: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct AlertRuleDb {
+    pub id: String,
+    pub dataset_id: String,
+    pub nombre: String,
+    pub columna: Option<String>,
+    pub agregacion: String,
+    pub operador: String,
+    pub umbral: f64,
+    pub ventana_minutos: Option<i64>,
+    pub filtro: String, // JSON string
+    pub silenciada: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<AlertRuleDb> for AlertRule {
+    fn from(db: AlertRuleDb) -> Self {
+        Self {
+            id: db.id,
+            dataset_id: db.dataset_id,
+            nombre: db.nombre,
+            columna: db.columna,
+            agregacion: db.agregacion,
+            operador: db.operador,
+            umbral: db.umbral,
+            ventana_minutos: db.ventana_minutos,
+            filtro: serde_json::from_str(&db.filtro).unwrap_or(serde_json::json!({})),
+            silenciada: db.silenciada,
+            created_at: db.created_at,
+            updated_at: db.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertEvent {
+    pub id: String,
+    pub rule_id: String,
+    pub dataset_id: String,
+    pub nombre: String,
+    pub estado: String, // 'activa', 'resuelta'
+    pub valor: Option<f64>,
+    pub umbral: f64,
+    pub reconocida: bool,
+    pub disparada_at: String,
+    pub resuelta_at: Option<String>,
+    pub reconocida_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateAlertRuleDto {
+    pub dataset_id: String,
+    pub nombre: String,
+    pub columna: Option<String>,
+    pub agregacion: String,
+    pub operador: String,
+    pub umbral: f64,
+    pub ventana_minutos: Option<i64>,
+    pub filtro: Option<serde_json::Value>,
+}
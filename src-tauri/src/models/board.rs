@@ -1,4 +1,5 @@
-use serde::{Deserialize, Serialize};
+use crate::models::panel::PanelKind;
+use serde::{Deserialize, Deserializer, Serialize};
 use sqlx::FromRow;
 
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
@@ -17,7 +18,8 @@ pub struct Size {
 pub struct Panel {
     pub id: String,
     pub board_id: String,
-    pub tipo: String,
+    #[sqlx(try_from = "String")]
+    pub tipo: PanelKind,
     pub titulo: String,
     #[sqlx(skip)]
     pub posicion: Position,
@@ -27,9 +29,15 @@ pub struct Panel {
     pub z_index: i32,
     pub activo: bool,
     pub dataset_id: Option<String>, // Vinculación al dataset
+    pub page_id: Option<String>,    // Página del board en la que está
+    pub grupo_id: Option<String>,   // Grupo con el que se mueve y redimensiona
+    pub bloqueado: bool,
+    pub oculto: bool,
     #[sqlx(skip)]
     pub config: serde_json::Value, // Configuración específica del panel
     #[sqlx(skip)]
+    pub config_plantilla: Option<serde_json::Value>, // Config sin sustituir variables, si las usa
+    #[sqlx(skip)]
     pub data: Option<serde_json::Value>, // Datos del dataset vinculado
     pub created_at: String,
     pub updated_at: String,
@@ -49,6 +57,10 @@ pub struct PanelDb {
     pub activo: i32,
     pub dataset_id: Option<String>,
     pub config: String,
+    pub page_id: Option<String>,
+    pub grupo_id: Option<String>,
+    pub bloqueado: i32,
+    pub oculto: i32,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -62,12 +74,175 @@ pub struct Board {
     pub description: String,
     pub icon: String,
     pub color: String,
+    pub folder_id: Option<String>, // Carpeta; None = raíz
+    #[sqlx(json)]
+    pub etiquetas: Vec<String>,
+    pub favorito: bool, // Los favoritos se fijan al principio de la lista
+    pub orden: i64,     // Posición en el orden manual
+    pub abierto_at: Option<String>,
+    pub cuadricula: Option<i32>, // Tamaño de celda para ajustar posiciones
+    pub evitar_solapes: bool,
     pub created_at: String,
     pub updated_at: String,
     #[sqlx(skip)]
+    pub filtros: Vec<BoardFilter>,
+    #[sqlx(skip)]
+    pub selecciones: Vec<BoardSelection>,
+    #[sqlx(skip)]
+    pub variables: Vec<BoardVariable>,
+    #[sqlx(skip)]
+    pub pages: Vec<BoardPage>,
+    #[sqlx(skip)]
     pub panels: Vec<Panel>,
 }
 
+// Filtro de board que se aplica a los datos de todos sus paneles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardFilter {
+    #[serde(default)]
+    pub id: String,
+    pub columna: String,
+    pub etiqueta: String,
+    pub tipo: String, // 'valores', 'rango_fechas', 'rango_numerico'
+    #[serde(default)]
+    pub valor: serde_json::Value, // Valor actual; null = filtro inactivo
+}
+
+// Variable de board ($nombre) que se sustituye en títulos, config y filtros de los paneles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardVariable {
+    pub nombre: String, // Sin el '$'
+    pub etiqueta: String,
+    pub tipo: String, // 'texto', 'numero', 'fecha', 'booleano'
+    #[serde(default)]
+    pub valor_defecto: serde_json::Value,
+    #[serde(default)]
+    pub valor: serde_json::Value, // Valor actual; null = usar el valor por defecto
+    pub opciones: Option<Vec<serde_json::Value>>, // Valores permitidos, si se restringen
+}
+
+// Selección hecha en un panel (p. ej. clic en una barra) que filtra a los demás
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardSelection {
+    pub panel_id: String,
+    pub valores: Vec<SelectionValue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectionValue {
+    pub columna: String,
+    pub valor: serde_json::Value,
+}
+
+// Carpeta de boards; se anidan mediante parent_id
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardFolder {
+    pub id: String,
+    pub user_id: String,
+    pub nombre: String,
+    pub parent_id: Option<String>,
+    pub orden: i64,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+// Orden y filtros opcionales de get_my_boards
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardListQuery {
+    pub orden: Option<String>, // 'creacion' (por defecto), 'actualizacion', 'titulo', 'manual', 'recientes'
+    pub folder_id: Option<String>,
+    #[serde(default)]
+    pub incluir_subcarpetas: bool,
+    #[serde(default)]
+    pub sin_carpeta: bool, // Solo boards en la raíz
+    pub etiqueta: Option<String>,
+    #[serde(default)]
+    pub solo_favoritos: bool,
+    pub texto: Option<String>, // Busca también en los paneles del board
+    pub limite: Option<i64>,
+}
+
+// Estado del lienzo y opciones de edición de un usuario en un board
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardPreferences {
+    pub board_id: String,
+    pub user_id: String,
+    pub page_id: Option<String>, // Página abierta la última vez; si no hay, la primera
+    pub viewport_x: f64,         // Viewport de esa página
+    pub viewport_y: f64,
+    pub escala: f64,
+    pub panel_seleccionado_id: Option<String>,
+    pub mostrar_minimapa: bool,
+    pub cuadricula: Option<i32>, // De las reglas de layout del board
+    pub updated_at: Option<String>, // None mientras no se haya guardado nada
+}
+
+// Solo se modifican los campos presentes. El viewport se guarda en la página abierta
+// y la cuadrícula en las reglas de layout del board
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateBoardPreferencesDto {
+    pub page_id: Option<String>,
+    pub viewport_x: Option<f64>,
+    pub viewport_y: Option<f64>,
+    pub escala: Option<f64>,
+    pub panel_seleccionado_id: Option<String>,
+    #[serde(default)]
+    pub limpiar_seleccion: bool, // Quita el panel seleccionado
+    pub mostrar_minimapa: Option<bool>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub cuadricula: Option<Option<i32>>, // null vuelve a las posiciones libres
+}
+
+// Página (pestaña) de un board con su propio lienzo
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardPage {
+    pub id: String,
+    pub board_id: String,
+    pub nombre: String,
+    pub orden: i64,
+    pub viewport_x: f64, // Desplazamiento del lienzo al abrir la página
+    pub viewport_y: f64,
+    pub zoom: f64,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageViewport {
+    pub x: f64,
+    pub y: f64,
+    pub zoom: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct PanelGroup {
+    pub id: String,
+    pub board_id: String,
+    pub nombre: String,
+    pub created_at: String,
+    #[sqlx(skip)]
+    pub panel_ids: Vec<String>,
+}
+
+// Posición de un panel en el orden de apilamiento del board
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct PanelLayer {
+    pub panel_id: String,
+    pub z_index: i32,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateBoardDto {
@@ -81,7 +256,8 @@ pub struct CreateBoardDto {
 #[serde(rename_all = "camelCase")]
 pub struct CreatePanelDto {
     pub board_id: String,
-    pub tipo: String,
+    pub page_id: Option<String>, // Por defecto, la primera página del board
+    pub tipo: PanelKind,
     pub titulo: String,
     pub posicion: Position,
     pub tamano: Size,
@@ -98,6 +274,16 @@ pub struct UpdatePanelDto {
     pub tamano: Option<Size>,
     pub z_index: Option<i32>,
     pub activo: Option<bool>,
-    pub dataset_id: Option<String>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub dataset_id: Option<Option<String>>, // null desvincula el dataset
     pub config: Option<serde_json::Value>,
 }
+
+// Distingue un campo ausente (None) de uno enviado como null (Some(None))
+fn double_option<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+// Evento de calendario de un usuario, opcionalmente vinculado a un board.
+// Las fechas se guardan en hora local: 'YYYY-MM-DDTHH:MM:SS', o 'YYYY-MM-DD' si es de día completo.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarEvent {
+    pub id: String,
+    pub user_id: String,
+    pub board_id: Option<String>,
+    pub uid: Option<String>, // UID del .ics del que se importó
+    pub titulo: String,
+    pub descripcion: Option<String>,
+    pub inicio: String,
+    pub fin: Option<String>, // En eventos de día completo, el último día incluido
+    pub todo_el_dia: bool,
+    pub recurrencia: Option<String>, // RRULE, p. ej. 'FREQ=WEEKLY;BYDAY=MO,WE'
+    #[sqlx(json)]
+    pub excepciones: Vec<String>, // Inicios de ocurrencias anuladas
+    pub color: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+// Datos de un evento al crearlo o al reemplazarlo
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarEventDto {
+    pub board_id: Option<String>,
+    pub titulo: String,
+    pub descripcion: Option<String>,
+    pub inicio: String,
+    pub fin: Option<String>,
+    #[serde(default)]
+    pub todo_el_dia: bool,
+    pub recurrencia: Option<String>,
+    #[serde(default)]
+    pub excepciones: Vec<String>,
+    pub color: Option<String>,
+}
+
+// Aparición concreta de un evento, o de una fila de dataset, dentro de una ventana de fechas
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarOccurrence {
+    pub event_id: Option<String>, // None si viene de un dataset
+    pub board_id: Option<String>,
+    pub titulo: String,
+    pub inicio: String,
+    pub fin: Option<String>,
+    pub todo_el_dia: bool,
+    pub color: Option<String>,
+    pub recurrente: bool,
+    pub origen: String, // 'evento', 'dataset'
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IcsImportResult {
+    pub creados: usize,
+    pub actualizados: usize, // Eventos con un UID ya importado
+    pub omitidos: usize,
+    pub errores: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IcsExportResult {
+    pub ruta: String,
+    pub eventos: usize,
+}
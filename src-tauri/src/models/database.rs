@@ -2,6 +2,7 @@ use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
 use std::sync::Arc;
 use tauri::State;
 use thiserror::Error;
+use uuid::Uuid;
 
 #[derive(Error, Debug)]
 pub enum DbError {
@@ -46,10 +47,244 @@ impl AppState {
             .execute(&pool)
             .await?;
 
+        // Columnas añadidas después de crear las tablas
+        ensure_column(&pool, "boards", "filtros", "TEXT NOT NULL DEFAULT '[]'").await?;
+        ensure_column(&pool, "boards", "variables", "TEXT NOT NULL DEFAULT '[]'").await?;
+        ensure_column(
+            &pool,
+            "boards",
+            "folder_id",
+            "TEXT REFERENCES board_folders(id) ON DELETE SET NULL",
+        )
+        .await?;
+        ensure_column(&pool, "boards", "etiquetas", "TEXT NOT NULL DEFAULT '[]'").await?;
+        ensure_column(&pool, "boards", "favorito", "INTEGER NOT NULL DEFAULT 0").await?;
+        ensure_column(&pool, "boards", "orden", "INTEGER NOT NULL DEFAULT 0").await?;
+        ensure_column(&pool, "boards", "abierto_at", "TEXT").await?;
+        ensure_column(&pool, "boards", "cuadricula", "INTEGER").await?;
+        ensure_column(&pool, "boards", "evitar_solapes", "INTEGER NOT NULL DEFAULT 0").await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_boards_folder_id ON boards(folder_id)")
+            .execute(&pool)
+            .await?;
+        ensure_column(
+            &pool,
+            "panels",
+            "grupo_id",
+            "TEXT REFERENCES panel_groups(id) ON DELETE SET NULL",
+        )
+        .await?;
+        ensure_column(&pool, "panels", "bloqueado", "INTEGER NOT NULL DEFAULT 0").await?;
+        ensure_column(&pool, "panels", "oculto", "INTEGER NOT NULL DEFAULT 0").await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_panels_grupo_id ON panels(grupo_id)")
+            .execute(&pool)
+            .await?;
+        ensure_column(
+            &pool,
+            "panels",
+            "page_id",
+            "TEXT REFERENCES board_pages(id) ON DELETE CASCADE",
+        )
+        .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_panels_page_id ON panels(page_id)")
+            .execute(&pool)
+            .await?;
+        ensure_column(
+            &pool,
+            "global_datasets",
+            "tipos_columna",
+            "TEXT NOT NULL DEFAULT '{}'",
+        )
+        .await?;
+
+        // Boards anteriores a las páginas
+        ensure_board_pages(&pool).await?;
+
+        // Listas que aún guardan sus elementos en la config
+        ensure_list_items(&pool).await?;
+
+        // Bases creadas antes del índice de búsqueda o de su versión actual
+        ensure_search_index(&pool).await?;
+
         Ok(Self {
             pool: Arc::new(pool),
         })
     }
 }
 
+// Añade una columna a una base existente si aún no la tiene
+async fn ensure_column(
+    pool: &SqlitePool,
+    tabla: &str,
+    columna: &str,
+    definicion: &str,
+) -> Result<(), sqlx::Error> {
+    let existe: Option<(String,)> =
+        sqlx::query_as("SELECT name FROM pragma_table_info(?) WHERE name = ?")
+            .bind(tabla)
+            .bind(columna)
+            .fetch_optional(pool)
+            .await?;
+
+    if existe.is_none() {
+        sqlx::query(&format!(
+            "ALTER TABLE {} ADD COLUMN {} {}",
+            tabla, columna, definicion
+        ))
+        .execute(pool)
+        .await?;
+    }
+
+    Ok(())
+}
+
+// Da a cada board sin páginas una página por defecto con todos sus paneles
+async fn ensure_board_pages(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let sin_paginas: Vec<(String, String)> = sqlx::query_as(
+        r#"
+        SELECT id, created_at FROM boards b
+        WHERE NOT EXISTS (SELECT 1 FROM board_pages p WHERE p.board_id = b.id)
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for (board_id, created_at) in sin_paginas {
+        sqlx::query(
+            r#"
+            INSERT INTO board_pages (id, board_id, nombre, orden, created_at, updated_at)
+            VALUES (?, ?, 'Página 1', 0, ?, ?)
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(&board_id)
+        .bind(&created_at)
+        .bind(&created_at)
+        .execute(pool)
+        .await?;
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE panels SET page_id = (
+            SELECT id FROM board_pages p WHERE p.board_id = panels.board_id
+            ORDER BY orden LIMIT 1
+        )
+        WHERE page_id IS NULL
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// Pasa los elementos de `config.items` de las listas a filas de panel_list_items
+async fn ensure_list_items(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let listas: Vec<(String, String)> = sqlx::query_as(
+        r#"
+        SELECT id, config FROM panels
+        WHERE tipo = 'lista'
+          AND CASE WHEN json_valid(config) THEN json_array_length(config, '$.items') > 0 END
+        "#,
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let now = chrono::Utc::now().to_rfc3339();
+    for (panel_id, config) in listas {
+        let config: serde_json::Value = serde_json::from_str(&config).unwrap_or_default();
+        let items = config
+            .get("items")
+            .and_then(|i| i.as_array())
+            .cloned()
+            .unwrap_or_default();
+
+        let (siguiente,): (i64,) = sqlx::query_as(
+            "SELECT COALESCE(MAX(orden) + 1, 0) FROM panel_list_items WHERE panel_id = ?",
+        )
+        .bind(&panel_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        for (i, item) in items.iter().enumerate() {
+            let texto = match item {
+                serde_json::Value::String(texto) => texto.as_str(),
+                _ => item
+                    .get("titulo")
+                    .and_then(|t| t.as_str())
+                    .unwrap_or_default(),
+            };
+            if texto.trim().is_empty() {
+                continue;
+            }
+            let completado = item.get("estado").and_then(|e| e.as_str()) == Some("completado")
+                || item.get("completado").and_then(|c| c.as_bool()) == Some(true);
+
+            sqlx::query(
+                r#"
+                INSERT INTO panel_list_items
+                (id, panel_id, texto, completado, orden, completado_at, created_at, updated_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(&panel_id)
+            .bind(texto.trim())
+            .bind(completado)
+            .bind(siguiente + i as i64)
+            .bind(completado.then(|| now.clone()))
+            .bind(&now)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query("UPDATE panels SET config = json_remove(config, '$.items') WHERE id = ?")
+            .bind(&panel_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
+
+// Versión del contenido del índice de búsqueda; se sube cuando cambia lo que se indexa
+const VERSION_INDICE_BUSQUEDA: i64 = 1;
+
+// Rehace el índice de búsqueda en bases anteriores a él o a su versión actual;
+// a partir de ahí lo mantienen los triggers
+async fn ensure_search_index(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let (version,): (i64,) = sqlx::query_as("PRAGMA user_version")
+        .fetch_one(pool)
+        .await?;
+    if version >= VERSION_INDICE_BUSQUEDA {
+        return Ok(());
+    }
+
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM search_index")
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        r#"
+        INSERT INTO search_index (tipo, entidad_id, board_id, user_id, titulo, contenido)
+        SELECT 'board', id, id, user_id, title, COALESCE(description, '') FROM boards
+        UNION ALL
+        SELECT 'panel', id, board_id, user_id, titulo, contenido FROM search_panels
+        UNION ALL
+        SELECT 'dataset', id, NULL, NULL, nombre, columnas FROM global_datasets
+        "#,
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(&format!("PRAGMA user_version = {}", VERSION_INDICE_BUSQUEDA))
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
 pub type AppStateHandle<'a> = State<'a, AppState>;
\ No newline at end of file
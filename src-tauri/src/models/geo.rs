@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeoJsonImportResult {
+    pub dataset_id: String,
+    pub filas: usize,
+    pub omitidas: usize, // Features sin geometría válida
+    pub columnas: Vec<String>,
+}
+
+// Consulta de puntos de un panel de mapa para la vista actual
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeoPointsQuery {
+    pub columna: Option<String>, // Por defecto, `columnaGeo` de la config del panel
+    pub bbox: Option<[f64; 4]>,  // [oeste, sur, este, norte] en grados
+    pub zoom: Option<f64>,       // Sin zoom no se agrupan los puntos
+    pub radio: Option<f64>,      // Radio de agrupación en píxeles; por defecto 60
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeoPoint {
+    pub lat: f64,
+    pub lon: f64,
+    pub fila: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeoCluster {
+    pub lat: f64, // Centro de los puntos agrupados
+    pub lon: f64,
+    pub conteo: usize,
+    pub bbox: [f64; 4], // Extensión de los puntos, para acercarse al pulsar
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeoPointsResult {
+    pub total: usize,         // Puntos dentro del bbox
+    pub sin_ubicacion: usize, // Filas sin ubicación válida
+    pub puntos: Vec<GeoPoint>,
+    pub clusters: Vec<GeoCluster>,
+}
+
+// Agregación de los puntos de un panel por las regiones de otro dataset
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeoRegionsQuery {
+    pub columna: Option<String>, // Por defecto, `columnaGeo` de la config del panel
+    pub regiones_dataset_id: String,
+    pub columna_region: String, // Columna GeoJSON con los polígonos
+    pub columna_nombre: String,
+    pub medida: Option<String>,
+    pub agregacion: Option<String>, // 'sum', 'avg', 'count', 'min', 'max'; por defecto 'count'
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeoRegion {
+    pub region: String,
+    pub conteo: usize,
+    pub valor: Option<f64>,
+    pub geometria: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeoRegionsResult {
+    pub regiones: Vec<GeoRegion>,
+    pub sin_region: usize, // Puntos fuera de todas las regiones
+}
+
+// Paquete de mapas sin conexión declarado en el `paquetes.json` de una carpeta de mapas
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MapPack {
+    pub id: String,
+    pub nombre: String,
+    pub tipo: String,    // 'limites' (GeoJSON) o 'teselas' (MBTiles)
+    pub fichero: String, // Relativo a la carpeta del manifiesto
+    pub propiedad_codigo: Option<String>, // Límites: propiedad con el código de región; por defecto 'codigo'
+    pub propiedad_nombre: Option<String>, // Límites: propiedad con el nombre de región
+    pub atribucion: Option<String>,
+    #[serde(default, skip_deserializing)]
+    pub origen: String, // 'incluido' (recursos de la app) o 'usuario' (datos de la app)
+    #[serde(default, skip_deserializing)]
+    pub disponible: bool, // El fichero existe
+    #[serde(skip)]
+    pub ruta: PathBuf,
+}
+
+// Carpetas de paquetes de mapas, sus manifiestos ya leídos y los ficheros ya abiertos
+pub struct MapPackStore {
+    pub directorios: Vec<(PathBuf, String)>, // Carpeta y origen; las últimas tienen prioridad
+    pub paquetes: RwLock<Option<Vec<MapPack>>>, // Se lee al primer uso y al listar los paquetes
+    pub teselas: Mutex<HashMap<PathBuf, SqlitePool>>,
+    pub limites: Mutex<HashMap<PathBuf, Arc<serde_json::Value>>>,
+}
+
+impl MapPackStore {
+    pub fn new(directorios: Vec<(PathBuf, String)>) -> Self {
+        Self {
+            directorios,
+            paquetes: RwLock::new(None),
+            teselas: Mutex::new(HashMap::new()),
+            limites: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+// Coropletas: une las filas de un panel de mapa con las regiones de un paquete de límites
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeoChoroplethQuery {
+    pub limites: Option<String>, // Por defecto, `limites` de la config del panel
+    pub columna_codigo: Option<String>, // Por defecto, `columnaCodigo` de la config del panel
+    pub medida: Option<String>,
+    pub agregacion: Option<String>, // 'sum', 'avg', 'count', 'min', 'max'; por defecto 'count'
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeoChoroplethResult {
+    pub limites: String,
+    pub geojson: serde_json::Value, // FeatureCollection con codigo, nombre, valor y conteo
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub sin_coincidencia: Vec<String>, // Códigos del dataset que no están en el paquete
+}
@@ -0,0 +1,47 @@
+use crate::models::board::{Position, Size};
+use serde::{Deserialize, Serialize};
+
+// Reglas de maquetación que el backend aplica al guardar paneles
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardLayoutOptions {
+    pub cuadricula: Option<i32>, // Tamaño de celda en px; None = posiciones libres
+    #[serde(default)]
+    pub evitar_solapes: bool, // Empuja a los vecinos cuando un panel cae encima
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoArrangeDto {
+    pub estrategia: String, // 'grid', 'masonry', 'flow'
+    pub columnas: Option<i32>,
+    pub espaciado: Option<i32>,
+    pub ancho: Option<i32>, // Ancho disponible del lienzo
+}
+
+// Posición y tamaño de un panel en un breakpoint distinto de escritorio
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PanelLayoutDto {
+    pub breakpoint: String, // 'laptop', 'tablet'
+    pub posicion: Position,
+    #[serde(rename = "tamaño")]
+    pub tamano: Size,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PanelRect {
+    pub panel_id: String,
+    pub posicion: Position,
+    #[serde(rename = "tamaño")]
+    pub tamano: Size,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PanelOverlap {
+    pub panel_id: String,
+    pub otro_panel_id: String,
+    pub area: i64, // Superficie solapada en px²
+}
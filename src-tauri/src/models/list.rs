@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+// Elemento de un panel de lista
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ListItem {
+    pub id: String,
+    pub panel_id: String,
+    pub texto: String,
+    pub completado: bool,
+    pub fecha_limite: Option<String>, // 'YYYY-MM-DD' o 'YYYY-MM-DDTHH:MM:SS' en hora local
+    pub asignado_id: Option<String>,
+    pub asignado_nombre: Option<String>,
+    pub prioridad: String, // 'baja', 'media', 'alta'
+    pub orden: i64,
+    pub vencido: bool, // Pendiente y con la fecha límite ya pasada
+    pub completado_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+// Datos de un elemento al crearlo o al reemplazarlo
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListItemDto {
+    pub texto: String,
+    pub fecha_limite: Option<String>,
+    pub asignado_id: Option<String>,
+    pub prioridad: Option<String>, // Por defecto 'media'
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ListCounts {
+    pub total: i64,
+    pub abiertos: i64,
+    pub completados: i64,
+    pub vencidos: i64,
+}
@@ -2,8 +2,30 @@ pub mod board;
 pub mod stats;
 pub mod user;
 pub mod database;
+pub mod alert;
+pub mod pivot;
+pub mod report;
+pub mod schedule;
+pub mod search;
+pub mod layout;
+pub mod panel;
+pub mod note;
+pub mod calendar;
+pub mod list;
+pub mod geo;
 
 pub use board::*;
 pub use stats::*;
 pub use user::*;
-pub use database::*;
\ No newline at end of file
+pub use database::*;
+pub use alert::*;
+pub use pivot::*;
+pub use report::*;
+pub use schedule::*;
+pub use search::*;
+pub use layout::*;
+pub use panel::*;
+pub use note::*;
+pub use calendar::*;
+pub use list::*;
+pub use geo::*;
\ No newline at end of file
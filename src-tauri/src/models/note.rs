@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+// Contenido de un panel de notas con sus imágenes
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct PanelNote {
+    pub panel_id: String,
+    pub contenido: String, // Markdown
+    #[sqlx(skip)]
+    pub adjuntos: Vec<NoteAttachment>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>, // None mientras la nota no se haya guardado
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteRevision {
+    pub id: String,
+    pub panel_id: String,
+    pub user_id: Option<String>,
+    pub contenido: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteAttachment {
+    pub id: String,
+    pub panel_id: String,
+    pub nombre: String,
+    pub mime: String, // 'image/png', 'image/jpeg', 'image/gif', 'image/webp'
+    pub tamano: i64,
+    pub ruta: String, // Ruta absoluta del fichero
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdatePanelNoteDto {
+    pub contenido: String,
+    pub updated_at: Option<String>, // El `updatedAt` leído; si la nota cambió desde entonces, se rechaza
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddNoteAttachmentDto {
+    pub nombre: String,
+    pub datos: Vec<u8>,
+}
@@ -0,0 +1,179 @@
+use crate::models::database::DbError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Tipos de panel que conoce el frontend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PanelKind {
+    Estadistica,
+    Grafico,
+    Lista,
+    Tabla,
+    Mapa,
+    Calendario,
+    Notas,
+    // Tipo guardado que esta versión no conoce; el panel se muestra pero no se edita
+    #[serde(skip_deserializing)]
+    Desconocido,
+}
+
+impl PanelKind {
+    pub const ALL: [PanelKind; 7] = [
+        PanelKind::Estadistica,
+        PanelKind::Grafico,
+        PanelKind::Lista,
+        PanelKind::Tabla,
+        PanelKind::Mapa,
+        PanelKind::Calendario,
+        PanelKind::Notas,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PanelKind::Estadistica => "estadistica",
+            PanelKind::Grafico => "grafico",
+            PanelKind::Lista => "lista",
+            PanelKind::Tabla => "tabla",
+            PanelKind::Mapa => "mapa",
+            PanelKind::Calendario => "calendario",
+            PanelKind::Notas => "notas",
+            PanelKind::Desconocido => "desconocido",
+        }
+    }
+}
+
+impl TryFrom<String> for PanelKind {
+    type Error = DbError;
+
+    fn try_from(tipo: String) -> Result<Self, Self::Error> {
+        PanelKind::ALL
+            .into_iter()
+            .find(|k| k.as_str() == tipo)
+            .ok_or_else(|| DbError::InvalidData(format!("Tipo de panel desconocido: {}", tipo)))
+    }
+}
+
+// Formato de presentación de un número
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct NumberFormat {
+    pub estilo: Option<String>, // 'numero', 'moneda', 'porcentaje', 'compacto'
+    pub decimales: Option<u8>,
+    pub moneda: Option<String>, // Código ISO, p. ej. 'EUR'
+    pub prefijo: Option<String>,
+    pub sufijo: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct EstadisticaConfig {
+    pub valor: Option<serde_json::Value>, // Valor fijo si no se calcula del dataset
+    pub subtitulo: Option<String>,
+    pub columna: Option<String>,
+    pub agregacion: Option<String>, // 'sum', 'avg', 'min', 'max', 'count'
+    pub formato: Option<NumberFormat>,
+    pub lista_panel_id: Option<String>, // Panel de lista del mismo board cuyo conteo se muestra
+    pub conteo_lista: Option<String>,   // 'total', 'abiertos', 'completados', 'vencidos'
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ChartSeries {
+    pub key: String,
+    pub name: Option<String>,
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct GraficoConfig {
+    pub titulo: Option<String>,       // Título dentro del gráfico, aparte del del panel
+    pub tipo_grafico: Option<String>, // 'lineas', 'barras', 'area', 'circular'
+    #[serde(default)]
+    pub series: Vec<ChartSeries>,
+    pub columna_x: Option<String>,
+    #[serde(default)]
+    pub datos: Vec<serde_json::Value>, // Datos locales, sin dataset
+    #[serde(default)]
+    pub colores: Vec<String>, // Paleta para las series sin color propio
+    pub curve_type: Option<String>,
+    pub legend_position: Option<String>,
+    pub formato: Option<NumberFormat>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct TablaConfig {
+    #[serde(default)]
+    pub columnas: Vec<String>,
+    #[serde(default)]
+    pub filas: Vec<Vec<serde_json::Value>>, // Datos locales, sin dataset
+    #[serde(default)]
+    pub formatos: BTreeMap<String, NumberFormat>, // Por nombre de columna
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ListaItem {
+    pub id: serde_json::Value,
+    pub titulo: String,
+    pub estado: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ListaConfig {
+    #[serde(default)]
+    pub items: Vec<ListaItem>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct MapaConfig {
+    #[serde(default)]
+    pub ubicaciones: Vec<serde_json::Value>,
+    pub columna_geo: Option<String>, // Columna 'latlon' o 'geojson' del dataset vinculado
+    pub radio_cluster: Option<f64>,  // Radio de agrupación en píxeles
+    pub mapa_base: Option<String>,   // Paquete de teselas sin conexión
+    // Coropleta sobre un paquete de límites, unida por código de región
+    pub limites: Option<String>,
+    pub columna_codigo: Option<String>,
+    pub medida: Option<String>,
+    pub agregacion: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct CalendarioConfig {
+    #[serde(default)]
+    pub eventos: Vec<serde_json::Value>,
+    pub incluir_eventos: Option<bool>, // Eventos del board; por defecto sí
+    // Columnas del dataset vinculado que generan eventos
+    pub columna_inicio: Option<String>,
+    pub columna_fin: Option<String>,
+    pub columna_titulo: Option<String>,
+    pub columna_color: Option<String>,
+    pub todo_el_dia: Option<bool>, // Por defecto, según el formato de la fecha
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct NotasConfig {
+    pub contenido: Option<String>,
+}
+
+// Config tipada según el tipo del panel. Las secciones compartidas (filtro,
+// pivote, análisis) se validan aparte y no forman parte de estas estructuras;
+// cualquier otra clave que no conozcan se rechaza.
+#[derive(Debug, Clone)]
+pub enum PanelConfig {
+    Estadistica(EstadisticaConfig),
+    Grafico(GraficoConfig),
+    Lista(ListaConfig),
+    Tabla(TablaConfig),
+    Mapa(MapaConfig),
+    Calendario(CalendarioConfig),
+    Notas(NotasConfig),
+}
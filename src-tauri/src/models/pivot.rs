@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+// Configuración de tabla dinámica, guardada en `config.pivote` de un panel tabla
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PivotConfig {
+    pub filas: Vec<String>,
+    #[serde(default)]
+    pub columnas: Vec<String>,
+    pub medida: Option<String>,     // No requerida para 'count'
+    pub agregacion: Option<String>, // 'sum' (por defecto), 'avg', 'count', 'min', 'max'
+    #[serde(default)]
+    pub subtotales: bool,
+    #[serde(default)]
+    pub totales: bool,
+    pub porcentaje: Option<String>, // 'fila', 'columna', 'total'
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PivotGrid {
+    pub dimensiones_filas: Vec<String>,
+    pub dimensiones_columnas: Vec<String>,
+    pub medida: Option<String>,
+    pub agregacion: String,
+    pub porcentaje: Option<String>,
+    pub columnas: Vec<PivotHeader>,
+    pub filas: Vec<PivotRow>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PivotHeader {
+    pub claves: Vec<String>,
+    pub tipo: String, // 'valor', 'subtotal', 'total'
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PivotRow {
+    pub claves: Vec<String>,
+    pub tipo: String, // 'valor', 'subtotal', 'total'
+    pub valores: Vec<Option<f64>>,
+}
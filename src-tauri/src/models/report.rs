@@ -0,0 +1,14 @@
+use serde::Serialize;
+
+// Archivo generado al exportar un board
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardReport {
+    pub board_id: String,
+    pub ruta: String,
+    pub formato: String, // 'svg', 'png', 'pdf'
+    pub paginas: usize,  // Siempre 1 salvo en PDF
+    pub ancho: u32,
+    pub alto: u32,
+    pub generado_at: String,
+}
@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+// Reporte de un board generado periódicamente en una carpeta local
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportSchedule {
+    pub id: String,
+    pub user_id: String,
+    pub board_id: String,
+    pub nombre: String,
+    pub formato: String,      // 'svg', 'png', 'pdf'
+    pub programacion: String, // Cron: minuto hora día-del-mes mes día-de-la-semana
+    pub directorio: String,
+    pub activo: bool,
+    pub ultima_ejecucion: Option<String>,
+    pub proxima_ejecucion: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportRun {
+    pub id: String,
+    pub schedule_id: String,
+    pub estado: String, // 'exito', 'error'
+    pub ruta: Option<String>,
+    pub mensaje: Option<String>,
+    pub manual: bool,
+    pub iniciada_at: String,
+    pub finalizada_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateReportScheduleDto {
+    pub board_id: String,
+    pub nombre: String,
+    pub formato: String,
+    pub programacion: String,
+    pub directorio: String,
+}
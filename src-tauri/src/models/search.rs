@@ -0,0 +1,14 @@
+use serde::Serialize;
+use sqlx::FromRow;
+
+// Resultado de la búsqueda de texto completo
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub tipo: String, // 'board', 'panel', 'dataset'
+    pub id: String,
+    pub board_id: Option<String>, // Board al que navegar; para datasets, uno que lo use
+    pub titulo: String,
+    pub fragmento: String, // Extracto con las coincidencias entre <mark></mark>
+    pub puntuacion: f64,   // Mayor = más relevante
+}
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 #[serde(rename_all = "camelCase")]
@@ -9,6 +10,8 @@ pub struct GlobalDataset {
     pub tipo: String,
     #[sqlx(skip)]
     pub columnas: Vec<String>,
+    #[sqlx(skip)]
+    pub tipos_columna: HashMap<String, String>, // Tipo declarado de cada columna
     pub created_at: String,
     pub updated_at: String,
     #[sqlx(skip)]
@@ -21,6 +24,7 @@ pub struct GlobalDatasetDb {
     pub nombre: String,
     pub tipo: String,
     pub columnas: String, // JSON string
+    pub tipos_columna: String, // JSON object
     pub created_at: String,
     pub updated_at: String,
 }
@@ -39,6 +43,8 @@ pub struct CreateDatasetDto {
     pub nombre: String,
     pub tipo: String,
     pub columnas: Vec<String>,
+    #[serde(default)]
+    pub tipos_columna: HashMap<String, String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -46,4 +52,184 @@ pub struct CreateDatasetDto {
 pub struct AddDatasetDataDto {
     pub dataset_id: String,
     pub data: serde_json::Value,
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DatasetDescription {
+    pub dataset_id: String,
+    pub nombre: String,
+    pub filas: usize,
+    pub columnas: Vec<ColumnDescription>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnDescription {
+    pub columna: String,
+    pub tipo: String, // 'numerico', 'fecha', 'texto', 'vacio'
+    pub count: usize, // Valores no nulos
+    pub nulos: usize,
+    pub distintos: usize,
+    pub numerico: Option<NumericSummary>,
+    pub fecha: Option<DateSummary>,
+    pub texto: Option<TextSummary>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NumericSummary {
+    pub min: f64,
+    pub max: f64,
+    pub media: f64,
+    pub mediana: f64,
+    pub desviacion_estandar: f64,
+    pub q1: f64,
+    pub q3: f64,
+    pub histograma: Vec<HistogramBin>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistogramBin {
+    pub desde: f64,
+    pub hasta: f64,
+    pub conteo: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DateSummary {
+    pub min: String,
+    pub max: String,
+    pub rango_dias: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextSummary {
+    pub frecuencias: Vec<FrequencyEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrequencyEntry {
+    pub valor: String,
+    pub conteo: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorrelationMatrix {
+    pub columnas: Vec<String>,
+    pub pearson: Vec<Vec<Option<f64>>>,
+    pub spearman: Vec<Vec<Option<f64>>>,
+    pub observaciones: Vec<Vec<usize>>, // Pares completos usados en cada celda
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinearRegressionDto {
+    pub dataset_id: String,
+    pub columna_y: String,
+    pub columnas_x: Vec<String>,
+    pub nivel_confianza: Option<f64>, // Por defecto 0.95
+    pub predecir: Option<Vec<Vec<f64>>>, // Puntos x para los que calcular predicción
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinearRegressionResult {
+    pub columna_y: String,
+    pub columnas_x: Vec<String>,
+    pub n: usize,
+    pub intercepto: f64,
+    pub coeficientes: Vec<RegressionCoefficient>,
+    pub r2: f64,
+    pub r2_ajustado: f64,
+    pub error_estandar_residual: f64,
+    pub ajustados: Vec<f64>,
+    pub residuos: Vec<f64>,
+    pub predicciones: Vec<RegressionPrediction>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegressionCoefficient {
+    pub columna: String,
+    pub valor: f64,
+    pub error_estandar: f64,
+    pub t: Option<f64>, // Sin valor si el ajuste es exacto (error estándar nulo)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegressionPrediction {
+    pub x: Vec<f64>,
+    pub valor: f64,
+    pub inferior: f64,
+    pub superior: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForecastDto {
+    pub dataset_id: String,
+    pub columna_fecha: String,
+    pub columna_valor: String,
+    pub periodos: usize,
+    pub metodo: Option<String>, // 'holt_winters' (por defecto) o 'tendencia_estacional'
+    pub estacionalidad: Option<usize>, // Periodos por ciclo; se infiere si no se indica
+    pub nivel_confianza: Option<f64>, // Por defecto 0.95
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForecastResult {
+    pub metodo: String,
+    pub estacionalidad: Option<usize>,
+    pub parametros: serde_json::Value,
+    pub rmse: f64,
+    pub historico: Vec<ForecastPoint>,
+    pub pronostico: Vec<ForecastPoint>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForecastPoint {
+    pub fecha: String,
+    pub valor: f64,
+    pub inferior: Option<f64>,
+    pub superior: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnomalyDto {
+    pub dataset_id: String,
+    pub columna: String,
+    pub metodo: String, // 'zscore', 'iqr', 'mediana_movil'
+    pub umbral: Option<f64>,
+    pub ventana: Option<usize>, // Solo para 'mediana_movil'
+    pub columna_orden: Option<String>, // Orden de las filas; por defecto el de inserción
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnomalyResult {
+    pub columna: String,
+    pub metodo: String,
+    pub umbral: f64,
+    pub evaluadas: usize,
+    pub anomalias: Vec<Anomaly>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Anomaly {
+    pub id: String, // Fila de dataset_data
+    pub fila: serde_json::Value,
+    pub valor: f64,
+    pub esperado: f64,
+    pub puntuacion: f64,
+}